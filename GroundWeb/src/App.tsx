@@ -325,7 +325,7 @@ function App() {
           const manifest = new ManifestBuilder()
           .withdrawFromAccountByAmount(accountAddress!, amount, StableCoin)
           .takeFromWorktop(StableCoin, 'bucket')
-          .callMethod(GroundLendingComponent, 'new_lending_account', [`Bucket("bucket")`])
+          .callMethod(GroundLendingComponent, 'new_lending_account', [`Bucket("bucket")`, `Enum("Flexible")`])
           .callMethodWithAllResources(accountAddress!, 'deposit_batch')
           .build()
           .toString();
@@ -33,6 +33,7 @@ The service also help the DAO aggregrate the CV id score for the quartic voting
 
 - The Oracle solution is for the DAO to keep track of member, voting delegator's committed time for the DAO, 
 make sure they cannot withdraw their stake from the DAO until the committed time.
+The time can also be read through a GroundTimeSource component, which validate the time before the DAO use it.
 
 - The Liquid Democracy Service is for the DAO's member to make their own voting pool 
 where user who don't meet the member entry requirement can also contribute to the DAO.
@@ -63,6 +64,7 @@ where user who don't meet the member entry requirement can also contribute to th
 
 use scrypto::prelude::*;
 use neuracle::neuracle::*;
+use crate::ground_time_source::*;
 use ground_id::{Identity, GroundID};
 use crate::utils::*;
 use crate::cv_id_const::*;
@@ -253,6 +255,8 @@ blueprint! {
         total_voting_power: Decimal,
         /// The on-using Oracle
         oracle: (ComponentAddress, Vault),
+        /// The on-using time source, None if the DAO read the time directly from the oracle.
+        time_source: Option<ComponentAddress>,
         /// The controller badge of the protocols that this DAO is running
         protocols: Vec<ResourceAddress>,
         /// The controller badge resource address of the DAO's components 
@@ -355,6 +359,7 @@ blueprint! {
 
             let rules = AccessRules::new()
                 .method("use_oracle", rule!(require(dao_badge.resource_address())))
+                .method("use_time_source", rule!(require(dao_badge.resource_address())))
                 .method("withdraw", rule!(require(dao_badge.resource_address())))
                 .method("add_protocol", rule!(require(dao_badge.resource_address())))
                 .method("remove_protocol", rule!(require(dao_badge.resource_address())))
@@ -403,6 +408,7 @@ blueprint! {
                 unstake_delay: unstake_delay,
                 total_voting_power: Decimal::ZERO,
                 oracle: (oracle.0, Vault::with_bucket(oracle.1)),
+                time_source: None,
                 protocols: protocols,
                 dao_controllers: Vec::from([controller_badge_address])
 
@@ -446,6 +452,11 @@ blueprint! {
             bucket
         }
 
+        /// This method is for the DAO to change the time source using, the DAO's controller badge must be listed on the time source.
+        pub fn use_time_source(&mut self, time_source: Option<ComponentAddress>) {
+            self.time_source = time_source
+        }
+
         /// This method is for the DAO to refund the oracle account.
         pub fn refund_oracle_account(&self, bucket: Bucket) -> Bucket {
             let neuracle: NeuRacle = self.oracle.0.into();
@@ -1011,10 +1022,18 @@ blueprint! {
         pub fn current(&self, controller_proof: Proof) -> u64 {
             assert!(controller_proof.resource_address() == self.controller_badge.resource_address(), "Wrong proof!");
             controller_proof.drop();
-            let neuracle: NeuRacle = self.oracle.0.into();
-            let data_proof = self.oracle.1.create_proof();
-            let current = neuracle.get_data(data_proof);
-            current.parse().expect("Wrong data!")
+            match self.time_source {
+                Some(time_source) => {
+                    let time_source: GroundTimeSource = time_source.into();
+                    time_source.current(self.controller_badge.create_proof())
+                }
+                None => {
+                    let neuracle: NeuRacle = self.oracle.0.into();
+                    let data_proof = self.oracle.1.create_proof();
+                    let current = neuracle.get_data(data_proof);
+                    current.parse().expect("Wrong data!")
+                }
+            }
         }
 
         pub fn change_voting_pool_fee(&mut self, new_fee: Decimal) {
@@ -0,0 +1,59 @@
+//! # [GroundClock]: A local clock for Ground protocols.
+//! GroundClock is a simple admin-set clock for testing and private deployments, where running a NeuRacle oracle is not needed.
+//!
+//! ## Main Features:
+//! The blueprint keep a unix time set by the clock operator, the time is read through the GroundTimeSource blueprint
+//! which validate the time before any Ground protocol use it.
+//!
+//! ## Protocol entities:
+//! 1. **Clock operator**: Main manager of the clock. Through the blueprint's method, *clock operator* is allowed to:
+//! - Set the clock's time.
+//!
+//! 2. **Time sources**: Any component can read the clock's time.
+
+use scrypto::prelude::*;
+
+blueprint! {
+
+    struct GroundClock {
+
+        /// The clock's time. (unix)
+        time: u64
+
+    }
+
+    impl GroundClock {
+
+        /// This function will create new GroundClock component
+        /// ### Input:
+        /// - admin_badge: the clock operator's badge address.
+        /// - time: the initial time. (unix)
+        /// ### Output:
+        /// The clock component address.
+        pub fn new(admin_badge: ResourceAddress, time: u64) -> ComponentAddress {
+
+            let rules = AccessRules::new()
+                .method("set_time", rule!(require(admin_badge)))
+                .default(rule!(allow_all));
+
+            Self {
+                time: time
+            }
+            .instantiate()
+            .add_access_check(rules)
+            .globalize()
+
+        }
+
+        /// This method is for the clock operator to set the clock's time.
+        pub fn set_time(&mut self, time: u64) {
+            self.time = time;
+            info!("The clock's time has been set to {} (unix time)", time);
+        }
+
+        /// Read only method to get the clock's time.
+        pub fn get_time(&self) -> u64 {
+            self.time
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! # [GroundTimeSource]: A validated time for Ground protocols.
+//! GroundTimeSource is the shared time source for Ground protocols (GroundLending, GroundBusinessDAO,...).
+//!
+//! ## Main Features:
+//! The blueprint read the unix time from a NeuRacle oracle in production, or from a local GroundClock component for testing and private deployments.
+//!
+//! Before any protocol use the time, the blueprint validate it:
+//! - The time must be a valid unix time.
+//! - The time must not move backward from the last accepted time (the initial time set by the time source operator on the first read).
+//! - The time must not jump implausibly far into the future, counted by the ledger epochs passed since the last accepted time.
+//! - The time must not stay unchanged for too many ledger epochs (stale data).
+//!
+//! The last accepted time is cached on the component.
+//!
+//! ## Protocol entities:
+//! 1. **Time source operator**: Main manager of the time source. Through the blueprint's method, *time source operator* is allowed to:
+//! - Add, remove protocols which can use the time source.
+//! - Change the time source's validation policy.
+//! - Reset the last accepted time, in case the time feed has been rejected for too long.
+//! - Refund the NeuRacle account.
+//!
+//! 2. **Protocols**: Listed protocols can get the validated time with their controller badge proof.
+
+use scrypto::prelude::*;
+use neuracle::neuracle::*;
+use crate::ground_clock::*;
+
+/// The time feed of the time source.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub enum TimeFeed {
+    /// The NeuRacle component address and the time data badge.
+    NeuRacle(ComponentAddress, Vault),
+    /// The local GroundClock component address.
+    Clock(ComponentAddress)
+}
+
+/// The validation policy of the time source.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct TimePolicy {
+
+    /// The maximum ledger epochs the time can stay unchanged before it's considered stale.
+    pub max_stale_epochs: u64,
+    /// The maximum seconds the time can move forward per ledger epoch passed since the last accepted time.
+    pub max_forward_per_epoch: u64
+
+}
+
+impl TimePolicy {
+
+    pub fn check_policy(&self) {
+        assert!(self.max_stale_epochs > 0 && self.max_forward_per_epoch > 0, "Wrong data!");
+    }
+}
+
+blueprint! {
+
+    struct GroundTimeSource {
+
+        /// The time feed.
+        feed: TimeFeed,
+        /// The validation policy.
+        policy: TimePolicy,
+        /// The last accepted time. (unix)
+        last_time: u64,
+        /// The ledger epoch the last accepted time has changed.
+        last_epoch: u64,
+        /// The controller badge addresses of protocols which can use the time source.
+        protocols: Vec<ResourceAddress>
+
+    }
+
+    impl GroundTimeSource {
+
+        /// This function will create new GroundTimeSource component reading from a NeuRacle oracle.
+        /// ### Input:
+        /// - admin_badge: the time source operator's badge address.
+        /// - neuracle: the NeuRacle component address.
+        /// - data_badge: the NeuRacle unix time data badge.
+        /// - policy: the validation policy.
+        /// - initial_time: the initial accepted time, the first time read is validated against it. (unix)
+        /// ### Output:
+        /// The time source component address.
+        pub fn new_neuracle(admin_badge: ResourceAddress, neuracle: ComponentAddress, data_badge: Bucket, policy: TimePolicy, initial_time: u64) -> ComponentAddress {
+            Self::new(admin_badge, TimeFeed::NeuRacle(neuracle, Vault::with_bucket(data_badge)), policy, initial_time)
+        }
+
+        /// This function will create new GroundTimeSource component reading from a local GroundClock component.
+        /// ### Input:
+        /// - admin_badge: the time source operator's badge address.
+        /// - clock: the GroundClock component address.
+        /// - policy: the validation policy.
+        /// - initial_time: the initial accepted time, the first time read is validated against it. (unix)
+        /// ### Output:
+        /// The time source component address.
+        pub fn new_clock(admin_badge: ResourceAddress, clock: ComponentAddress, policy: TimePolicy, initial_time: u64) -> ComponentAddress {
+            Self::new(admin_badge, TimeFeed::Clock(clock), policy, initial_time)
+        }
+
+        fn new(admin_badge: ResourceAddress, feed: TimeFeed, policy: TimePolicy, initial_time: u64) -> ComponentAddress {
+
+            policy.check_policy();
+
+            assert!(initial_time > 0, "Wrong data!");
+
+            let rules = AccessRules::new()
+                .method("add_protocol", rule!(require(admin_badge)))
+                .method("remove_protocol", rule!(require(admin_badge)))
+                .method("change_policy", rule!(require(admin_badge)))
+                .method("reset", rule!(require(admin_badge)))
+                .method("refund_oracle_account", rule!(require(admin_badge)))
+                .default(rule!(allow_all));
+
+            Self {
+                feed: feed,
+                policy: policy,
+                last_time: initial_time,
+                last_epoch: Runtime::current_epoch(),
+                protocols: Vec::new()
+            }
+            .instantiate()
+            .add_access_check(rules)
+            .globalize()
+
+        }
+
+        /// This method is for listed protocols to get the validated current time.
+        ///
+        /// Input: the protocol's controller badge proof.
+        ///
+        /// Output: the current time. (unix)
+        pub fn current(&mut self, protocol_proof: Proof) -> u64 {
+
+            assert!(self.protocols.contains(&protocol_proof.resource_address()), "This protocol is not allowed to use the time source.");
+
+            protocol_proof.drop();
+
+            let time = self.read_feed();
+
+            let epoch = Runtime::current_epoch();
+
+            assert!(time >= self.last_time, "The time feed moved backward from {} to {}, the time is rejected.", self.last_time, time);
+
+            let epochs = epoch - self.last_epoch;
+
+            assert!(
+                time - self.last_time <= self.policy.max_forward_per_epoch * (epochs + 1),
+                "The time feed jumped {} seconds in {} ledger epochs, the time is rejected.", time - self.last_time, epochs
+            );
+
+            assert!(
+                time != self.last_time || epochs <= self.policy.max_stale_epochs,
+                "The time feed hasn't changed for {} ledger epochs, the time is stale.", epochs
+            );
+
+            if time != self.last_time {
+                self.last_time = time;
+                self.last_epoch = epoch;
+            }
+
+            time
+
+        }
+
+        /// Read only method to get the last accepted time.
+        pub fn last_time(&self) -> u64 {
+            self.last_time
+        }
+
+        /// Read the raw time from the time feed.
+        fn read_feed(&self) -> u64 {
+            match &self.feed {
+                TimeFeed::NeuRacle(neuracle, data_badge) => {
+                    let neuracle: NeuRacle = (*neuracle).into();
+                    let data = neuracle.get_data(data_badge.create_proof());
+                    match data.parse() {
+                        Ok(time) => time,
+                        Err(_) => panic!("The time feed returned a bad value: {}, the time is rejected.", data)
+                    }
+                }
+                TimeFeed::Clock(clock) => {
+                    let clock: GroundClock = (*clock).into();
+                    clock.get_time()
+                }
+            }
+        }
+
+        /// This method is for the time source operator to allow a protocol to use the time source.
+        ///
+        /// Input: The protocol's controller badge address.
+        pub fn add_protocol(&mut self, protocol_controller_address: ResourceAddress) {
+            self.protocols.push(protocol_controller_address);
+            info!("listed the protocol with controller badge address {}", protocol_controller_address);
+        }
+
+        /// This method is for the time source operator to deny a protocol.
+        ///
+        /// Input: The protocol's controller badge address.
+        pub fn remove_protocol(&mut self, protocol_controller_address: ResourceAddress) {
+            self.protocols.retain(|x| *x != protocol_controller_address);
+        }
+
+        pub fn change_policy(&mut self, policy: TimePolicy) {
+            policy.check_policy();
+            self.policy = policy
+        }
+
+        /// This method is for the time source operator to reset the last accepted time.
+        ///
+        /// This is to recover the time source when the time feed has been rejected for too long (a long downtime or a wrong feed fixed later), 
+        /// the next time read is validated against the new time.
+        ///
+        /// Input: the new last accepted time. (unix)
+        pub fn reset(&mut self, time: u64) {
+            assert!(time > 0, "Wrong data!");
+            self.last_time = time;
+            self.last_epoch = Runtime::current_epoch();
+            info!("The time source has been reset to {} (unix time)", time);
+        }
+
+        /// This method is for the time source operator to refund the NeuRacle account.
+        pub fn refund_oracle_account(&self, bucket: Bucket) -> Bucket {
+            match &self.feed {
+                TimeFeed::NeuRacle(neuracle, data_badge) => {
+                    let neuracle: NeuRacle = (*neuracle).into();
+                    neuracle.refund_account(data_badge.create_proof(), bucket)
+                }
+                TimeFeed::Clock(_) => panic!("The time source doesn't use a NeuRacle oracle.")
+            }
+        }
+    }
+}
@@ -3,5 +3,7 @@ mod cv_id_const;
 pub mod ground_cv;
 pub mod ground_business_dao;
 pub mod ground_voting_pool;
+pub mod ground_clock;
+pub mod ground_time_source;
 mod utils;
 
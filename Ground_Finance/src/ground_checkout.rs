@@ -0,0 +1,416 @@
+//! # [GroundCheckout]: Buy now, pay later with your Web 3 Credit!
+//!
+//! Ground Checkout is the merchant-facing blueprint of the Ground Finance package,
+//! allow borrowers to pay merchants directly from their revolving credit on a GroundLending protocol.
+//!
+//! ## Main Features
+//!
+//! The blueprint is for web3 organizations to instantiate and manage a "buy now, pay later" checkout on-chain.
+//!
+//! The checkout component has to be listed on the GroundLending protocol to take the revolving loan on behalf of borrowers.
+//!
+//! The purchase is booked as the borrower's revolving debt on the lending protocol, the borrower then repay the debt as usual.
+//!
+//! ## Protocol Entities:
+//! 1. **Checkout Operator**: Main manager of the checkout. Through the blueprint's method, *checkout operator* is allowed to:
+//! - Register new merchants.
+//!
+//! 2. **Merchants**: Registered merchants with the Merchant Badge. Through the blueprint's method, *merchants* are allowed to:
+//! - Create (or cancel) an invoice for a buyer.
+//! - Settle the paid invoice and get the payment with a settlement receipt NFT.
+//! - Refund the purchase with the settlement receipt NFT, the refund will reverse the purchase's own debt and interest.
+//!
+//! 3. **Buyers**: Permissioned wallet address (require ID SBT and Credit SBT). Through the blueprint's method, *buyers* are allowed to:
+//! - Pay the invoice with their revolving credit.
+//! - Take the refund remainder (in case they had repaid part or all of the purchase before the refund).
+
+use scrypto::prelude::*;
+use ground_id::Identity;
+use crate::ground_credit::Credit;
+use crate::ground_lending::*;
+
+/// The NFT keep track of a registered merchant.
+#[derive(NonFungibleData)]
+pub struct Merchant {
+    /// The merchant's name.
+    pub name: String
+}
+
+/// The NFT proves the merchant has settled a purchase paid by the borrower's credit.
+///
+/// The merchant need this NFT to refund the purchase.
+#[derive(NonFungibleData)]
+pub struct Settlement {
+    /// The invoice id.
+    pub invoice_id: u64,
+    /// The merchant's badge ID.
+    pub merchant: NonFungibleId,
+    /// The buyer's Identity SBT ID.
+    pub buyer: NonFungibleId,
+    /// The purchase amount.
+    pub amount: Decimal,
+    /// The interest rate booked on the purchase.
+    pub interest_rate: Decimal,
+    /// The start time of the buyer's debt the purchase is booked on.
+    pub debt_start: u64
+}
+
+/// The status of an invoice.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
+pub enum InvoiceStatus {
+    Open,
+    Paid,
+    Settled,
+    Refunded,
+    Cancelled
+}
+
+/// The struct store an invoice data.
+#[derive(TypeId, Encode, Decode, Describe, Clone)]
+pub struct Invoice {
+    /// The merchant's badge ID.
+    pub merchant: NonFungibleId,
+    /// The purchase amount.
+    pub amount: Decimal,
+    /// The buyer's Identity SBT ID. None if any permissioned buyer can pay the invoice.
+    pub buyer: Option<NonFungibleId>,
+    /// The invoice status.
+    pub status: InvoiceStatus,
+    /// The interest rate booked on the purchase (0 before the invoice is paid).
+    pub interest_rate: Decimal,
+    /// The start time of the buyer's debt the purchase is booked on (0 before the invoice is paid).
+    pub debt_start: u64
+}
+
+blueprint! {
+
+    struct GroundCheckout {
+
+        /// Component controller badge, the badge need to be listed on the lending protocol.
+        controller_badge: Vault,
+        /// The merchant badge address.
+        merchant_badge: ResourceAddress,
+        /// The settlement receipt NFT address.
+        settlement_receipt: ResourceAddress,
+        /// The lending protocol the checkout is using.
+        lending: ComponentAddress,
+        /// The stable coin address used on the lending protocol.
+        stablecoin: ResourceAddress,
+        /// The map keep track of invoices.
+        ///
+        /// **Syntax**:
+        /// ```HashMap<invoice_id, invoice_data>```
+        invoices: HashMap<u64, Invoice>,
+        /// Invoice id counter
+        invoice_id_counter: u64,
+        /// The vaults keep the payments of paid invoices before the merchants settle.
+        ///
+        /// **Syntax**:
+        /// ```HashMap<invoice_id, payment>```
+        payments: HashMap<u64, Vault>,
+        /// The vaults keep the refund remainders for buyers.
+        ///
+        /// **Syntax**:
+        /// ```HashMap<buyer_id_sbt_id, refund>```
+        refunds: HashMap<NonFungibleId, Vault>
+
+    }
+
+    impl GroundCheckout {
+
+        /// This function will create new GroundCheckout component.
+        ///
+        /// ### Input:
+        /// - name: the organization's name.
+        /// - admin_badge: the organization admin badge.
+        /// - lending: the GroundLending component address.
+        /// - stablecoin: the stable coin address used on the lending protocol.
+        /// ### Output: Component address and the controller badge resource address (to be listed on the lending protocol).
+        pub fn new(name: String, admin_badge: ResourceAddress, lending: ComponentAddress, stablecoin: ResourceAddress) -> (ComponentAddress, ResourceAddress) {
+
+            let controller_badge = ResourceBuilder::new_fungible()
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata("name", name.clone() + "'s Checkout Controller Badge")
+                .initial_supply(dec!(1isize));
+
+            let merchant_badge = ResourceBuilder::new_non_fungible()
+                .metadata("name", name.clone() + "'s Merchant Badge")
+                .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
+            let settlement_receipt = ResourceBuilder::new_non_fungible()
+                .metadata("name", name.clone() + "'s Settlement Receipt")
+                .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
+            let rules = AccessRules::new()
+                .method("register_merchant", rule!(require(admin_badge)))
+                .default(rule!(allow_all));
+
+            let controller_badge_resource_address = controller_badge.resource_address();
+
+            let comp = Self {
+
+                controller_badge: Vault::with_bucket(controller_badge),
+                merchant_badge: merchant_badge,
+                settlement_receipt: settlement_receipt,
+                lending: lending,
+                stablecoin: stablecoin,
+                invoices: HashMap::new(),
+                invoice_id_counter: 0,
+                payments: HashMap::new(),
+                refunds: HashMap::new()
+
+            }
+            .instantiate()
+            .add_access_check(rules)
+            .globalize();
+
+            return (comp, controller_badge_resource_address)
+        }
+
+        /// This method is for the checkout operator to register a new merchant.
+        ///
+        /// Input: The merchant's name.
+        ///
+        /// Output: The Merchant Badge.
+        pub fn register_merchant(&mut self, name: String) -> Bucket {
+
+            info!("Registered the merchant {}", name);
+
+            self.controller_badge.authorize(|| {
+                borrow_resource_manager!(self.merchant_badge)
+                    .mint_non_fungible(
+                        &NonFungibleId::random(),
+                        Merchant { name }
+                )
+            })
+        }
+
+        /// This method is for merchants to create an invoice.
+        /// ### Input:
+        /// - merchant_proof: the Merchant Badge proof.
+        /// - amount: the purchase amount.
+        /// - buyer: the buyer's Identity SBT ID (None if any permissioned buyer can pay the invoice).
+        /// ### Output:
+        /// The invoice id.
+        pub fn create_invoice(&mut self, merchant_proof: Proof, amount: Decimal, buyer: Option<NonFungibleId>) -> u64 {
+
+            assert!(merchant_proof.resource_address() == self.merchant_badge, "Wrong resource.");
+
+            assert!(amount > Decimal::ZERO, "Wrong data provided!");
+
+            let merchant = merchant_proof.non_fungible::<Merchant>().id();
+
+            self.invoice_id_counter += 1;
+
+            self.invoices.insert(self.invoice_id_counter, Invoice {
+                merchant,
+                amount,
+                buyer,
+                status: InvoiceStatus::Open,
+                interest_rate: Decimal::ZERO,
+                debt_start: 0
+            });
+
+            info!("Created the invoice id {} with amount {} stable coins", self.invoice_id_counter, amount);
+
+            self.invoice_id_counter
+
+        }
+
+        /// This method is for merchants to cancel an open invoice.
+        /// ### Input:
+        /// - merchant_proof: the Merchant Badge proof.
+        /// - invoice_id: the invoice id.
+        pub fn cancel_invoice(&mut self, merchant_proof: Proof, invoice_id: u64) {
+
+            let invoice = self.get_merchant_invoice(merchant_proof, invoice_id);
+
+            assert!(invoice.status == InvoiceStatus::Open, "This invoice cannot be cancelled.");
+
+            invoice.status = InvoiceStatus::Cancelled;
+
+            info!("Cancelled the invoice id {}", invoice_id);
+
+        }
+
+        /// This method is for buyers to pay an invoice with their revolving credit.
+        ///
+        /// The purchase will be booked as the buyer's revolving debt on the lending protocol.
+        /// ### Input:
+        /// - id_proof: the Identity SBT proof.
+        /// - credit_proof: the Credit SBT proof.
+        /// - invoice_id: the invoice id.
+        pub fn pay_invoice(&mut self, id_proof: Proof, credit_proof: Proof, invoice_id: u64) {
+
+            let (id, id_resource) = (id_proof.non_fungible::<Identity>().id(), id_proof.resource_address());
+
+            let (credit_id, credit_resource) = (credit_proof.non_fungible::<Credit>().id(), credit_proof.resource_address());
+
+            id_proof.drop(); credit_proof.drop();
+
+            let invoice = self.invoices.get_mut(&invoice_id).expect("Wrong invoice id.");
+
+            assert!(invoice.status == InvoiceStatus::Open, "This invoice cannot be paid.");
+
+            if let Some(buyer) = &invoice.buyer {
+                assert!(*buyer == id, "This invoice is not for you.");
+            }
+
+            let lending: GroundLending = self.lending.into();
+
+            let (payment, interest_rate, debt_start) = lending.checkout_credit(self.controller_badge.create_proof(), id.clone(), id_resource, credit_id, credit_resource, invoice.amount);
+
+            invoice.buyer = Some(id);
+
+            invoice.status = InvoiceStatus::Paid;
+
+            invoice.interest_rate = interest_rate;
+
+            invoice.debt_start = debt_start;
+
+            info!("You have paid the invoice id {} with {} stable coins from your credit", invoice_id, invoice.amount);
+
+            self.payments.insert(invoice_id, Vault::with_bucket(payment));
+
+        }
+
+        /// This method is for merchants to settle a paid invoice.
+        /// ### Input:
+        /// - merchant_proof: the Merchant Badge proof.
+        /// - invoice_id: the invoice id.
+        /// ### Output:
+        /// The payment and the settlement receipt NFT.
+        pub fn settle_invoice(&mut self, merchant_proof: Proof, invoice_id: u64) -> (Bucket, Bucket) {
+
+            let merchant = merchant_proof.non_fungible::<Merchant>().id();
+
+            let invoice = self.get_merchant_invoice(merchant_proof, invoice_id);
+
+            assert!(invoice.status == InvoiceStatus::Paid, "This invoice is not paid.");
+
+            invoice.status = InvoiceStatus::Settled;
+
+            let settlement = Settlement {
+                invoice_id,
+                merchant,
+                buyer: invoice.buyer.clone().unwrap(),
+                amount: invoice.amount,
+                interest_rate: invoice.interest_rate,
+                debt_start: invoice.debt_start
+            };
+
+            let payment = self.payments.get_mut(&invoice_id).unwrap().take_all();
+
+            info!("Settled the invoice id {}, you received {} stable coins", invoice_id, payment.amount());
+
+            let receipt = self.controller_badge.authorize(|| {
+                borrow_resource_manager!(self.settlement_receipt)
+                    .mint_non_fungible(
+                        &NonFungibleId::random(),
+                        settlement
+                )
+            });
+
+            (payment, receipt)
+
+        }
+
+        /// This method is for merchants to refund a settled purchase.
+        ///
+        /// The refund will reverse the purchase's principal and the interest booked on it from the buyer's debt on the lending protocol.
+        /// If the buyer has repaid part (or all) of the purchase, the remainder will be kept for the buyer.
+        /// ### Input:
+        /// - receipt: the settlement receipt NFT.
+        /// - refund: the refund bucket.
+        /// ### Output:
+        /// The amount exceeded the purchase amount.
+        pub fn refund(&mut self, receipt: Bucket, mut refund: Bucket) -> Bucket {
+
+            assert!(receipt.resource_address() == self.settlement_receipt, "Wrong resource.");
+
+            assert!(refund.resource_address() == self.stablecoin, "Wrong resource.");
+
+            let settlement = receipt.non_fungible::<Settlement>().data();
+
+            assert!(refund.amount() >= settlement.amount, "You have to refund the full purchase amount.");
+
+            let refund_amount = refund.take(settlement.amount);
+
+            let lending: GroundLending = self.lending.into();
+
+            let remain = lending.refund_checkout_credit(self.controller_badge.create_proof(), settlement.buyer.clone(), settlement.interest_rate, settlement.debt_start, refund_amount);
+
+            if remain.amount() > Decimal::ZERO {
+
+                info!("{} stable coins will be kept for the buyer since the buyer has repaid part (or all) of the purchase.", remain.amount());
+
+                match self.refunds.get_mut(&settlement.buyer) {
+                    Some(vault) => {vault.put(remain)}
+                    None => {self.refunds.insert(settlement.buyer.clone(), Vault::with_bucket(remain));}
+                }
+
+            } else {
+                refund.put(remain)
+            }
+
+            self.invoices.get_mut(&settlement.invoice_id).unwrap().status = InvoiceStatus::Refunded;
+
+            self.controller_badge.authorize(|| {
+                receipt.burn()
+            });
+
+            info!("Refunded the invoice id {}", settlement.invoice_id);
+
+            refund
+
+        }
+
+        /// This method is for buyers to take their refund remainder.
+        ///
+        /// Input: the Identity SBT proof.
+        ///
+        /// Output: the refund remainder.
+        pub fn claim_refund(&mut self, id_proof: Proof) -> Bucket {
+
+            let lending: GroundLending = self.lending.into();
+
+            assert!(id_proof.resource_address() == lending.identity_sbt_address(), "Wrong resource.");
+
+            let id = id_proof.non_fungible::<Identity>().id();
+
+            id_proof.drop();
+
+            let bucket = self.refunds.get_mut(&id).expect("You don't have any refund.").take_all();
+
+            info!("You have claimed {} stable coins refund", bucket.amount());
+
+            bucket
+
+        }
+
+        /// Read method to get the invoice data.
+        pub fn get_invoice(&self, invoice_id: u64) -> Invoice {
+            self.invoices.get(&invoice_id).expect("Wrong invoice id.").clone()
+        }
+
+        fn get_merchant_invoice(&mut self, merchant_proof: Proof, invoice_id: u64) -> &mut Invoice {
+
+            assert!(merchant_proof.resource_address() == self.merchant_badge, "Wrong resource.");
+
+            let merchant = merchant_proof.non_fungible::<Merchant>().id();
+
+            merchant_proof.drop();
+
+            let invoice = self.invoices.get_mut(&invoice_id).expect("Wrong invoice id.");
+
+            assert!(invoice.merchant == merchant, "This is not your invoice.");
+
+            invoice
+
+        }
+    }
+}
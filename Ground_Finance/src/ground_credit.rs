@@ -15,28 +15,51 @@
 //! 1. **Credit service operator**: Main manager of the protocol. Through the blueprint's method, *Credit service operator* is allowed to:
 //! - Issue new Credit SBT for users (for user who wish to migrate his off-chain credit history). (Require off-chain process)
 //! - Review installment credit request. (Require off-chain process)
+//! - Revoke an outstanding installment credit badge and change the time period user has to take the installment loan.
 //! - List, delist a lending protocol to use the Credit service. (Require off-chain process if the protocols weren't run by the same entity)
+//! - Set each protocol's capabilities (revolving, installment, may degrade) and aggregate exposure cap, or wind a protocol down so its borrowers can only repay.
 //! - Blacklist, whitelist credit users who have issue with the ID SBT (wrong income, trust score) or have a large loan default. (Require off-chain process)
+//! - Set an expiry on blacklist entries so temporary restrictions automatically lapse.
+//! - Change the Oracle component address the credit service is using.
 //! - Change the credit degrade and restore rate when credit users have late (or on-time) repayment frequency.
+//! - Change the time-based credit score recovery model.
+//! - Change the business credit terms and link the Person owners underwriting a Business identity's credit. (Require off-chain process)
+//! - Trust (or untrust) other credit services for Credit SBT migration.
 //!
 //! Service operator is also required to protect user's private data.
 //!
 //! 2. **Credit users**: Verified unique identity on web3 who wish to use on-chain credit or take a loan. Through the blueprint's method, *Credit users* are allowed to:
 //! - Use the ID SBT to take new credit SBT.
 //! - Change credit type ("Monthly" or "Yearly") (Require no-debt credit status).
-//! - Check the maximum credit and current credit allowance.
+//! - Check the maximum credit and current credit allowance. Business identities' credit limit combines their own revenue with their owners' credit scores.
 //! - Request an installment credit.
 //! - Take the installment credit badge after the request has passed.
+//! - Pledge part of their own credit capacity to guarantee other user's credit line and release the guarantee when that user has no debt.
+//! - Migrate their Credit SBT from a trusted credit service (Require no-debt credit status).
 //!
 //! 3. **Lending protocols**: Listed lending protocols can use this blueprint for on-chain credit service. Through the blueprint's method, *Lending protocols* are allowed to:
 //! - Automatically evaluate user's credit score through late (or on-time) repayment frequency. 
 //! - Edit user's current debt or the credit's due time.
 //! - Let protocol users use the installment credit badge to change credit into installment type (Require no-debt credit status).
 //! - Let protocol users stop using installment credit and change the credit back into revolving type.
+//! - Call the guarantees of an overdue borrower to charge the shortfall on the guarantors' credit.
+//! - Blacklist credit users (the entry can have an expiry).
+//! - Apply the time-based credit score recovery on users' credit (active protocols only), the credit service reads the user's ID trust factor and the time from its own Oracle.
+//! - Degrade the owners' credit when a business's loan is declared defaulted.
+//! - Update users' Credit SBT within the protocol's capabilities and exposure cap. The credit service derives the lent out and repaid principal from the applied debt change, a protocol can only lower the debt it has lent out and defaulted debt stays on the protocol's exposure until it's recovered. Winding down protocols can only lower users' debt.
+//! - Record the principal of loans which are not lent out on the Credit SBT (delegated credit), the released principal is capped at the principal recorded on the borrower's credit.
 
 use scrypto::prelude::*;
+use neuracle::neuracle::*;
 use ground_id::*;
 
+/// Default time period user has to take the installment loan after the request has passed. (30 days)
+const INSTALLMENT_ACTIVATION_PERIOD: u64 = 60 * 60 * 24 * 30;
+/// Due period of a monthly revolving credit. (30 days)
+const MONTH: u64 = 60 * 60 * 24 * 30;
+/// Due period of a yearly revolving credit. (365 days)
+const YEAR: u64 = 60 * 60 * 24 * 365;
+
 /// The SBT keep track of an user's credit data. 
 /// 
 /// ## Uses:
@@ -104,7 +127,59 @@ pub struct CreditData {
     /// 
     /// Default repaid amount is 0.
     // #[scrypto(mutable)]
-    pub repaid_amount_accumulated: Decimal
+    pub repaid_amount_accumulated: Decimal,
+    /// The total amount other users has pledged to back this credit.
+    /// 
+    /// This amount is added into the user's maximum credit amount while the guarantees are active.
+    /// 
+    /// Default guaranteed amount is 0.
+    pub guaranteed_amount: Decimal,
+    /// The total amount this user has pledged to back other users' credit.
+    /// 
+    /// This amount is subtracted from the user's credit allowance while the guarantees are active.
+    /// 
+    /// Default pledged amount is 0.
+    pub pledged_amount: Decimal,
+    /// The credit is declared defaulted by a lending protocol or not.
+    /// 
+    /// A defaulted credit cannot take any new loan until the debt is fully recovered.
+    /// 
+    /// Default is false.
+    pub defaulted: bool,
+    /// The last time the credit score was checked for the time-based recovery. (unix)
+    /// 
+    /// The checkpoint is moved forward when the recovery is applied, or when the user is late on repayment.
+    pub score_checkpoint: u64,
+    /// The credit has been migrated to other credit service or not.
+    /// 
+    /// A migrated credit cannot be used anymore.
+    /// 
+    /// Default is false.
+    pub migrated: bool
+}
+
+impl CreditData {
+
+    /// Get the unpaid loan principal of the credit, including the installment loan's remaining periods.
+    pub fn outstanding_principal(&self) -> Decimal {
+        match self.credit_type {
+            CreditType::Installment(installment) if !self.defaulted && installment.period_counter < installment.period_max => {
+                self.current_debt + installment.total_loan / installment.period_max * (installment.period_max - installment.period_counter)
+            }
+            _ => {self.current_debt}
+        }
+    }
+
+    /// Get the unpaid debt of the credit, including the installment loan's remaining periods and their interest.
+    pub fn outstanding_debt(&self) -> Decimal {
+        let scheduled_interest = match self.credit_type {
+            CreditType::Installment(installment) if !self.defaulted && installment.period_counter < installment.period_max => {
+                installment.total_loan / installment.period_max * installment.interest_rate * (installment.period_max - installment.period_counter)
+            }
+            _ => {Decimal::ZERO}
+        };
+        self.outstanding_principal() + self.debt_interest + self.extra_debt + scheduled_interest
+    }
 }
 
 /// Type of the credit.
@@ -116,10 +191,22 @@ pub enum CreditType {
     Installment(InstallmentCreditData)
 }
 
+impl CreditType {
+
+    /// Get the time before a new debt of this credit type is due. (seconds)
+    pub fn due_period(&self) -> u64 {
+        match self {
+            CreditType::Revolving(RevolvingTypes::Yearly) => {YEAR}
+            CreditType::Revolving(RevolvingTypes::Monthly) => {MONTH}
+            CreditType::Installment(installment_data) => {installment_data.period_length}
+        }
+    }
+}
+
 /// Type of revolving credit.
 /// 
 /// Can have more revolving credit type in the future.
-#[derive(TypeId, Encode, Decode, Describe, Clone, Copy)]
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
 pub enum RevolvingTypes {
     /// Maximum credit amount is calculated by user's estimated yearly income data. Credit due time will be about one year since the loan is taken.
     Yearly,
@@ -160,6 +247,128 @@ impl CreditScoringRates {
         self.yearly.check_rate();
         self.monthly.check_rate()
     }
+
+    /// Get the credit degrade rate of a credit type.
+    pub fn degrade_rate(&self, credit_type: &CreditType) -> Decimal {
+        match credit_type {
+            CreditType::Revolving(RevolvingTypes::Yearly) => {self.yearly.degrade_rate}
+            _ => {self.monthly.degrade_rate}
+        }
+    }
+
+    /// Get the credit restore rate of a credit type.
+    pub fn restore_rate(&self, credit_type: &CreditType) -> Decimal {
+        match credit_type {
+            CreditType::Revolving(RevolvingTypes::Yearly) => {self.yearly.restore_rate}
+            _ => {self.monthly.restore_rate}
+        }
+    }
+}
+
+/// The attestation of a Credit SBT exported from a credit service to other trusted credit service.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct CreditAttestation {
+    /// The user's Identity SBT ID.
+    pub sbt_id: NonFungibleId,
+    /// The exported Credit SBT ID.
+    pub credit_id: NonFungibleId,
+    /// The exported credit data.
+    pub data: CreditData,
+    /// The user's blacklist history on the source credit service.
+    pub blacklist: Option<BlacklistEntry>
+}
+
+/// The time-based credit score recovery model.
+/// 
+/// Each elapsed period with no late event restores the credit score by the restore rate, up to the user's ID trust factor.
+/// 
+/// The recovery is applied lazily whenever the Credit SBT is touched.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy)]
+pub struct CreditRecovery {
+
+    /// Credit restore rate for each elapsed period.
+    pub restore_rate: Decimal,
+    /// The period length. (seconds)
+    pub period: u64
+
+}
+
+impl CreditRecovery {
+    pub fn check_rate(&self) {
+        assert_rate(self.restore_rate);
+        assert!(self.period > 0, "Wrong data provided!");
+    }
+}
+
+/// The terms of the business credit product.
+/// 
+/// A Business identity's credit limit combines its own revenue with the credit scores of its linked Person owners:
+/// 
+/// ```maximum_credit = revenue * revenue_rate * trust_factor * (credit_score * (1 - owner_weight) + owners_average_credit_score * owner_weight)```
+/// 
+/// The owners are jointly and severally degraded by the owner degrade rate when the business's loan is declared defaulted.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy)]
+pub struct BusinessCreditTerms {
+
+    /// The percent of the business's yearly revenue counted into the credit limit. (%)
+    pub revenue_rate: Decimal,
+    /// The weight of the owners' average credit score in the business's credit score. (%)
+    pub owner_weight: Decimal,
+    /// The owners' credit degrade rate when the business's loan is declared defaulted.
+    pub owner_degrade_rate: Decimal
+
+}
+
+impl BusinessCreditTerms {
+    pub fn check_rates(&self) {
+        assert_rate(self.revenue_rate);
+        assert_rate(self.owner_weight);
+        assert_rate(self.owner_degrade_rate);
+    }
+}
+
+/// The status of a listed lending protocol.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
+pub enum ProtocolStatus {
+    /// The protocol can use every permitted credit action.
+    Active,
+    /// The protocol is being delisted, borrowers can still repay their debt but cannot take new loans.
+    WindDown
+}
+
+/// The credit actions a listed lending protocol can be permitted to do.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
+pub enum ProtocolAction {
+    /// Take revolving loans on users' credit.
+    Revolving,
+    /// Let users use the Installment Credit Badge to take installment loans.
+    Installment,
+    /// Degrade users' credit score, blacklist users and call guarantees.
+    Degrade
+}
+
+/// The capabilities of a listed lending protocol.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy)]
+pub struct ProtocolCapabilities {
+    /// The protocol can take revolving loans on users' credit.
+    pub revolving: bool,
+    /// The protocol can let users take installment loans.
+    pub installment: bool,
+    /// The protocol can degrade users' credit score, blacklist users and call guarantees.
+    pub may_degrade: bool,
+    /// The maximum aggregate principal the protocol can lend out on this credit service.
+    pub exposure_cap: Decimal
+}
+
+/// The struct keep track of a listed lending protocol's permission.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy)]
+pub struct ProtocolPermission {
+    /// The protocol status.
+    pub status: ProtocolStatus,
+    /// The protocol capabilities.
+    pub capabilities: ProtocolCapabilities,
+    /// The current aggregate principal the protocol has lent out on this credit service.
+    pub exposure: Decimal
 }
 
 /// The NFT badge keeping track of an user's installment loan request.
@@ -195,6 +404,18 @@ pub struct InstallmentCreditData {
 
 }
 
+impl InstallmentCreditData {
+
+    /// Check if two installment credit data have the same terms, only the period counter can be changed by the lending protocols.
+    pub fn same_terms(&self, other: &InstallmentCreditData) -> bool {
+        self.total_loan == other.total_loan
+            && self.interest_rate == other.interest_rate
+            && self.interest_rate_late == other.interest_rate_late
+            && self.period_length == other.period_length
+            && self.period_max == other.period_max
+    }
+}
+
 /// The NFT badge allow users to change credit type into an installment credit.
 /// 
 /// ## Uses:
@@ -210,7 +431,67 @@ pub struct InstallmentCredit {
     /// This data is to make sure the one requested and used the installment credit is the same wallet address.
     pub sbt_id: NonFungibleId,
     /// Store the installment credit data.
-    pub data: InstallmentCreditData
+    pub data: InstallmentCreditData,
+    /// The time (unix) before which user has to take the installment loan, the badge cannot be used after this time.
+    /// 
+    /// 0 if the badge doesn't have an activation deadline (the credit service doesn't use any Oracle).
+    pub activation_deadline: u64
+}
+
+/// The NFT badge keeping track of a guarantee that an user has pledged to back other user's credit line.
+/// 
+/// ## Uses:
+/// The guarantor pledge part of their own credit capacity to back a borrower's revolving credit. 
+/// While the guarantee is active, the guarantor's credit allowance is reduced by the pledged amount.
+/// 
+/// If the borrower is overdue, any listed lending protocol can call the guarantee and charge the shortfall on the guarantor's credit.
+/// 
+/// The guarantor can use this badge to release the guarantee when the borrower has no debt.
+#[derive(NonFungibleData)]
+pub struct Guarantee {
+    /// The guarantor's Identity SBT ID.
+    pub guarantor: NonFungibleId,
+    /// The borrower's Identity SBT ID.
+    pub borrower: NonFungibleId,
+    /// The pledged amount.
+    pub amount: Decimal
+}
+
+/// The reason why an ID SBT is put on the credit blacklist.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
+pub enum BlacklistReason {
+    LateRepayment,
+    LoanDefault,
+    LoanScam,
+    IncomeChange,
+    TrustFactorChange,
+    Other
+}
+
+/// The authority who put an ID SBT on the credit blacklist.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
+pub enum BlacklistAuthority {
+    /// The credit service operator.
+    Operator,
+    /// A listed lending protocol (the protocol's controller badge address).
+    Protocol(ResourceAddress)
+}
+
+/// The struct keep track of a blacklisted ID SBT.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy)]
+pub struct BlacklistEntry {
+    /// The reason code.
+    pub reason: BlacklistReason,
+    /// The listing time. (unix)
+    pub listed_time: u64,
+    /// The expiry time (unix), None if the restriction is permanent.
+    /// 
+    /// The entry automatically lapses after the expiry time.
+    pub expiry: Option<u64>,
+    /// The listing authority.
+    pub authority: BlacklistAuthority,
+    /// The entry status.
+    pub active: bool
 }
 
 blueprint! {
@@ -237,22 +518,96 @@ blueprint! {
         /// 
         /// After the service operator pass a installment credit request, user can take the Installment Credit Badge from this vault.
         installment_credit_badge_vault: Vault,
+        /// The time period user has to take the installment loan after the request has passed. (seconds)
+        installment_activation_period: u64,
+        /// The revoked Installment Credit Badges.
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<Installment Credit Badge ID, revoked>`
+        revoked_installment_credit: LazyMap<NonFungibleId, bool>,
         /// The on-using identity service.
         identity_service: ComponentAddress,
         /// The black listed ID SBTs which are not allowed to use credit because of variable reasons:
         /// 
         /// late repayment frequency, loan scam, change of income, change of trust factor score,...
-        blacklist: Vec<NonFungibleId>,
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<Identity SBT ID, blacklist entry>`
+        blacklist: LazyMap<NonFungibleId, BlacklistEntry>,
+        /// The on-using Oracle ```(component_address, oracle_user_badge)``` to keep track of blacklist expiry.
+        /// 
+        /// None if the credit service doesn't use any Oracle, temporary restrictions won't lapse in that case.
+        oracle: Option<(ComponentAddress, Vault)>,
         /// Credit scoring rates. 
         credit_scoring_rates: CreditScoringRates,
+        /// The time-based credit score recovery model, None if the credit service doesn't use the time-based recovery.
+        credit_recovery: Option<CreditRecovery>,
+        /// The business credit terms, None if the credit service doesn't provide business credit.
+        business_credit_terms: Option<BusinessCreditTerms>,
+        /// List of the Person owners underwriting a Business identity's credit.
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<Business ID SBT ID, Vec<Person owner ID SBT ID>>`
+        business_owners: LazyMap<NonFungibleId, Vec<NonFungibleId>>,
         /// Listed protocols can use the credit service.
-        authorized_protocol: Vec<ResourceAddress>,
+        /// 
+        /// **Format**: 
+        /// 
+        /// `HashMap<protocol controller badge address, protocol permission>`
+        authorized_protocol: HashMap<ResourceAddress, ProtocolPermission>,
+        /// The loan principal each listed protocol has lent out on a Credit SBT through [update_credit](GroundCredit::update_credit).
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<Credit SBT ID, HashMap<protocol controller badge address, principal>>`
+        credit_loans: LazyMap<NonFungibleId, HashMap<ResourceAddress, Decimal>>,
+        /// The loan principal each listed protocol has recorded on a Credit SBT through [record_borrow](GroundCredit::record_borrow).
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<Credit SBT ID, HashMap<protocol controller badge address, principal>>`
+        recorded_loans: LazyMap<NonFungibleId, HashMap<ResourceAddress, Decimal>>,
         /// List of credit user.
         /// 
         /// **Format**: 
         /// 
         /// `LazyMap<Identity SBT ID, Credit SBT ID>`
-        credit_list: LazyMap<NonFungibleId, NonFungibleId>
+        /// 
+        /// The Credit SBT ID is None if the credit has been migrated to other credit service.
+        credit_list: LazyMap<NonFungibleId, Option<NonFungibleId>>,
+        /// Guarantee badge resource address.
+        guarantee_badge: ResourceAddress,
+        /// Guarantee book for keeping track of pledged guarantees.
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<guarantee_badge_ID, (guarantor ID SBT ID, borrower ID SBT ID, pledged amount, status)>`
+        guarantee_book: LazyMap<NonFungibleId, (NonFungibleId, NonFungibleId, Decimal, bool)>,
+        /// List of guarantees backing a borrower.
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<borrower ID SBT ID, Vec<guarantee_badge_ID>>`
+        guarantees: LazyMap<NonFungibleId, Vec<NonFungibleId>>,
+        /// Guarantee id counter
+        guarantee_id_counter: u64,
+        /// Trusted credit services which users can migrate their Credit SBT from (or into).
+        /// 
+        /// **Format**: 
+        /// 
+        /// `HashMap<credit service component address, credit service controller badge address>`
+        trusted_services: HashMap<ComponentAddress, ResourceAddress>,
+        /// List of credit user who has migrated their Credit SBT to other credit service.
+        /// 
+        /// **Format**: 
+        /// 
+        /// `LazyMap<Identity SBT ID, destination credit service component address>`
+        /// 
+        /// The destination is None if the credit has been migrated back into this credit service.
+        migrated: LazyMap<NonFungibleId, Option<ComponentAddress>>
 
     }
 
@@ -282,7 +637,7 @@ blueprint! {
                 .metadata("name", name.clone() + "'s Credit SBT")
                 .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
                 .restrict_withdraw(rule!(deny_all), LOCKED)
-                .updateable_non_fungible_data(rule!(deny_all), MUTABLE(rule!(require(controller_badge.resource_address()))))
+                .updateable_non_fungible_data(rule!(require(controller_badge.resource_address())), LOCKED)
                 .no_initial_supply();
             
             let request_badge = ResourceBuilder::new_non_fungible()
@@ -292,19 +647,33 @@ blueprint! {
                 .no_initial_supply();
 
             let installment_credit_badge = ResourceBuilder::new_non_fungible()
-                .metadata("name", name +"'s Installment Credit Badge")
+                .metadata("name", name.clone() +"'s Installment Credit Badge")
+                .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
+            let guarantee_badge = ResourceBuilder::new_non_fungible()
+                .metadata("name", name +"'s Credit Guarantee Badge")
                 .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
-                .burnable(rule!(require(controller_badge.resource_address())), MUTABLE(rule!(require(controller_badge.resource_address()))))
+                .burnable(rule!(require(controller_badge.resource_address())), LOCKED)
                 .no_initial_supply();
 
             let rules = AccessRules::new()
                 .method("issue_new_credit_sbt", rule!(require(admin_badge)))
                 .method("review_installment_credit_request", rule!(require(admin_badge)))
+                .method("revoke_installment_credit", rule!(require(admin_badge)))
+                .method("change_installment_activation_period", rule!(require(admin_badge)))
                 .method("list_protocol", rule!(require(admin_badge)))
                 .method("delist_protocol", rule!(require(admin_badge)))
                 .method("blacklist", rule!(require(admin_badge)))
                 .method("whitelist", rule!(require(admin_badge)))
+                .method("use_oracle", rule!(require(admin_badge)))
                 .method("change_credit_scoring_rate", rule!(require(admin_badge)))
+                .method("change_credit_recovery", rule!(require(admin_badge)))
+                .method("change_business_credit_terms", rule!(require(admin_badge)))
+                .method("link_business_owners", rule!(require(admin_badge)))
+                .method("trust_credit_service", rule!(require(admin_badge)))
+                .method("untrust_credit_service", rule!(require(admin_badge)))
                 .default(rule!(allow_all));
 
             let comp = Self {
@@ -316,11 +685,25 @@ blueprint! {
                 request_badge: request_badge,
                 installment_credit_badge: installment_credit_badge,
                 installment_credit_badge_vault: Vault::new(installment_credit_badge),
+                installment_activation_period: INSTALLMENT_ACTIVATION_PERIOD,
+                revoked_installment_credit: LazyMap::new(),
                 identity_service: identity_service,
-                blacklist: Vec::new(),
+                blacklist: LazyMap::new(),
+                oracle: None,
                 credit_scoring_rates: credit_scoring_rates,
-                authorized_protocol: Vec::new(),
-                credit_list: LazyMap::new()
+                credit_recovery: None,
+                business_credit_terms: None,
+                business_owners: LazyMap::new(),
+                authorized_protocol: HashMap::new(),
+                credit_loans: LazyMap::new(),
+                recorded_loans: LazyMap::new(),
+                credit_list: LazyMap::new(),
+                guarantee_badge: guarantee_badge,
+                guarantee_book: LazyMap::new(),
+                guarantees: LazyMap::new(),
+                guarantee_id_counter: 0,
+                trusted_services: HashMap::new(),
+                migrated: LazyMap::new()
 
             }
             .instantiate()
@@ -343,7 +726,9 @@ blueprint! {
 
             let sbt_id = id_sbt.non_fungible::<Identity>().id();
 
-            assert!(matches!(self.credit_list.get(&sbt_id), None), "You already has a credit SBT");
+            assert!(matches!(self.credit_list.get(&sbt_id).flatten(), None), "You already has a credit SBT");
+
+            assert!(self.migrated.get(&sbt_id).flatten().is_none(), "Your credit has been migrated to other credit service.");
 
             let trust_score = id_sbt.non_fungible::<Identity>().data().data.trust_factor;
 
@@ -353,7 +738,9 @@ blueprint! {
 
             info!("You got new Credit SBT no.{}", id.clone());
             
-            self.credit_list.insert(sbt_id, id.clone());
+            self.credit_list.insert(sbt_id, Some(id.clone()));
+
+            let current = self.current();
 
             self.controller_badge.authorize(|| {
                 borrow_resource_manager!(self.credit_sbt)
@@ -369,7 +756,12 @@ blueprint! {
                                 debt_interest: Decimal::zero(),
                                 due_time: 0,
                                 extra_debt: Decimal::zero(),
-                                repaid_amount_accumulated: Decimal::zero()
+                                repaid_amount_accumulated: Decimal::zero(),
+                                guaranteed_amount: Decimal::zero(),
+                                pledged_amount: Decimal::zero(),
+                                defaulted: false,
+                                score_checkpoint: current,
+                                migrated: false
                             }
                         }
                 )
@@ -385,7 +777,7 @@ blueprint! {
         /// The new Credit SBT.
         pub fn issue_new_credit_sbt(&self, sbt_id: NonFungibleId, credit_score: Decimal) -> Bucket {
 
-            assert!(matches!(self.credit_list.get(&sbt_id), None), "This Identity already has a credit SBT");
+            assert!(matches!(self.credit_list.get(&sbt_id).flatten(), None), "This Identity already has a credit SBT");
 
             assert_rate(credit_score);
 
@@ -393,6 +785,8 @@ blueprint! {
 
             info!("Issued new Credit SBT no.{}", id.clone());
 
+            let current = self.current();
+
             self.controller_badge.authorize(|| {
                 borrow_resource_manager!(self.credit_sbt)
                     .mint_non_fungible(
@@ -407,7 +801,12 @@ blueprint! {
                                 debt_interest: Decimal::zero(),
                                 due_time: 0,
                                 extra_debt: Decimal::zero(),
-                                repaid_amount_accumulated: Decimal::zero()
+                                repaid_amount_accumulated: Decimal::zero(),
+                                guaranteed_amount: Decimal::zero(),
+                                pledged_amount: Decimal::zero(),
+                                defaulted: false,
+                                score_checkpoint: current,
+                                migrated: false
                             }
 
                         }
@@ -496,14 +895,9 @@ blueprint! {
 
                     assert!(id_data.income > Decimal::zero(), "You don't have an income. Please consider using installment credit service.");
 
-                    let yearly_maximum_credit = id_data.income * (id_data.trust_factor / 100) * (data.credit_score / 100);
-
-                    let maximum_credit = match types {
-                        RevolvingTypes::Monthly => {yearly_maximum_credit / dec!("12")}
-                        RevolvingTypes::Yearly => {yearly_maximum_credit}
-                    };
+                    let maximum_credit = Self::maximum_credit(&id_data, &data, types);
 
-                    let allowance = maximum_credit - data.current_debt - data.debt_interest;
+                    let allowance = maximum_credit - data.current_debt - data.debt_interest - data.pledged_amount;
 
                     info!("Your current credit allowance is: {}", allowance);
 
@@ -514,11 +908,66 @@ blueprint! {
             }
         }
 
+        /// A workaround method for restrictive proof.
+        /// 
+        /// Get the maximum credit and current credit allowance of a Business identity.
+        /// ### Input: 
+        /// - id: the business's Identity SBT ID.
+        /// - id_data: the business's Identity data.
+        /// - data: the business's Credit data.
+        /// ### Output: 
+        /// The business's maximum credit amount and current allowance.
+        /// 
+        /// If the credit service doesn't provide business credit terms, the business's credit is calculated the same as a Person's revolving credit.
+        pub fn get_business_credit_amount_by_data(&self, id: NonFungibleId, id_data: IdentityData, data: CreditData) -> (Decimal, Decimal) {
+
+            assert!(matches!(id_data.identity, IdentityType::Business), "The ID SBT is not a Business SBT.");
+
+            if self.business_credit_terms.is_none() {
+                return self.get_revolving_credit_amount_by_data(id_data, data)
+            }
+
+            assert!(id_data.trust_factor >  Decimal::zero(), "You're not allowed to use credit.");
+            assert!(data.credit_score >  Decimal::zero(), "Your credit score has degraded to 0, you're not allowed to use credit.");
+            assert!(data.extra_debt == Decimal::zero(), "You have to repay your debt first!");
+            assert!(id_data.income > Decimal::zero(), "Your business doesn't have a revenue. Please consider using installment credit service.");
+
+            let maximum_credit = match data.credit_type {
+                CreditType::Revolving(types) => {self.maximum_business_credit(&id, &id_data, &data, types)}
+                _ => {panic!("You're using an installment credit. You cannot take the revolving credit.")}
+            };
+
+            let allowance = maximum_credit - data.current_debt - data.debt_interest - data.pledged_amount;
+
+            info!("Your business's current credit allowance is: {}", allowance);
+
+            (maximum_credit, allowance)
+        }
+
+        /// Read only method to get the credit limit of an user without any assertion.
+        /// 
+        /// Return 0 if the user is not allowed to use the revolving credit, or the total loan if the user is using installment credit.
+        pub fn get_credit_limit_by_data(&self, id: NonFungibleId, id_data: IdentityData, data: CreditData) -> Decimal {
+            match data.credit_type {
+                CreditType::Revolving(types) => {
+                    if id_data.trust_factor <= Decimal::ZERO || data.credit_score <= Decimal::ZERO || id_data.income <= Decimal::ZERO {
+                        data.guaranteed_amount
+                    } else if matches!(id_data.identity, IdentityType::Business) && self.business_credit_terms.is_some() {
+                        self.maximum_business_credit(&id, &id_data, &data, types)
+                    } else {
+                        Self::maximum_credit(&id_data, &data, types)
+                    }
+                }
+                CreditType::Installment(installment) => {installment.total_loan}
+            }
+        }
+
         /// This method is for users to get their maximum credit and current credit allowance.
         /// 
-        /// The maximum credit amount is calculated by a cubic function with the income, id trust score and credit score as the params.
+        /// The maximum credit amount is calculated by a cubic function with the income, id trust score and credit score as the params, 
+        /// plus the amount other users has pledged to guarantee the credit.
         /// 
-        /// The current credit allowance = maximum credit - current debt.
+        /// The current credit allowance = maximum credit - current debt - the amount user has pledged to guarantee other users' credit.
         /// 
         /// ### Input: 
         /// - id_sbt: The Proof of the user's Credit SBT.
@@ -534,9 +983,15 @@ blueprint! {
 
             // let id_data = id_proof.non_fungible::<Identity>().data();
 
+            let id_data = id_proof.non_fungible::<Identity>().data().data;
+
+            self.recover(&credit_sbt.non_fungible::<Credit>().id(), id_data.trust_factor, self.current());
+
             let data = credit_sbt.non_fungible::<Credit>().data().data;
 
-            let id_data = id_proof.non_fungible::<Identity>().data().data;
+            if matches!(id_data.identity, IdentityType::Business) {
+                return self.get_business_credit_amount_by_data(id_proof.non_fungible::<Identity>().id(), id_data, data)
+            }
 
             // assert!(id_data.trust_factor >  Decimal::zero(), "You're not allowed to use credit.");
             // assert!(data.credit_score >  Decimal::zero(), "Your credit score has degraded to 0, you're not allowed to use credit.");
@@ -648,13 +1103,18 @@ blueprint! {
 
                 info!("The installment credit request no.{} has passed.", request_id);
 
+                let current = self.current();
+
+                let activation_deadline = if current == 0 { 0 } else { current + self.installment_activation_period };
+
                 self.controller_badge.authorize(|| {
                     self.installment_credit_badge_vault.put(
                     borrow_resource_manager!(self.installment_credit_badge)
                         .mint_non_fungible(&request_id, InstallmentCredit {
 
                             sbt_id: sbt_id,
-                            data: data
+                            data: data,
+                            activation_deadline: activation_deadline
 
                         }))
                 })
@@ -689,7 +1149,10 @@ blueprint! {
                 request_badge.burn()
             });
 
-            if self.installment_credit_badge_vault.non_fungible_ids().contains(&request_id) {
+            if self.revoked_installment_credit.get(&request_id).is_some() {
+                info!("Your installment credit no.{} has been revoked.", request_id);
+                None
+            } else if self.installment_credit_badge_vault.non_fungible_ids().contains(&request_id) {
                 info!("Your installment credit request no.{} has passed.", request_id);
                 Some(self.installment_credit_badge_vault.take_non_fungible(&request_id))
             } else {
@@ -699,206 +1162,1011 @@ blueprint! {
 
         }
 
-        // /// This method is for protocols to check and update user's installment credit data
-        // /// before user take the installment loan.
-        // /// ### Input: 
-        // /// - id_sbt_proof: The Proof of the user's Credit SBT.
-        // /// - credit_proof: the Proof of the user's Credit SBT.
-        // /// - protocol_proof: The Proof of the protocol's controller badge.
-        // /// - installment_credit_badge: The Bucket of user's Installment Credit NFT.
-        // /// - current: Current time data fed in through the protocol. (unix)
-        // /// ### Output: 
-        // /// Return the installment credit amount.
-        // /// 
-        // /// Notice: This will not work with restrictive proof
-        // pub fn use_installment_credit(&self, id_sbt_proof: Proof, credit_proof: Proof, protocol_proof: Proof, installment_credit_badge: Bucket, current: u64) -> Decimal {
+        /// This method is for the service operator to revoke an outstanding Installment Credit Badge 
+        /// when the borrower's circumstances changed.
+        /// 
+        /// The badge will be burnt if it's still in the component's vault, otherwise it will be marked invalid.
+        /// ### Input: 
+        /// - id: the request ID.
+        pub fn revoke_installment_credit(&mut self, id: u64) {
 
-        //     self.check_protocol(protocol_proof);
+            let request_id = NonFungibleId::from_u64(id);
 
-        //     let credit = credit_proof.non_fungible::<Credit>();
+            assert!(self.revoked_installment_credit.get(&request_id).is_none(), "This installment credit is already revoked.");
 
-        //     let data = credit.data();
+            self.revoked_installment_credit.insert(request_id.clone(), true);
 
-        //     let id_sbt = id_sbt_proof.non_fungible::<Identity>().id();
+            if self.installment_credit_badge_vault.non_fungible_ids().contains(&request_id) {
 
-        //     id_sbt_proof.drop(); 
+                let badge = self.installment_credit_badge_vault.take_non_fungible(&request_id);
 
-        //     assert!(installment_credit_badge.resource_address() == self.installment_credit_badge, "Wrong resource!");
+                self.controller_badge.authorize(|| {
+                    badge.burn()
+                });
 
-        //     let installment_data = installment_credit_badge.non_fungible::<InstallmentCredit>().data();
+                info!("The installment credit no.{} has been revoked and burnt.", request_id);
 
-        //     let installment_id = installment_data.sbt_id;
+            } else {
+                info!("The installment credit no.{} has been revoked.", request_id);
+            }
 
-        //     assert!(id_sbt == installment_id, "Wrong Installment Credit Badge provided.");
+        }
 
-        //     assert!(data.due_time == 0, "You have to repay all your current debt first.");
+        /// This method is for the service operator to change the time period user has to take the installment loan after the request has passed.
+        pub fn change_installment_activation_period(&mut self, period: u64) {
+            assert!(period > 0, "Wrong data provided!");
+            self.installment_activation_period = period
+        }
 
-        //     let mut installment_data = installment_data.data;
+        /// This method is for users to pledge part of their own credit capacity to guarantee other user's revolving credit line.
+        /// 
+        /// While the guarantee is active, the guarantor's credit allowance is reduced by the pledged amount 
+        /// and the borrower's maximum credit is increased by the same amount.
+        /// 
+        /// Only Person ID SBT holders can become a guarantor.
+        /// ### Input: 
+        /// - id_proof: The Proof of the guarantor's Identity SBT.
+        /// - credit_proof: the Proof of the guarantor's Credit SBT.
+        /// - borrower: the borrower's Identity SBT ID.
+        /// - amount: the pledged amount.
+        /// ### Output: 
+        /// The Guarantee badge.
+        pub fn pledge_guarantee(&mut self, id_proof: Proof, credit_proof: Proof, borrower: NonFungibleId, amount: Decimal) -> Bucket {
 
-        //     installment_data.period_counter = 1;
-            
-        //     let current_debt = installment_data.total_loan / installment_data.period_max;
+            assert!(amount > Decimal::zero(), "Wrong data provided!");
 
-        //     let debt_interest = current_debt * installment_data.interest_rate;
+            let (id_proof, credit_proof) = self.check_id_and_credit(id_proof, credit_proof);
 
-        //     let due_time = current + installment_data.period_length;
+            let guarantor = id_proof.non_fungible::<Identity>().id();
 
-        //     self.controller_badge.authorize(|| { 
-        //         installment_credit_badge.burn();
-        //         credit.update_data(Credit {
-        //             credit_type: CreditType::Installment(installment_data),
-        //             current_debt_start_time: current,
-        //             current_debt,
-        //             debt_interest,
-        //             due_time,
-        //             ..data
-        //         })
-        //     });
+            let id_data = id_proof.non_fungible::<Identity>().data().data;
 
-        //     info!("You have changed your credit type into Installment Credit. Your current debt is: {}. Your debt will over due in: {} (unix time)", current_debt, due_time);
+            id_proof.drop();
 
-        //     credit_proof.drop();
+            id_data.identity.check_human_proof();
 
-        //     installment_data.total_loan
-                    
-        // }
+            assert!(guarantor != borrower, "You cannot guarantee your own credit.");
 
-        // /// This method is for protocols to check and update user's installment credit data
-        // /// after user repaid the period loan.
-        // /// ### Input: 
-        // /// - credit_proof: the Proof of the user's Credit SBT.
-        // /// - protocol_proof: The Proof of the protocol's controller badge.
-        // /// - late: input if the repayment is late or not.
-        // /// ### Output:
-        // /// - Update the installment credit data based on user's repayment.
-        // /// - Return the next period debt amount.
-        // /// 
-        // /// Notice: This will not work with restrictive proof
-        // pub fn update_installment_credit_data(&self, credit_proof: Proof, protocol_proof: Proof, late: bool) -> Decimal {
+            self.lapse_blacklist(&borrower);
 
-        //     self.check_protocol(protocol_proof);
+            assert!(!self.is_blacklisted(&borrower), "The borrower is not allowed to use credit.");
 
-        //     let credit = credit_proof.non_fungible::<Credit>();
+            let borrower_credit_id = self.credit_list.get(&borrower).flatten().expect("The borrower doesn't have a credit SBT.");
 
-        //     let mut data = credit.data();
+            let credit = credit_proof.non_fungible::<Credit>();
 
-        //     let mut installment_data = match data.credit_type {
+            let data = credit.data().data;
 
-        //         CreditType::Installment(data) => {data}
-        //         _ => {panic!("Wrong credit type!")}
+            assert!(!data.defaulted, "Your credit is defaulted, you cannot pledge any guarantee.");
 
-        //     };
+            let (_, allowance) = self.get_revolving_credit_amount_by_data(id_data, data);
 
-        //     if !late {
-        //         data.credit_score += self.credit_scoring_rates.monthly.restore_rate
-        //     };
+            assert!(allowance >= amount, "Your current credit allowance is not enough to pledge this guarantee.");
 
-        //     if installment_data.period_counter < installment_data.period_max {
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
 
-        //         installment_data.period_counter += 1;
+            let borrower_data = resource_manager.get_non_fungible_data::<Credit>(&borrower_credit_id).data;
 
-        //         let current_debt = installment_data.total_loan / installment_data.period_max;
+            assert!(matches!(borrower_data.credit_type, CreditType::Revolving(_)), "The borrower is using an installment credit.");
 
-        //         let debt_interest = current_debt * installment_data.interest_rate;
+            let id = NonFungibleId::from_u64(self.guarantee_id_counter);
 
-        //         let period_length = installment_data.period_length;
+            self.guarantee_id_counter += 1;
+
+            self.guarantee_book.insert(id.clone(), (guarantor.clone(), borrower.clone(), amount, true));
+
+            let mut guarantees = self.guarantees.get(&borrower).unwrap_or(Vec::new());
+            guarantees.push(id.clone());
+            self.guarantees.insert(borrower.clone(), guarantees);
+
+            let guarantee_badge = self.controller_badge.authorize(|| {
+                credit.update_data(
+                    Credit {
+                        data: CreditData {
+                            pledged_amount: data.pledged_amount + amount,
+                            ..data
+                        }
+                    }
+                );
+                resource_manager.update_non_fungible_data(&borrower_credit_id, 
+                    Credit {
+                        data: CreditData {
+                            guaranteed_amount: borrower_data.guaranteed_amount + amount,
+                            ..borrower_data
+                        }
+                    }
+                );
+                borrow_resource_manager!(self.guarantee_badge)
+                    .mint_non_fungible(&id, Guarantee {
+                        guarantor: guarantor,
+                        borrower: borrower.clone(),
+                        amount: amount
+                    })
+            });
+
+            credit_proof.drop();
+
+            info!("You have pledged {} stable coins of your credit to guarantee the credit of the user ID {}. Guarantee no.{}", amount, borrower, id);
+
+            guarantee_badge
+
+        }
+
+        /// This method is for guarantors to release their guarantee when the borrower has no debt.
+        /// ### Input: 
+        /// - guarantee_badge: the Guarantee badge bucket.
+        /// ### Output: 
+        /// The guarantor's credit allowance is restored and the badge is burnt.
+        pub fn release_guarantee(&mut self, guarantee_badge: Bucket) {
+
+            assert!(guarantee_badge.resource_address() == self.guarantee_badge, "Wrong resource!");
+
+            let guarantee = guarantee_badge.non_fungible::<Guarantee>();
+
+            let id = guarantee.id();
+
+            let (guarantor, borrower, amount, status) = self.guarantee_book.get(&id).unwrap();
+
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
+
+            if status {
+
+                let borrower_credit_id = self.credit_list.get(&borrower).flatten().unwrap();
+
+                let borrower_data = resource_manager.get_non_fungible_data::<Credit>(&borrower_credit_id).data;
+
+                assert!(borrower_data.due_time == 0, "The borrower still has debt, you cannot release the guarantee yet.");
+
+                let guarantor_credit_id = self.credit_list.get(&guarantor).flatten().unwrap();
+
+                let guarantor_data = resource_manager.get_non_fungible_data::<Credit>(&guarantor_credit_id).data;
+
+                self.controller_badge.authorize(|| {
+                    resource_manager.update_non_fungible_data(&borrower_credit_id, 
+                        Credit {
+                            data: CreditData {
+                                guaranteed_amount: borrower_data.guaranteed_amount - amount,
+                                ..borrower_data
+                            }
+                        }
+                    );
+                    resource_manager.update_non_fungible_data(&guarantor_credit_id, 
+                        Credit {
+                            data: CreditData {
+                                pledged_amount: guarantor_data.pledged_amount - amount,
+                                ..guarantor_data
+                            }
+                        }
+                    );
+                });
+
+                self.guarantee_book.insert(id.clone(), (guarantor, borrower, amount, false));
+
+                info!("You have released the guarantee no.{}, your credit allowance is restored by {} stable coins.", id, amount);
+
+            } else {
+                info!("The guarantee no.{} has already been called.", id);
+            }
+
+            self.controller_badge.authorize(|| {
+                guarantee_badge.burn()
+            });
+
+        }
+
+        /// This method is for listed lending protocols to call the guarantees of an overdue borrower.
+        /// 
+        /// The shortfall is charged pro-rata to the pledged amounts, capped at the total pledged amount, 
+        /// as new debt on the guarantors' credit. The guarantors' credit scores are degraded.
+        /// 
+        /// The charged principal is booked as the protocol's loan on the guarantors' credit, 
+        /// the protocol has to release the same principal from the borrower's credit through [update_credit](GroundCredit::update_credit).
+        /// 
+        /// The called guarantees are no longer active.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - borrower: the borrower's Identity SBT ID.
+        /// - principal: the borrower's unpaid initial debt.
+        /// - interest: the borrower's unpaid debt interest.
+        /// - current: Current time data fed in through the protocol. (unix)
+        /// ### Output: 
+        /// The initial debt and the debt interest amount charged on the guarantors, 
+        /// and the initial debt charged on each guarantor by the guarantor's Identity SBT ID.
+        /// 
+        /// If a guarantor doesn't have any debt, the charged debt is due after one period of the guarantor's own credit type.
+        pub fn call_guarantees(&mut self, protocol_proof: Proof, borrower: NonFungibleId, principal: Decimal, interest: Decimal, current: u64) -> (Decimal, Decimal, Vec<(NonFungibleId, Decimal)>) {
+
+            let protocol = protocol_proof.resource_address();
+
+            self.check_permission(protocol_proof, ProtocolAction::Degrade);
+
+            let guarantees = self.guarantees.get(&borrower).unwrap_or(Vec::new());
+
+            let mut active_guarantees = Vec::new();
+
+            let mut total_pledged = Decimal::ZERO;
+
+            for id in guarantees {
+                let (guarantor, _, amount, status) = self.guarantee_book.get(&id).unwrap();
+                if status {
+                    total_pledged += amount;
+                    active_guarantees.push((id, guarantor, amount));
+                }
+            }
+
+            if total_pledged == Decimal::ZERO || principal + interest == Decimal::ZERO {
+                info!("The user ID {} doesn't have any active guarantee to call.", borrower);
+                return (Decimal::ZERO, Decimal::ZERO, Vec::new())
+            }
+
+            let rate = if total_pledged >= principal + interest {
+                Decimal::ONE
+            } else {
+                total_pledged / (principal + interest)
+            };
+
+            let (charged_principal, charged_interest) = (principal * rate, interest * rate);
+
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
+
+            let mut charges = Vec::new();
+
+            for (id, guarantor, amount) in active_guarantees {
+
+                let share = amount / total_pledged;
+
+                let guarantor_credit_id = self.credit_list.get(&guarantor).flatten().unwrap();
+
+                let data = resource_manager.get_non_fungible_data::<Credit>(&guarantor_credit_id).data;
+
+                let degrade_rate = self.credit_scoring_rates.degrade_rate(&data.credit_type);
+
+                let new_score = if data.credit_score >= degrade_rate {
+                    data.credit_score - degrade_rate
+                } else { Decimal::zero() };
+
+                let (new_due_time, new_start_time) = if data.due_time == 0 {
+                    (current + data.credit_type.due_period(), current)
+                } else {
+                    (data.due_time, data.current_debt_start_time)
+                };
+
+                self.controller_badge.authorize(|| {
+                    resource_manager.update_non_fungible_data(&guarantor_credit_id, 
+                        Credit {
+                            data: CreditData {
+                                credit_score: new_score,
+                                current_debt: data.current_debt + charged_principal * share,
+                                debt_interest: data.debt_interest + charged_interest * share,
+                                due_time: new_due_time,
+                                current_debt_start_time: new_start_time,
+                                pledged_amount: data.pledged_amount - amount,
+                                score_checkpoint: current,
+                                ..data
+                            }
+                        }
+                    )
+                });
+
+                self.guarantee_book.insert(id.clone(), (guarantor.clone(), borrower.clone(), amount, false));
+
+                let mut loans = self.credit_loans.get(&guarantor_credit_id).unwrap_or(HashMap::new());
+
+                *loans.entry(protocol).or_insert(Decimal::ZERO) += charged_principal * share;
+
+                self.credit_loans.insert(guarantor_credit_id, loans);
+
+                self.authorized_protocol.get_mut(&protocol).unwrap().exposure += charged_principal * share;
+
+                charges.push((guarantor.clone(), charged_principal * share));
+
+                info!("Called the guarantee no.{}, the guarantor ID {} has been charged {} stable coins and got the credit score degraded to {}", id, guarantor, (charged_principal + charged_interest) * share, new_score);
+
+            }
+
+            let borrower_credit_id = self.credit_list.get(&borrower).flatten().unwrap();
+
+            let borrower_data = resource_manager.get_non_fungible_data::<Credit>(&borrower_credit_id).data;
+
+            self.controller_badge.authorize(|| {
+                resource_manager.update_non_fungible_data(&borrower_credit_id, 
+                    Credit {
+                        data: CreditData {
+                            guaranteed_amount: borrower_data.guaranteed_amount - total_pledged,
+                            ..borrower_data
+                        }
+                    }
+                )
+            });
+
+            (charged_principal, charged_interest, charges)
+
+        }
+
+        // /// This method is for protocols to check and update user's installment credit data
+        // /// before user take the installment loan.
+        // /// ### Input: 
+        // /// - id_sbt_proof: The Proof of the user's Credit SBT.
+        // /// - credit_proof: the Proof of the user's Credit SBT.
+        // /// - protocol_proof: The Proof of the protocol's controller badge.
+        // /// - installment_credit_badge: The Bucket of user's Installment Credit NFT.
+        // /// - current: Current time data fed in through the protocol. (unix)
+        // /// ### Output: 
+        // /// Return the installment credit amount.
+        // /// 
+        // /// Notice: This will not work with restrictive proof
+        // pub fn use_installment_credit(&self, id_sbt_proof: Proof, credit_proof: Proof, protocol_proof: Proof, installment_credit_badge: Bucket, current: u64) -> Decimal {
+
+        //     self.check_protocol(protocol_proof);
+
+        //     let credit = credit_proof.non_fungible::<Credit>();
+
+        //     let data = credit.data();
+
+        //     let id_sbt = id_sbt_proof.non_fungible::<Identity>().id();
+
+        //     id_sbt_proof.drop(); 
+
+        //     assert!(installment_credit_badge.resource_address() == self.installment_credit_badge, "Wrong resource!");
+
+        //     let installment_data = installment_credit_badge.non_fungible::<InstallmentCredit>().data();
+
+        //     let installment_id = installment_data.sbt_id;
+
+        //     assert!(id_sbt == installment_id, "Wrong Installment Credit Badge provided.");
+
+        //     assert!(data.due_time == 0, "You have to repay all your current debt first.");
+
+        //     let mut installment_data = installment_data.data;
+
+        //     installment_data.period_counter = 1;
+            
+        //     let current_debt = installment_data.total_loan / installment_data.period_max;
+
+        //     let debt_interest = current_debt * installment_data.interest_rate;
+
+        //     let due_time = current + installment_data.period_length;
+
+        //     self.controller_badge.authorize(|| { 
+        //         installment_credit_badge.burn();
+        //         credit.update_data(Credit {
+        //             credit_type: CreditType::Installment(installment_data),
+        //             current_debt_start_time: current,
+        //             current_debt,
+        //             debt_interest,
+        //             due_time,
+        //             ..data
+        //         })
+        //     });
+
+        //     info!("You have changed your credit type into Installment Credit. Your current debt is: {}. Your debt will over due in: {} (unix time)", current_debt, due_time);
+
+        //     credit_proof.drop();
+
+        //     installment_data.total_loan
+                    
+        // }
+
+        // /// This method is for protocols to check and update user's installment credit data
+        // /// after user repaid the period loan.
+        // /// ### Input: 
+        // /// - credit_proof: the Proof of the user's Credit SBT.
+        // /// - protocol_proof: The Proof of the protocol's controller badge.
+        // /// - late: input if the repayment is late or not.
+        // /// ### Output:
+        // /// - Update the installment credit data based on user's repayment.
+        // /// - Return the next period debt amount.
+        // /// 
+        // /// Notice: This will not work with restrictive proof
+        // pub fn update_installment_credit_data(&self, credit_proof: Proof, protocol_proof: Proof, late: bool) -> Decimal {
+
+        //     self.check_protocol(protocol_proof);
+
+        //     let credit = credit_proof.non_fungible::<Credit>();
+
+        //     let mut data = credit.data();
+
+        //     let mut installment_data = match data.credit_type {
+
+        //         CreditType::Installment(data) => {data}
+        //         _ => {panic!("Wrong credit type!")}
+
+        //     };
+
+        //     if !late {
+        //         data.credit_score += self.credit_scoring_rates.monthly.restore_rate
+        //     };
+
+        //     if installment_data.period_counter < installment_data.period_max {
+
+        //         installment_data.period_counter += 1;
+
+        //         let current_debt = installment_data.total_loan / installment_data.period_max;
+
+        //         let debt_interest = current_debt * installment_data.interest_rate;
+
+        //         let period_length = installment_data.period_length;
 
         //         info!("You have repaid all the current period debt from your installment credit, your period will be advanced by 1. Current period: {}", installment_data.period_counter);
 
-        //         data = Credit {
-        //             credit_type: CreditType::Installment(installment_data),
-        //             current_debt_start_time: data.due_time,
-        //             current_debt,
-        //             debt_interest,
-        //             due_time: data.due_time + period_length,
-        //             ..data
-        //         };
+        //         data = Credit {
+        //             credit_type: CreditType::Installment(installment_data),
+        //             current_debt_start_time: data.due_time,
+        //             current_debt,
+        //             debt_interest,
+        //             due_time: data.due_time + period_length,
+        //             ..data
+        //         };
+
+        //     } else if installment_data.period_counter == installment_data.period_max {
+
+        //         installment_data.period_counter += 1;
+
+        //         data = Credit {
+        //             credit_type: CreditType::Revolving(RevolvingTypes::Monthly),
+        //             current_debt_start_time: 0,
+        //             current_debt: Decimal::ZERO,
+        //             debt_interest: Decimal::ZERO,
+        //             due_time: 0,
+        //             ..data
+        //         };
+
+        //         info!("You have repaid all your installment loan, your credit will change into the Monthly Revolving Credit");
+
+        //     } else {panic!("You have already repaid all the installment credit debt. Please consider changing your credit type into revolving credit.")};
+
+        //     self.controller_badge.authorize(|| { 
+        //         credit.update_data(Credit {
+        //             ..data
+        //         })
+        //     });
+
+        //     credit_proof.drop();
+
+        //     return data.current_debt
+
+        // }
+
+        // /// This method is for the service operator to allow a protocol using on-chain credit service.
+        // /// 
+        // /// Input: The protocol controller badge resource address
+        // pub fn list_protocol(&mut self, protocol_controller_address: ResourceAddress) {
+
+        //     self.authorized_protocol.push(protocol_controller_address);
+
+        //     info!("listed the lending protocol with controller badge address {}", protocol_controller_address);
+
+        // }
+
+        // /// This method is for the service operator to deny a protocol using on-chain credit service.
+        // /// All protocols is denied by default.
+        // /// 
+        // /// Input: The protocol controller badge resource address
+        // pub fn delist_protocol(&mut self, protocol_controller_address: ResourceAddress) {
+
+        //     let index = self.authorized_protocol.iter().position(|x| *x == protocol_controller_address);
+
+        //     match index {
+        //         None => {info!("Doesn't have this protocol on the list.")}
+        //         Some(x) => {
+
+        //             self.authorized_protocol.remove(x);
+
+        //             info!("delisted the lending protocol with controller badge address {}", protocol_controller_address);
+
+        //         }
+        //     }
+        // }
+
+        /// This method is for the service operator to allow a protocol using on-chain credit service.
+        /// 
+        /// The method can also be used to change a listed protocol's capabilities or reactivate a winding down protocol.
+        /// ### Input: 
+        /// - protocol_controller_address: The protocol controller badge resource address
+        /// - capabilities: The protocol capabilities, syntax:
+        /// 
+        /// ```Struct({revolving}, {installment}, {may_degrade}, Decimal("{exposure_cap}"))```
+        pub fn list_protocol(&mut self, protocol_controller_address: ResourceAddress, capabilities: ProtocolCapabilities) {
+
+            assert!(capabilities.exposure_cap >= Decimal::ZERO, "Wrong data provided!");
+
+            let exposure = match self.authorized_protocol.get(&protocol_controller_address) {
+                None => Decimal::ZERO,
+                Some(permission) => permission.exposure
+            };
+
+            self.authorized_protocol.insert(protocol_controller_address, ProtocolPermission {
+                status: ProtocolStatus::Active,
+                capabilities,
+                exposure
+            });
+
+            info!("listed the lending protocol with controller badge address {}", protocol_controller_address);
+
+        }
+
+        /// This method is for the service operator to deny a protocol using on-chain credit service.
+        /// All protocols is denied by default.
+        /// ### Input: 
+        /// - protocol_controller_address: The protocol controller badge resource address
+        /// - wind_down: true if the protocol's borrowers can still repay their debt, but the protocol cannot lend out new loans.
+        /// The protocol is removed right away if false.
+        pub fn delist_protocol(&mut self, protocol_controller_address: ResourceAddress, wind_down: bool) {
+
+            if wind_down {
+
+                match self.authorized_protocol.get_mut(&protocol_controller_address) {
+                    None => {info!("Doesn't have this protocol on the list.")}
+                    Some(permission) => {
+                        permission.status = ProtocolStatus::WindDown;
+                        info!("The lending protocol with controller badge address {} is winding down, it cannot lend out new loans", protocol_controller_address);
+                    }
+                }
+
+            } else {
+
+                match self.authorized_protocol.remove(&protocol_controller_address) {
+                    None => {info!("Doesn't have this protocol on the list.")}
+                    Some(_) => {
+                        info!("delisted the lending protocol with controller badge address {}", protocol_controller_address);
+                    }
+                }
+
+            }
+        }
+
+        /// Read only method to get a protocol's permission.
+        pub fn get_protocol_permission(&self, protocol_controller_address: ResourceAddress) -> Option<ProtocolPermission> {
+            self.authorized_protocol.get(&protocol_controller_address).map(|permission| permission.clone())
+        }
+
+        /// This method is to check if the protocol is allowed to do a credit action or not.
+        /// 
+        /// Winding down protocols cannot lend out new loans.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - action: The credit action.
+        pub fn check_permission(&self, protocol_proof: Proof, action: ProtocolAction) {
+
+            let protocol = protocol_proof.resource_address();
+
+            protocol_proof.drop();
+
+            self.assert_permission(&protocol, action);
+
+        }
+
+        /// This method is for listed lending protocols to update an user's credit data.
+        /// 
+        /// Every change on the Credit SBT go through this method, the credit service check each field of the change against the protocol's permission:
+        /// - The credit score cannot be raised, the guarantee, migration, repaid accumulation and score checkpoint data cannot be changed. 
+        /// - Raising the credit's outstanding debt (and changing the credit into an installment credit) require an active protocol with the credit type's capability, 
+        /// the new loan principal is added to the protocol's exposure (capped at the protocol's exposure cap). Winding down protocols can only lower the debt.
+        /// - Lowering the debt or changing the due time require the protocol to hold a loan on the credit, 
+        /// the released principal is capped at the principal the protocol has lent out on the credit and is released from the protocol's exposure.
+        /// - Degrading the credit score or flagging the credit defaulted require the degrade capability, the score checkpoint is moved to current time. 
+        /// The defaulted flag can only be cleared when the credit has no debt.
+        /// 
+        /// The credit service also counts the on-time repayments of a revolving credit into the repaid accumulation 
+        /// and restores the credit score when an installment period is repaid on time.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - credit_id: the user's Credit SBT ID.
+        /// - data: the new credit data.
+        pub fn update_credit(&mut self, protocol_proof: Proof, credit_id: NonFungibleId, mut data: CreditData) {
+
+            let protocol = protocol_proof.resource_address();
+
+            protocol_proof.drop();
+
+            assert!(self.authorized_protocol.contains_key(&protocol), "This protocol is not allowed to use on-chain credit service.");
+
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
+
+            let old_data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+            assert!(data.credit_score <= old_data.credit_score, "The protocol is not allowed to raise the credit score.");
+
+            assert!(
+                data.guaranteed_amount == old_data.guaranteed_amount && data.pledged_amount == old_data.pledged_amount 
+                && data.migrated == old_data.migrated && data.score_checkpoint == old_data.score_checkpoint 
+                && data.repaid_amount_accumulated == old_data.repaid_amount_accumulated, 
+                "The protocol is not allowed to change this credit data."
+            );
+
+            let action = match data.credit_type {
+                CreditType::Revolving(_) => ProtocolAction::Revolving,
+                CreditType::Installment(_) => ProtocolAction::Installment
+            };
+
+            match (old_data.credit_type, data.credit_type) {
+                (CreditType::Revolving(old_types), CreditType::Revolving(types)) => {
+                    assert!(old_types == types, "The protocol is not allowed to change the credit type.");
+                }
+                (CreditType::Revolving(_), CreditType::Installment(_)) => {
+                    self.assert_permission(&protocol, ProtocolAction::Installment);
+                }
+                (CreditType::Installment(old_installment), CreditType::Installment(installment)) => {
+                    assert!(installment.same_terms(&old_installment) && installment.period_counter >= old_installment.period_counter, "The protocol is not allowed to change the installment credit terms.");
+                }
+                (CreditType::Installment(_), CreditType::Revolving(_)) => {
+                    assert!(data.outstanding_principal() == Decimal::ZERO, "The installment loan has not been repaid.");
+                }
+            }
+
+            let current = self.current();
+
+            if data.credit_score < old_data.credit_score || (data.defaulted && !old_data.defaulted) {
+                self.assert_permission(&protocol, ProtocolAction::Degrade);
+                if current != 0 {
+                    data.score_checkpoint = current
+                }
+            }
+
+            let (old_debt, debt) = (old_data.outstanding_debt(), data.outstanding_debt());
+
+            assert!(data.defaulted || !old_data.defaulted || debt == Decimal::ZERO, "The defaulted credit still has debt.");
+
+            if debt > old_debt {
+                self.assert_permission(&protocol, action);
+            }
+
+            let mut loans = self.credit_loans.get(&credit_id).unwrap_or(HashMap::new());
+
+            let time_changed = data.due_time != old_data.due_time || data.current_debt_start_time != old_data.current_debt_start_time;
+
+            if debt < old_debt || (time_changed && debt <= old_debt && !(debt == Decimal::ZERO && data.due_time == 0 && data.current_debt_start_time == 0)) {
+                assert!(loans.contains_key(&protocol), "The protocol doesn't hold any loan on this credit.");
+            }
+
+            let (old_principal, principal) = (old_data.outstanding_principal(), data.outstanding_principal());
+
+            if principal > old_principal {
+
+                self.add_exposure(&protocol, action, principal - old_principal);
+
+                *loans.entry(protocol).or_insert(Decimal::ZERO) += principal - old_principal;
+
+            } else if principal < old_principal {
+
+                let lent = loans.get_mut(&protocol).expect("The protocol doesn't hold any loan on this credit.");
+
+                assert!(old_principal - principal <= *lent, "The protocol can only release the principal it has lent out on this credit, currently {} stable coins.", *lent);
+
+                *lent -= old_principal - principal;
+
+                self.remove_exposure(&protocol, old_principal - principal);
+
+            } else if debt > old_debt {
+
+                loans.entry(protocol).or_insert(Decimal::ZERO);
+
+            }
+
+            if debt == Decimal::ZERO {
+                for (protocol, lent) in loans.drain() {
+                    self.remove_exposure(&protocol, lent);
+                }
+            }
+
+            self.credit_loans.insert(credit_id.clone(), loans);
+
+            let late = old_data.defaulted || old_data.extra_debt != Decimal::ZERO || (current != 0 && old_data.due_time != 0 && old_data.due_time <= current);
+
+            if !late {
+
+                match (old_data.credit_type, data.credit_type) {
+
+                    (CreditType::Revolving(_), CreditType::Revolving(_)) => {
+                        let (old_repayable, repayable) = (old_data.current_debt + old_data.debt_interest, data.current_debt + data.debt_interest);
+                        if repayable < old_repayable {
+                            data.repaid_amount_accumulated += old_repayable - repayable
+                        }
+                    }
+
+                    (CreditType::Installment(old_installment), new_type) => {
+                        let repaid_period = match new_type {
+                            CreditType::Installment(installment) => {installment.period_counter > old_installment.period_counter}
+                            CreditType::Revolving(_) => {true}
+                        };
+                        if repaid_period {
+                            let restore_rate = self.credit_scoring_rates.monthly.restore_rate;
+                            data.credit_score = if data.credit_score <= dec!("100") - restore_rate { data.credit_score + restore_rate } else { dec!("100") };
+                            info!("Your credit score has been restored to {} because of your on-time installment repayment", data.credit_score);
+                        }
+                    }
+
+                    _ => {}
+                }
+            }
+
+            self.controller_badge.authorize(|| {
+                resource_manager.update_non_fungible_data(&credit_id, Credit { data })
+            });
+
+        }
+
+        /// This method is for listed lending protocols to restore an user's credit score when the on-time repaid amount 
+        /// on the revolving credit has reached the user's maximum credit amount.
+        /// 
+        /// The repaid accumulation is counted by the credit service through [update_credit](GroundCredit::update_credit) and reset after the credit score is restored.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - id: the user's Identity SBT ID.
+        pub fn restore_credit_score(&self, protocol_proof: Proof, id: NonFungibleId) {
+
+            self.check_protocol(protocol_proof);
+
+            let credit_id = self.credit_list.get(&id).flatten().expect("The user doesn't have a credit SBT.");
+
+            let id_data = borrow_resource_manager!(self.identity_sbt_address()).get_non_fungible_data::<Identity>(&id).data;
+
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
+
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+            if !matches!(data.credit_type, CreditType::Revolving(_)) {
+                return
+            }
+
+            let maximum = self.get_credit_limit_by_data(id, id_data, data);
+
+            if maximum == Decimal::ZERO || data.repaid_amount_accumulated < maximum {
+                return
+            }
+
+            let restore_rate = self.credit_scoring_rates.restore_rate(&data.credit_type);
+
+            let credit_score = if data.credit_score <= dec!("100") - restore_rate {
+                data.credit_score + restore_rate
+            } else { dec!("100") };
+
+            self.controller_badge.authorize(|| {
+                resource_manager.update_non_fungible_data(&credit_id, 
+                    Credit {
+                        data: CreditData {
+                            credit_score,
+                            repaid_amount_accumulated: Decimal::ZERO,
+                            ..data
+                        }
+                    }
+                )
+            });
+
+            info!("Your credit score has been restored to {} because of your on-time repayment frequency", credit_score);
+
+        }
+
+        /// This method is for listed protocols to burn an used Installment Credit Badge.
+        /// 
+        /// The protocol has to be allowed to let users take installment loans.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - installment_credit_badge: The used Installment Credit Badge.
+        pub fn burn_installment_credit_badge(&self, protocol_proof: Proof, installment_credit_badge: Bucket) {
+
+            assert!(installment_credit_badge.resource_address() == self.installment_credit_badge, "Wrong resource!");
+
+            self.check_permission(protocol_proof, ProtocolAction::Installment);
+
+            self.controller_badge.authorize(|| {
+                installment_credit_badge.burn()
+            });
+
+        }
+
+        /// This method is for listed lending protocols to record a new loan which is not lent out through [update_credit](GroundCredit::update_credit) (E.g: delegated credit).
+        /// 
+        /// The protocol's aggregate exposure cannot exceed its exposure cap.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - action: The credit action of the loan (revolving or installment).
+        /// - credit_id: The borrower's Credit SBT ID.
+        /// - amount: The loan principal.
+        pub fn record_borrow(&mut self, protocol_proof: Proof, action: ProtocolAction, credit_id: NonFungibleId, amount: Decimal) {
+
+            assert!(action != ProtocolAction::Degrade && amount > Decimal::ZERO, "Wrong data provided!");
+
+            let protocol = protocol_proof.resource_address();
+
+            protocol_proof.drop();
+
+            self.add_exposure(&protocol, action, amount);
+
+            let mut loans = self.recorded_loans.get(&credit_id).unwrap_or(HashMap::new());
+
+            *loans.entry(protocol).or_insert(Decimal::ZERO) += amount;
+
+            self.recorded_loans.insert(credit_id, loans);
+
+        }
+
+        /// This method is for listed lending protocols to record a repaid (or written-off) loan principal which is not released through [update_credit](GroundCredit::update_credit) (E.g: delegated credit).
+        /// 
+        /// The released principal is capped at the principal the protocol has recorded on the borrower's credit. Winding down protocols can still record repayments.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - credit_id: The borrower's Credit SBT ID.
+        /// - amount: The repaid principal.
+        pub fn record_repay(&mut self, protocol_proof: Proof, credit_id: NonFungibleId, amount: Decimal) {
+
+            let protocol = protocol_proof.resource_address();
+
+            protocol_proof.drop();
+
+            assert!(self.authorized_protocol.contains_key(&protocol), "This protocol is not allowed to use on-chain credit service.");
+
+            let mut loans = self.recorded_loans.get(&credit_id).unwrap_or(HashMap::new());
+
+            let recorded = loans.get(&protocol).copied().unwrap_or(Decimal::ZERO);
+
+            let amount = if amount <= recorded { amount } else { recorded };
+
+            if recorded - amount == Decimal::ZERO {
+                loans.remove(&protocol);
+            } else {
+                loans.insert(protocol, recorded - amount);
+            }
+
+            self.recorded_loans.insert(credit_id, loans);
+
+            self.remove_exposure(&protocol, amount);
+
+        }
+
+        /// This method is for the service operator to trust other credit service, 
+        /// users can migrate their Credit SBT between trusted credit services.
+        /// 
+        /// Input: the credit service component address and the credit service controller badge address.
+        pub fn trust_credit_service(&mut self, credit_service: ComponentAddress, controller_badge: ResourceAddress) {
+            self.trusted_services.insert(credit_service, controller_badge);
+            info!("Trusted the credit service {}", credit_service);
+        }
+
+        /// This method is for the service operator to untrust a credit service.
+        pub fn untrust_credit_service(&mut self, credit_service: ComponentAddress) {
+            match self.trusted_services.remove(&credit_service) {
+                None => {info!("Doesn't have this credit service on the trusted list.")}
+                Some(_) => {info!("Untrusted the credit service {}", credit_service)}
+            }
+        }
+
+        /// Read only method to get the controller badge address of this credit service.
+        pub fn controller_badge_address(&self) -> ResourceAddress {
+            self.controller_badge.resource_address()
+        }
+
+        /// This method is for trusted credit services to export an user's Credit SBT.
+        /// 
+        /// The Credit SBT on this credit service will be marked migrated and cannot be used anymore, the user can import the credit back later.
+        /// ### Input: 
+        /// - service_proof: the Proof of the destination credit service's controller badge.
+        /// - sbt_id: the user's Identity SBT ID.
+        /// ### Output: 
+        /// The credit attestation.
+        pub fn export_credit(&mut self, service_proof: Proof, sbt_id: NonFungibleId) -> CreditAttestation {
+
+            let destination = self.trusted_services.iter()
+                .find(|(_, badge)| **badge == service_proof.resource_address())
+                .map(|(address, _)| address.clone())
+                .expect("This credit service is not trusted.");
+
+            service_proof.drop();
+
+            assert!(self.migrated.get(&sbt_id).flatten().is_none(), "The credit has already been migrated.");
 
-        //     } else if installment_data.period_counter == installment_data.period_max {
+            let credit_id = self.credit_list.get(&sbt_id).flatten().expect("The user doesn't have a credit SBT.");
 
-        //         installment_data.period_counter += 1;
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
 
-        //         data = Credit {
-        //             credit_type: CreditType::Revolving(RevolvingTypes::Monthly),
-        //             current_debt_start_time: 0,
-        //             current_debt: Decimal::ZERO,
-        //             debt_interest: Decimal::ZERO,
-        //             due_time: 0,
-        //             ..data
-        //         };
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
 
-        //         info!("You have repaid all your installment loan, your credit will change into the Monthly Revolving Credit");
+            assert!(data.due_time == 0 && !data.defaulted, "The user has to repay all the current debt first.");
 
-        //     } else {panic!("You have already repaid all the installment credit debt. Please consider changing your credit type into revolving credit.")};
+            assert!(data.pledged_amount == Decimal::ZERO && data.guaranteed_amount == Decimal::ZERO, "The user has to release all the guarantees first.");
 
-        //     self.controller_badge.authorize(|| { 
-        //         credit.update_data(Credit {
-        //             ..data
-        //         })
-        //     });
+            self.controller_badge.authorize(|| {
+                resource_manager.update_non_fungible_data(&credit_id, 
+                    Credit {
+                        data: CreditData {
+                            migrated: true,
+                            ..data
+                        }
+                    }
+                )
+            });
 
-        //     credit_proof.drop();
+            self.migrated.insert(sbt_id.clone(), Some(destination));
 
-        //     return data.current_debt
+            self.credit_list.insert(sbt_id.clone(), None);
 
-        // }
+            self.lapse_blacklist(&sbt_id);
 
-        // /// This method is for the service operator to allow a protocol using on-chain credit service.
-        // /// 
-        // /// Input: The protocol controller badge resource address
-        // pub fn list_protocol(&mut self, protocol_controller_address: ResourceAddress) {
+            info!("The Credit SBT no.{} has been migrated to the credit service {}", credit_id, destination);
 
-        //     self.authorized_protocol.push(protocol_controller_address);
+            CreditAttestation {
+                sbt_id: sbt_id.clone(),
+                credit_id,
+                data,
+                blacklist: self.blacklist.get(&sbt_id)
+            }
 
-        //     info!("listed the lending protocol with controller badge address {}", protocol_controller_address);
+        }
 
-        // }
+        /// This method is for users to migrate their Credit SBT from a trusted credit service into this credit service.
+        /// ### Input: 
+        /// - id_sbt: the proof of user's Identity SBT.
+        /// - source: the source credit service component address.
+        /// ### Output: 
+        /// The new Credit SBT with the same credit score and credit history.
+        pub fn import_credit(&mut self, id_sbt: Proof, source: ComponentAddress) -> Bucket {
 
-        // /// This method is for the service operator to deny a protocol using on-chain credit service.
-        // /// All protocols is denied by default.
-        // /// 
-        // /// Input: The protocol controller badge resource address
-        // pub fn delist_protocol(&mut self, protocol_controller_address: ResourceAddress) {
+            assert!(self.trusted_services.contains_key(&source), "This credit service is not trusted.");
 
-        //     let index = self.authorized_protocol.iter().position(|x| *x == protocol_controller_address);
+            let sbt_id = id_sbt.non_fungible::<Identity>().id();
 
-        //     match index {
-        //         None => {info!("Doesn't have this protocol on the list.")}
-        //         Some(x) => {
+            let identity_service: GroundID = self.identity_service.into();
+            identity_service.check_resource(id_sbt.resource_address());
 
-        //             self.authorized_protocol.remove(x);
+            id_sbt.drop();
 
-        //             info!("delisted the lending protocol with controller badge address {}", protocol_controller_address);
+            assert!(matches!(self.credit_list.get(&sbt_id).flatten(), None), "You already has a credit SBT");
 
-        //         }
-        //     }
-        // }
+            let source_service: GroundCredit = source.into();
 
-        /// This method is for the service operator to allow a protocol using on-chain credit service.
-        /// 
-        /// Input: The protocol controller badge resource address
-        pub fn list_protocol(&mut self, protocol_controller_address: ResourceAddress) {
+            let attestation = source_service.export_credit(self.controller_badge.create_proof(), sbt_id.clone());
+
+            assert!(attestation.sbt_id == sbt_id, "Wrong attestation!");
 
-            self.authorized_protocol.push(protocol_controller_address);
+            if let Some(entry) = attestation.blacklist {
+                self.blacklist.insert(sbt_id.clone(), entry);
+            }
+
+            if self.migrated.get(&sbt_id).flatten().is_some() {
+                self.migrated.insert(sbt_id.clone(), None);
+            }
+
+            let id = NonFungibleId::random();
+
+            self.credit_list.insert(sbt_id, Some(id.clone()));
+
+            let current = self.current();
+
+            let data = attestation.data;
+
+            info!("You got new Credit SBT no.{} migrated from the credit service {}", id.clone(), source);
 
             self.controller_badge.authorize(|| {
                 borrow_resource_manager!(self.credit_sbt)
-                .set_updateable_non_fungible_data(rule!(require(self.controller_badge.resource_address()) || require(protocol_controller_address)));
-                borrow_resource_manager!(self.installment_credit_badge)
-                .set_burnable(rule!(require(protocol_controller_address)));
-            });
-
-            info!("listed the lending protocol with controller badge address {}", protocol_controller_address);
+                    .mint_non_fungible(
+                        &id,
+                        Credit {
+                            data: CreditData {
+                                current_debt_start_time: 0,
+                                current_debt: Decimal::zero(),
+                                debt_interest: Decimal::zero(),
+                                due_time: 0,
+                                extra_debt: Decimal::zero(),
+                                guaranteed_amount: Decimal::zero(),
+                                pledged_amount: Decimal::zero(),
+                                defaulted: false,
+                                score_checkpoint: current,
+                                migrated: false,
+                                ..data
+                            }
+                        }
+                )
+            })
 
         }
 
-        // /// This method is to check if the protocol is listed or not.
-        // /// 
-        // /// Input: The protocol controller badge proof.
-        // pub fn check_protocol(&self, protocol_proof: Proof) {
-        //     assert!(self.authorized_protocol.contains(&protocol_proof.resource_address()), "This protocol is not allowed to use on-chain credit service.");
-        //     protocol_proof.drop();
-        // }
+        /// This method is to check if the protocol is listed or not.
+        /// 
+        /// Input: The protocol controller badge proof.
+        pub fn check_protocol(&self, protocol_proof: Proof) {
+            assert!(self.authorized_protocol.contains_key(&protocol_proof.resource_address()), "This protocol is not allowed to use on-chain credit service.");
+            protocol_proof.drop();
+        }
 
         // /// This method is for lending protocol to update the debt amount of an user.
         // /// ### Input: 
@@ -1086,10 +2354,41 @@ blueprint! {
             
         // }
 
+        /// This method is for the service operator to change oracle using.
+        /// 
+        /// The Oracle is used to keep track of blacklist listing time and expiry.
+        /// ### Output: 
+        /// The old oracle badge (if any).
+        pub fn use_oracle(&mut self, oracle: ComponentAddress, data_badge: Bucket) -> Option<Bucket> {
+            if let Some((address, vault)) = &mut self.oracle {
+                *address = oracle;
+                let bucket = vault.take_all();
+                vault.put(data_badge);
+                return Some(bucket)
+            }
+            self.oracle = Some((oracle, Vault::with_bucket(data_badge)));
+            None
+        }
+
         /// This method is for the service operator to blacklist an ID SBT.
-        pub fn blacklist(&mut self, id: NonFungibleId) {
-            info!("ID address {} has been blacklisted", id.clone());
-            self.blacklist.push(id);
+        /// ### Input: 
+        /// - id: the Identity SBT ID.
+        /// - reason: the reason code.
+        /// - expiry: the expiry time (unix), None if the restriction is permanent.
+        pub fn blacklist(&mut self, id: NonFungibleId, reason: BlacklistReason, expiry: Option<u64>) {
+            self.list(id, reason, expiry, BlacklistAuthority::Operator)
+        }
+
+        /// This method is for listed lending protocols to blacklist an ID SBT.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - id: the Identity SBT ID.
+        /// - reason: the reason code.
+        /// - expiry: the expiry time (unix), None if the restriction is permanent.
+        pub fn blacklist_by_protocol(&mut self, protocol_proof: Proof, id: NonFungibleId, reason: BlacklistReason, expiry: Option<u64>) {
+            let protocol = protocol_proof.resource_address();
+            self.check_permission(protocol_proof, ProtocolAction::Degrade);
+            self.list(id, reason, expiry, BlacklistAuthority::Protocol(protocol))
         }
 
         /// This method is for the service operator to whitelist an ID SBT.
@@ -1097,25 +2396,38 @@ blueprint! {
         /// All ID SBTs is whitelisted by default.
         pub fn whitelist(&mut self, id: NonFungibleId) {
 
-            let index = self.blacklist.iter().position(|x| *x == id);
+            self.lapse_blacklist(&id);
 
-            match index {
-                None => {info!("Doesn't have this ID on the blacklist.")}
-                Some(x) => {
+            match self.blacklist.get(&id) {
+                Some(entry) if entry.active => {
 
-                    self.blacklist.remove(x);
+                    self.blacklist.insert(id.clone(), BlacklistEntry {
+                        active: false,
+                        ..entry
+                    });
 
-                    info!("ID address {} has been whitelisted", id.clone());
+                    info!("ID address {} has been whitelisted", id);
 
                 }
+                _ => {info!("Doesn't have this ID on the blacklist.")}
             }
         }
 
+        /// Read only method to get the blacklist entry of an ID SBT.
+        /// 
+        /// The entry is reported inactive after the expiry time.
+        pub fn get_blacklist_entry(&self, id: NonFungibleId) -> Option<BlacklistEntry> {
+            self.blacklist.get(&id).map(|entry| BlacklistEntry {
+                active: self.is_active(&entry),
+                ..entry
+            })
+        }
+
         /// This method is to check if the SBT address is blacklisted or not.
         pub fn check_id(&self, id_proof: Proof) -> Proof {
             let identity_service: GroundID = self.identity_service.into();
             identity_service.check_resource(id_proof.resource_address());
-            assert!(!self.blacklist.contains(&id_proof.non_fungible::<Identity>().id()), "You're not allowed to use credit. Please contact your credit issuer.");
+            assert!(!self.is_blacklisted(&id_proof.non_fungible::<Identity>().id()), "You're not allowed to use credit. Please contact your credit issuer.");
             id_proof
         }
 
@@ -1125,7 +2437,8 @@ blueprint! {
             let sbt_id = id_proof.non_fungible::<Identity>().id();
             let credit = credit_proof.non_fungible::<Credit>();
             assert!(credit_proof.resource_address() == self.credit_sbt, "Wrong resource!");
-            assert!(credit.id() == self.credit_list.get(&sbt_id).unwrap(), "Wrong credit SBT!");
+            assert!(!credit.data().data.migrated, "Your credit has been migrated to other credit service.");
+            assert!(self.credit_list.get(&sbt_id).flatten() == Some(credit.id()), "Wrong credit SBT!");
             (id_proof, credit_proof)
         }
 
@@ -1133,13 +2446,391 @@ blueprint! {
         pub fn check_id_and_credit_by_data(&self, id: NonFungibleId, id_resource: ResourceAddress, credit_id: NonFungibleId, credit_resource: ResourceAddress) {
             let identity_service: GroundID = self.identity_service.into();
             identity_service.check_resource(id_resource);
-            assert!(!self.blacklist.contains(&id), "You're not allowed to use credit. Please contact your credit issuer.");
+            assert!(!self.is_blacklisted(&id), "You're not allowed to use credit. Please contact your credit issuer.");
             assert!(credit_resource == self.credit_sbt, "Wrong resource!");
-            assert!(credit_id == self.credit_list.get(&id).unwrap(), "Wrong credit SBT!");
+            assert!(self.migrated.get(&id).flatten().is_none(), "Your credit has been migrated to other credit service.");
+            assert!(self.credit_list.get(&id).flatten() == Some(credit_id), "Wrong credit SBT!");
         }
 
-        pub fn check_installment_credit(&self, resource_address: ResourceAddress) {
+        /// This method is to check the Installment Credit Badge resource and if the badge has been revoked or not.
+        pub fn check_installment_credit(&self, resource_address: ResourceAddress, id: NonFungibleId) {
             assert!(resource_address == self.installment_credit_badge, "Wrong resource!");
+            assert!(self.revoked_installment_credit.get(&id).is_none(), "This installment credit has been revoked.");
+        }
+
+        /// Get the Credit SBT ID of an Identity SBT ID.
+        /// 
+        /// Return None if the credit has been migrated to other credit service.
+        pub fn get_credit_id(&self, id: NonFungibleId) -> Option<NonFungibleId> {
+            self.credit_list.get(&id).flatten()
+        }
+
+        /// Get the Credit SBT resource address.
+        pub fn credit_sbt_address(&self) -> ResourceAddress {
+            self.credit_sbt
+        }
+
+        /// Get the Identity SBT resource address of the credit service's identity service.
+        pub fn identity_sbt_address(&self) -> ResourceAddress {
+            let identity_service: GroundID = self.identity_service.into();
+            identity_service.identity_sbt_address()
+        }
+
+        /// This method is for active lending protocols to apply the time-based credit score recovery on an user's Credit SBT.
+        /// 
+        /// The trust factor is read from the user's ID SBT and the time is read from the credit service's Oracle.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - id: the user's Identity SBT ID.
+        pub fn recover_credit_score_by_data(&self, protocol_proof: Proof, id: NonFungibleId) {
+
+            let permission = self.authorized_protocol.get(&protocol_proof.resource_address()).expect("This protocol is not allowed to use on-chain credit service.");
+
+            protocol_proof.drop();
+
+            assert!(permission.status == ProtocolStatus::Active, "This protocol is not allowed to do this credit action.");
+
+            let credit_id = self.credit_list.get(&id).flatten().expect("The user doesn't have a credit SBT.");
+
+            let trust_factor = borrow_resource_manager!(self.identity_sbt_address()).get_non_fungible_data::<Identity>(&id).data.trust_factor;
+
+            self.recover(&credit_id, trust_factor, self.current())
+
+        }
+
+        /// This method is for the service operator to change the time-based credit score recovery model.
+        /// 
+        /// Input None to stop using the time-based recovery.
+        pub fn change_credit_recovery(&mut self, credit_recovery: Option<CreditRecovery>) {
+            if let Some(recovery) = &credit_recovery {
+                recovery.check_rate()
+            }
+            self.credit_recovery = credit_recovery
+        }
+
+        /// This method is for the service operator to change the business credit terms.
+        /// 
+        /// Input None to stop providing business credit terms, business credit is then calculated the same as a Person's revolving credit.
+        /// 
+        /// Syntax: ```Some(Struct({revenue_rate}, {owner_weight}, {owner_degrade_rate}))```
+        pub fn change_business_credit_terms(&mut self, business_credit_terms: Option<BusinessCreditTerms>) {
+            if let Some(terms) = &business_credit_terms {
+                terms.check_rates()
+            }
+            self.business_credit_terms = business_credit_terms
+        }
+
+        /// Read only method to get the business credit terms.
+        pub fn business_credit_terms(&self) -> Option<BusinessCreditTerms> {
+            self.business_credit_terms
+        }
+
+        /// This method is for the service operator to link the Person owners to a Business identity. (Require off-chain process)
+        /// 
+        /// The owners' credit scores will be counted into the business's credit limit 
+        /// and the owners will be degraded when the business's loan is declared defaulted.
+        /// ### Input: 
+        /// - id_resource: the Identity SBT resource address.
+        /// - business: the business's Identity SBT ID.
+        /// - owners: the owners' Identity SBT IDs, duplicated IDs are only linked once.
+        pub fn link_business_owners(&self, id_resource: ResourceAddress, business: NonFungibleId, mut owners: Vec<NonFungibleId>) {
+
+            let mut linked = HashSet::new();
+
+            owners.retain(|owner| linked.insert(owner.clone()));
+
+            let identity_service: GroundID = self.identity_service.into();
+            identity_service.check_resource(id_resource);
+
+            let resource_manager = borrow_resource_manager!(id_resource);
+
+            assert!(matches!(resource_manager.get_non_fungible_data::<Identity>(&business).data.identity, IdentityType::Business), "The ID SBT is not a Business SBT.");
+
+            for owner in &owners {
+                resource_manager.get_non_fungible_data::<Identity>(owner).data.identity.check_human_proof();
+                assert!(self.credit_list.get(owner).flatten().is_some(), "The owner {} doesn't have a credit SBT.", owner);
+            }
+
+            info!("Linked {} owners to the business ID {}", owners.len(), business);
+
+            self.business_owners.insert(business, owners);
+
+        }
+
+        /// Read only method to get the Person owners of a Business identity.
+        pub fn get_business_owners(&self, business: NonFungibleId) -> Vec<NonFungibleId> {
+            self.business_owners.get(&business).unwrap_or(Vec::new())
+        }
+
+        /// This method is for listed lending protocols to degrade the Person owners' credit when the business's loan is declared defaulted.
+        /// 
+        /// Do nothing if the ID SBT doesn't have any linked owner.
+        /// ### Input: 
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - business: the business's Identity SBT ID.
+        /// - current: Current time data fed in through the protocol. (unix)
+        pub fn degrade_business_owners(&self, protocol_proof: Proof, business: NonFungibleId, current: u64) {
+
+            self.check_permission(protocol_proof, ProtocolAction::Degrade);
+
+            let owners = match self.business_owners.get(&business) {
+                None => {return}
+                Some(owners) => owners
+            };
+
+            let degrade_rate = match self.business_credit_terms {
+                None => self.credit_scoring_rates.monthly.degrade_rate,
+                Some(terms) => terms.owner_degrade_rate
+            };
+
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
+
+            for owner in owners {
+                if let Some(credit_id) = self.credit_list.get(&owner).flatten() {
+
+                    let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+                    let credit_score = if data.credit_score >= degrade_rate {
+                        data.credit_score - degrade_rate
+                    } else { Decimal::zero() };
+
+                    self.controller_badge.authorize(|| {
+                        resource_manager.update_non_fungible_data(&credit_id, 
+                            Credit {
+                                data: CreditData {
+                                    credit_score,
+                                    score_checkpoint: current,
+                                    ..data
+                                }
+                            }
+                        )
+                    });
+
+                    info!("The credit score of the owner ID {} has been degraded to {} because of the business's loan default", owner, credit_score);
+                }
+            }
+        }
+
+        /// Read only method to get the time-based credit score recovery model.
+        pub fn credit_recovery(&self) -> Option<CreditRecovery> {
+            self.credit_recovery
+        }
+
+        /// Apply the time-based credit score recovery on a Credit SBT.
+        /// 
+        /// Only the elapsed periods with no late event are counted, 
+        /// the checkpoint is moved to current time if the user is late on repayment or the credit is defaulted.
+        fn recover(&self, credit_id: &NonFungibleId, trust_factor: Decimal, current: u64) {
+
+            let recovery = match self.credit_recovery {
+                None => {return}
+                Some(recovery) => {recovery}
+            };
+
+            if current == 0 {
+                return
+            }
+
+            let resource_manager = borrow_resource_manager!(self.credit_sbt);
+
+            let data = resource_manager.get_non_fungible_data::<Credit>(credit_id).data;
+
+            if current <= data.score_checkpoint {
+                return
+            }
+
+            let late = data.defaulted || (data.due_time != 0 && data.due_time <= current);
+
+            let (credit_score, score_checkpoint) = if late || data.score_checkpoint == 0 {
+
+                (data.credit_score, current)
+
+            } else {
+
+                let periods = (current - data.score_checkpoint) / recovery.period;
+
+                let score = if data.credit_score >= trust_factor {
+                    data.credit_score
+                } else {
+                    let score = data.credit_score + recovery.restore_rate * periods;
+                    if score > trust_factor { trust_factor } else { score }
+                };
+
+                (score, data.score_checkpoint + periods * recovery.period)
+
+            };
+
+            if score_checkpoint == data.score_checkpoint {
+                return
+            }
+
+            self.controller_badge.authorize(|| {
+                resource_manager.update_non_fungible_data(credit_id, 
+                    Credit {
+                        data: CreditData {
+                            credit_score,
+                            score_checkpoint,
+                            ..data
+                        }
+                    }
+                )
+            });
+
+            if credit_score > data.credit_score {
+                info!("Your credit score has been restored to {} because of your on-time repayment history", credit_score);
+            }
+        }
+
+        /// Get the current time from the Oracle, return 0 if the credit service doesn't use any Oracle.
+        fn current(&self) -> u64 {
+            match &self.oracle {
+                None => 0,
+                Some((oracle, badge)) => {
+                    let neuracle: NeuRacle = oracle.clone().into();
+                    let current = neuracle.get_data(badge.create_proof());
+                    current.parse().expect("Wrong data!")
+                }
+            }
+        }
+
+        /// Put an ID SBT on the blacklist.
+        fn list(&mut self, id: NonFungibleId, reason: BlacklistReason, expiry: Option<u64>, authority: BlacklistAuthority) {
+
+            let current = self.current();
+
+            if let Some(expiry) = expiry {
+                assert!(self.oracle.is_some(), "The credit service doesn't use any Oracle, cannot set the blacklist expiry.");
+                assert!(expiry > current, "Wrong data provided!");
+            }
+
+            self.blacklist.insert(id.clone(), BlacklistEntry {
+                reason,
+                listed_time: current,
+                expiry,
+                authority,
+                active: true
+            });
+
+            match expiry {
+                None => {info!("ID address {} has been blacklisted", id)}
+                Some(expiry) => {info!("ID address {} has been blacklisted until {} (unix time)", id, expiry)}
+            }
+        }
+
+        /// Calculate the maximum revolving credit amount by a cubic function with the income, id trust score and credit score as the params, 
+        /// plus the amount other users has pledged to guarantee the credit.
+        fn maximum_credit(id_data: &IdentityData, data: &CreditData, types: RevolvingTypes) -> Decimal {
+
+            let yearly_maximum_credit = id_data.income * (id_data.trust_factor / 100) * (data.credit_score / 100);
+
+            match types {
+                RevolvingTypes::Monthly => {yearly_maximum_credit / dec!("12")}
+                RevolvingTypes::Yearly => {yearly_maximum_credit}
+            } + data.guaranteed_amount
+        }
+
+        /// Calculate the maximum revolving credit amount of a Business identity by the business credit terms.
+        fn maximum_business_credit(&self, id: &NonFungibleId, id_data: &IdentityData, data: &CreditData, types: RevolvingTypes) -> Decimal {
+
+            let terms = self.business_credit_terms.unwrap();
+
+            let owners = self.business_owners.get(id).unwrap_or(Vec::new());
+
+            let credit_score = if owners.is_empty() {
+                data.credit_score * (dec!("100") - terms.owner_weight) / dec!("100")
+            } else {
+                let mut total = Decimal::ZERO;
+                for owner in &owners {
+                    if let Some(credit_id) = self.credit_list.get(owner).flatten() {
+                        let owner_data = borrow_resource_manager!(self.credit_sbt).get_non_fungible_data::<Credit>(&credit_id).data;
+                        if !owner_data.defaulted && !self.is_blacklisted(owner) {
+                            total += owner_data.credit_score
+                        }
+                    }
+                }
+                (data.credit_score * (dec!("100") - terms.owner_weight) + total / Decimal::from(owners.len() as u64) * terms.owner_weight) / dec!("100")
+            };
+
+            let yearly_maximum_credit = id_data.income * (terms.revenue_rate / 100) * (id_data.trust_factor / 100) * (credit_score / 100);
+
+            match types {
+                RevolvingTypes::Monthly => {yearly_maximum_credit / dec!("12")}
+                RevolvingTypes::Yearly => {yearly_maximum_credit}
+            } + data.guaranteed_amount
+        }
+
+        /// Assert the protocol is allowed to do a credit action.
+        fn assert_permission(&self, protocol: &ResourceAddress, action: ProtocolAction) {
+
+            let permission = self.authorized_protocol.get(protocol).expect("This protocol is not allowed to use on-chain credit service.");
+
+            let allowed = match action {
+                ProtocolAction::Revolving => {permission.status == ProtocolStatus::Active && permission.capabilities.revolving}
+                ProtocolAction::Installment => {permission.status == ProtocolStatus::Active && permission.capabilities.installment}
+                ProtocolAction::Degrade => {permission.capabilities.may_degrade}
+            };
+
+            assert!(allowed, "This protocol is not allowed to do this credit action.");
+
+        }
+
+        /// Add a new loan principal to the protocol's exposure.
+        fn add_exposure(&mut self, protocol: &ResourceAddress, action: ProtocolAction, amount: Decimal) {
+
+            self.assert_permission(protocol, action);
+
+            let permission = self.authorized_protocol.get_mut(protocol).unwrap();
+
+            assert!(permission.exposure + amount <= permission.capabilities.exposure_cap, 
+                "The protocol has reached its exposure cap on this credit service, currently it can only lend out {} stable coins.", permission.capabilities.exposure_cap - permission.exposure
+            );
+
+            permission.exposure += amount;
+
+        }
+
+        /// Release the repaid (or written-off) principal from the protocol's exposure.
+        /// 
+        /// Nothing is released if the protocol has been delisted.
+        fn remove_exposure(&mut self, protocol: &ResourceAddress, amount: Decimal) {
+
+            if let Some(permission) = self.authorized_protocol.get_mut(protocol) {
+                permission.exposure = if permission.exposure >= amount {
+                    permission.exposure - amount
+                } else { Decimal::ZERO };
+            }
+
+        }
+
+        /// Check if an ID SBT is blacklisted or not.
+        /// 
+        /// The blacklist entry is treated as lapsed after the expiry time.
+        fn is_blacklisted(&self, id: &NonFungibleId) -> bool {
+            match self.blacklist.get(id) {
+                Some(entry) => self.is_active(&entry),
+                None => false
+            }
+        }
+
+        /// Check if a blacklist entry is still restricting the ID SBT at the current time.
+        fn is_active(&self, entry: &BlacklistEntry) -> bool {
+            entry.active && match entry.expiry {
+                Some(expiry) => self.current() < expiry,
+                None => true
+            }
+        }
+
+        /// Mark the blacklist entry of an ID SBT inactive if it has expired.
+        fn lapse_blacklist(&mut self, id: &NonFungibleId) {
+            if let Some(entry) = self.blacklist.get(id) {
+                if entry.active && !self.is_active(&entry) {
+                    self.blacklist.insert(id.clone(), BlacklistEntry {
+                        active: false,
+                        ..entry
+                    });
+                    info!("The blacklist entry of ID address {} has expired, the ID has been whitelisted", id);
+                }
+            }
         }
 
         /// The method for the service operators to change on-chain credit scoring rates. syntax:
@@ -11,7 +11,7 @@
 //!
 //! - The Credit Service is for the protocol to keep track and update the borrower's credit data: current debt (include initial debt, debt interest and extra debt by late repayment), credit score, credit due time, credit start time.
 //!
-//! - The Oracle solution is for the protocol to keep track on the passage of time, to see which repayment is on-time (or late) and which lending accounts are eligible for the interest from borrowers, enable "bank level" earning tracker for lenders.
+//! - The Oracle solution is for the protocol to keep track on the passage of time, to see which repayment is on-time (or late) and how long each lending account's capital has been lent, enable "bank level" earning tracker for lenders.
 //!
 //! - The DAO solution is to run the protocol by collective actions, reduce human "bias" in the lending protocol. 
 //!
@@ -22,16 +22,41 @@
 //! - Change the DAO component address the protocol is using.
 //! - Change the Oracle component address the protocol is using.
 //! - Funding the Oracle account from a badge received from that Oracle.
+//! - Change the GroundTimeSource component the protocol is using, which validate the oracle (or a local GroundClock) time before the protocol use it.
 //! - Change the protocol's revolving credit interest rates.
+//! - Change the protocol's utilization-based interest rate model (the revolving credit interest rates are used as base rates).
 //! - Change the protocol's fee and compensate rate.
+//! - Change the flash loan fee.
+//! - Change the insurance reserve factor (the share of all interest that goes to the insurance reserve).
+//! - Start the pool token mode, which let lenders hold their lending as a fungible pool-share token.
+//! - List, delist checkout components which can take the revolving loan on behalf of borrowers to pay merchants.
+//! - List stablecoin markets with their own interest rates, peg feed and peg policy (the accepted peg bounds and the maximum age of a peg quote), change a market's interest rates or peg policy and take the market's fee.
+//! - Change the protocol's tranche policy (the minimum junior ratio and the junior interest premium).
+//! - Change the term deposit bonuses (the extra share of interest for 3, 6 and 12 months term deposits).
+//! - Change the protocol's default policy (the number of missed repayment periods or days overdue before a loan can be declared defaulted).
 //! - Change the protocol's tolerance threshold (the minimum remained percent in protocol's vault allowed for user to take a loan).
+//! - Change the protocol's exposure limits (the maximum share of the pool per borrower, per credit product and to Business and Organization identities).
+//! - Change the protocol's guardian, who can pause and resume deposit, borrow, repay, withdraw and compensate actions separately.
+//! - Change the protocol's circuit breakers (the maximum oracle time jump and the maximum vault drop in one ledger epoch).
 //! - Take the protocol's fee.
 //! - Deposit a stable coin bucket into the protocol's vault to support the protocol in case of loan default.
+//! - Deposit a stable coin bucket into the insurance reserve and check the reserve's coverage ratio.
+//! - Package a defaulted position as a debt claim NFT and sell it in a descending price auction.
 //!
 //! 2. **Lenders**: Any wallet address (permissionless) wish to lend the protocol their stable coin to maximize earn rates. Through the blueprint's method, *lenders* are allowed to:
 //! - Lend an amount of stable coins into the protocol to earn interest and get the Account badge.
+//! - Choose a 3, 6 or 12 months term when lending to earn a bonus share of interest, the term deposit cannot be withdrawn until maturity.
+//! - Lend into the junior tranche, which absorbs loan defaults first and earns a higher share of interest, or the senior tranche, which is protected by the junior tranche.
 //! - Withdraw part of (or all) the return amount from the Account badge.
+//! - Check the Account badges' return amount, include the interest released to them.
+//! - Queue a withdrawal when the protocol's vault is short, then claim the filled amount (or cancel) with the withdrawal ticket.
+//! - Lend for a transferable pool-share token instead (if the protocol uses the pool token mode), redeem the token, or convert the Account badges into the token.
+//! - Lend a listed stablecoin into its market for the market share token, and redeem the token.
 //! - Take the compensate amount from the DAO running this protocol in the worse case of cooperated loan default.
+//! - Delegate part of their lending amount to a specific borrower at their own interest rate, and revoke the delegation (the drawn part is released when the borrower repay it).
+//! - Call the guarantees of an overdue borrower to charge the unpaid debt on the borrower's guarantors.
+//! - Declare a default on a loan taken from the protocol when the borrower is overdue past the protocol's default policy.
+//! - Buy a debt claim from the auction, and take the defaulted borrower's repayments routed to the claim.
 //!
 //! 2. **Borrowers**: Permissioned wallet address (require ID SBT and Credit SBT) can make an automated collateral-free 
 //! loan through this blueprint to maximize capital efficiency. 
@@ -39,54 +64,797 @@
 //! - Use the revolving credit SBT to take the revolving loan
 //! - Use the installment credit badge to take the installment loan and change credit SBT into installment type.
 //! - Get the current total debt (the debt is increased if user's late on repayment).
+//! - Get the current interest rate curve and quotes, the revolving loan rate is snapshotted at borrow time.
+//! - Check the credit health: limit, drawn amount, utilization, days to due, blacklist status and the projected late penalty.
+//! - Check the exposure headroom: how much more can be taken before hitting the protocol's exposure limits.
 //! - Repay part of the current debt or repay in full.
+//! - Take and repay the loan from a credit delegation.
+//! - Pay merchants directly from the revolving credit through a listed checkout component.
+//! - Take the revolving loan in a listed stablecoin (counted on the credit in the primary stablecoin through the market's peg feed) and repay it in the same stablecoin.
+//!
+//! 4. **Integrators**: Any wallet address or component (permissionless) such as liquidators and arbitrageurs. Through the blueprint's method, *integrators* are allowed to:
+//! - Take a flash loan from the protocol's vault and repay it with the flash loan fee in the same transaction, the fee goes to lenders as interest.
 
 use scrypto::prelude::*;
 use neuracle::neuracle::*;
 use ground_business::ground_business_dao::*;
-use ground_id::{Identity};
+use ground_business::ground_time_source::*;
+use ground_id::{Identity, IdentityData, IdentityType};
 use crate::utils::*;
 use crate::ground_credit::*;
 
+const DAY: u64 = 60 * 60 * 24;
 const MONTH: u64 = 60 * 60 * 24 * 30;
 const YEAR: u64 = 60 * 60 * 24 * 365;
+/// The period the interest which isn't from a loan (and the pool token holders' share of a loan's interest) is released over.
+const INTEREST_RELEASE_PERIOD: u64 = 7 * DAY;
+/// The length of a lender cohort.
+const COHORT_PERIOD: u64 = DAY;
+/// The late repayment penalty stop compounding at this multiple of the debt.
+const MAX_LATE_MULTIPLIER: u64 = 1000000;
 
 /// The struct keep track of lender's data.
 #[derive(TypeId, Encode, Decode, Describe)]
 pub struct Lender {
 
-    /// The current user's lending amount. The amount can be decreased when lender make a withdrawal.
+    /// The lender's tranche.
+    tranche: Tranche,
+    /// The lender's cohort index in the tranche.
+    /// 
+    /// Lenders are only eligible for the interest of any borrow made after their cohort's checkpoint.
+    cohort: u64,
+    /// The lender's interest weight, increased by the term deposit bonus when the account was created.
     /// 
-    /// The amount will also automatically increase over time when borrowers repay their loan.
+    /// This data is fixed for each lending account.
+    weight: Decimal,
+    /// The lender's shares of the cohort. 
     /// 
-    /// If lenders make a withdrawal before eligible interests are concluded by borrower's repayments, 
-    /// they won't get the interest from the withdrawal amount.
-    lending_amount: Decimal,
-    /// The user's lending start time.
+    /// The shares can be decreased when lender make a withdrawal.
     /// 
-    /// This is to keep track of the start time of the lender's account.
+    /// The released interest is settled into the lender's shares whenever the account changes.
+    shares: Decimal,
+    /// The cohort's interest index when the lender's interest was last settled.
     /// 
-    /// This data is fixed for each lending account.
+    /// Lenders earn the released interest by their weighted shares since the checkpoint.
+    interest_checkpoint: Decimal,
+    /// The amount of the lending amount that the lender has earmarked for credit delegations.
+    /// 
+    /// This amount is held outside the cohort shares, it is not eligible for the pool's interest and cannot be withdrawn while the delegations are active.
+    delegated: Decimal
+
+}
+
+/// The struct keep track of a share pool.
+/// 
+/// All holders of the pool share the same exchange rate, 
+/// so the pool's interest (or loss) is applied to the pool instead of each holder.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct SharePool {
+
+    /// The total shares of the pool.
+    shares: Decimal,
+    /// The total return amount (lending amount + interest) of the pool.
+    value: Decimal
+
+}
+
+impl SharePool {
+
+    /// Convert an amount of shares into the return amount.
+    pub fn to_amount(&self, shares: Decimal) -> Decimal {
+        if self.shares == Decimal::ZERO { Decimal::ZERO } else { shares * self.value / self.shares }
+    }
+
+    /// Convert a return amount into the amount of shares.
+    pub fn to_shares(&self, amount: Decimal) -> Decimal {
+        if self.shares == Decimal::ZERO || self.value == Decimal::ZERO { amount } else { amount * self.shares / self.value }
+    }
+
+    /// Add a return amount into the pool and return the minted shares.
+    /// 
+    /// The pool can't mint new shares after its return amount has been wiped out while its old shares remain, 
+    /// or the new shares would be diluted by the worthless old shares.
+    pub fn mint(&mut self, amount: Decimal) -> Decimal {
+        assert!(self.shares == Decimal::ZERO || self.value != Decimal::ZERO, "The pool's return amount has been wiped out, cannot mint new shares.");
+        let shares = self.to_shares(amount);
+        self.shares += shares;
+        self.value += amount;
+        shares
+    }
+
+    /// Take a return amount out of the pool and return the burnt shares.
+    pub fn burn(&mut self, amount: Decimal) -> Decimal {
+        let shares = self.to_shares(amount);
+        self.shares -= shares;
+        self.value -= amount;
+        shares
+    }
+}
+
+/// The struct keep track of a lender cohort.
+/// 
+/// Lenders who lend into a tranche in the same cohort period are grouped into a cohort, all lenders of the cohort share the cohort's exchange rate. 
+/// The cohort's interest is accumulated in the cohort's interest index (interest per weighted share), 
+/// so each lender earn the interest by their weighted shares without walking the lenders. 
+/// The cohort's capital×time is accumulated whenever its return amount changes, so each loan's interest is shared by the cohorts' capital×time over the loan's life.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct Cohort {
+
+    /// The cohort's start time. (unix)
+    start_time: u64,
+    /// The cohort's shares and return amount.
+    pool: SharePool,
+    /// The cohort's total weighted shares (lender's weight * lender's shares).
+    weighted_shares: Decimal,
+    /// The cohort's accumulated interest per weighted share.
+    interest_index: Decimal,
+    /// The cohort's accumulated capital×time (weighted return amount × days).
+    capital_time: Decimal,
+    /// The last time the cohort's capital×time was accumulated. (unix)
+    capital_time_update: u64
+
+}
+
+impl Cohort {
+
+    /// The cohort is eligible for a loan if the cohort has been closed before the debt start time.
+    pub fn is_eligible(&self, debt_start: u64) -> bool {
+        self.start_time + COHORT_PERIOD <= debt_start
+    }
+
+    /// The cohort's interest weight, the lenders' return amount weighted by their term deposit bonus.
+    pub fn weight(&self) -> Decimal {
+        self.pool.to_amount(self.weighted_shares)
+    }
+
+    /// Release an amount of interest to the cohort's lenders, pro-rata to their weighted shares.
+    pub fn release(&mut self, interest: Decimal) {
+        if self.weighted_shares != Decimal::ZERO {
+            self.interest_index += interest / self.weighted_shares
+        }
+    }
+
+    /// Accumulate the cohort's capital×time until the current time, this must be called before the cohort's return amount change.
+    pub fn accumulate(&mut self, current: u64) {
+        if current <= self.capital_time_update { return }
+        self.capital_time += self.weight() * Decimal::from(current - self.capital_time_update) / Decimal::from(DAY);
+        self.capital_time_update = current;
+    }
+}
+
+/// The struct keep track of a lending tranche.
+/// 
+/// The tranche's lenders are grouped into cohorts by the time they lend, 
+/// the tranche's loss (or recovery) is applied to the cohorts' exchange rates and the interest is released to the cohorts' interest indexes. 
+/// This way, interest distribution only update the cohorts, it cost the same no matter how many lenders there are.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct TranchePool {
+
+    /// The tranche's lender cohorts, sorted by the cohort's start time.
+    cohorts: Vec<Cohort>
+
+}
+
+impl TranchePool {
+
+    /// Create a new lender of the tranche, the lender join the current cohort.
+    /// 
+    /// A new cohort is opened if the last cohort has been closed, or its return amount has been wiped out while its old shares remain.
+    pub fn new_lender(&mut self, tranche: Tranche, weight: Decimal, current: u64) -> Lender {
+        let start_time = current / COHORT_PERIOD * COHORT_PERIOD;
+        let open = match self.cohorts.last() {
+            None => true,
+            Some(cohort) => cohort.start_time != start_time || (cohort.pool.value == Decimal::ZERO && cohort.pool.shares != Decimal::ZERO)
+        };
+        if open {
+            self.cohorts.push(Cohort { start_time, pool: SharePool { shares: Decimal::ZERO, value: Decimal::ZERO }, weighted_shares: Decimal::ZERO, interest_index: Decimal::ZERO, capital_time: Decimal::ZERO, capital_time_update: current });
+        }
+        let cohort = self.cohorts.len() - 1;
+        Lender { tranche, cohort: cohort as u64, weight, shares: Decimal::ZERO, interest_checkpoint: self.cohorts[cohort].interest_index, delegated: Decimal::ZERO }
+    }
+
+    /// The tranche's return amount, or the return amount of the cohorts eligible for a loan.
+    pub fn value(&self, debt_start: Option<u64>) -> Decimal {
+        self.cohorts.iter()
+            .take_while(|cohort| debt_start.map_or(true, |debt_start| cohort.is_eligible(debt_start)))
+            .fold(Decimal::ZERO, |total, cohort| total + cohort.pool.value)
+    }
+
+    /// The tranche's interest weight, the lenders' return amount weighted by their term deposit bonus.
+    pub fn weight(&self) -> Decimal {
+        self.cohorts.iter().fold(Decimal::ZERO, |total, cohort| total + cohort.weight())
+    }
+
+    /// Release an amount of interest to the tranche's cohorts, pro-rata to their interest weight.
+    pub fn release(&mut self, interest: Decimal) {
+        let weight = self.weight();
+        if weight == Decimal::ZERO { return }
+        for cohort in self.cohorts.iter_mut() {
+            let cohort_interest = interest * cohort.weight() / weight;
+            cohort.release(cohort_interest)
+        }
+    }
+
+    /// Accumulate the cohorts' capital×time until the current time.
+    pub fn accumulate(&mut self, current: u64) {
+        for cohort in self.cohorts.iter_mut() {
+            cohort.accumulate(current)
+        }
+    }
+
+    /// The capital×time of the cohorts eligible for a loan.
+    pub fn capital_times(&self, debt_start: u64) -> Vec<Decimal> {
+        self.cohorts.iter()
+            .take_while(|cohort| cohort.is_eligible(debt_start))
+            .map(|cohort| cohort.capital_time)
+            .collect()
+    }
+
+    /// The capital×time each cohort in the checkpoint has accumulated since the checkpoint, the checkpoint is moved to the cohorts' current capital×time.
+    /// 
+    /// The cohorts without any lender left don't count, so their share goes to the other cohorts.
+    pub fn capital_times_since(&self, checkpoint: &mut Vec<Decimal>) -> Vec<Decimal> {
+        checkpoint.iter_mut().zip(self.cohorts.iter())
+            .map(|(capital_time, cohort)| {
+                let since = if cohort.weighted_shares == Decimal::ZERO { Decimal::ZERO } else { cohort.capital_time - *capital_time };
+                *capital_time = cohort.capital_time;
+                since
+            })
+            .collect()
+    }
+
+    /// Release an amount of interest per capital×time to the cohorts by their capital×time.
+    pub fn release_by(&mut self, rate: Decimal, capital_times: &Vec<Decimal>) {
+        for (cohort, capital_time) in self.cohorts.iter_mut().zip(capital_times.iter()) {
+            cohort.release(rate * *capital_time)
+        }
+    }
+
+    /// Apply the interest (or loss) rate on the tranche's cohorts (or the cohorts eligible for a loan).
+    pub fn apply_rate(&mut self, rate: Decimal, debt_start: Option<u64>) {
+        for cohort in self.cohorts.iter_mut() {
+            if let Some(debt_start) = debt_start {
+                if !cohort.is_eligible(debt_start) { break }
+            }
+            cohort.pool.value = cohort.pool.value * rate
+        }
+    }
+
+    /// Get the lender's released interest which hasn't been settled into the lender's shares.
+    pub fn pending_interest(&self, lender: &Lender) -> Decimal {
+        let cohort = &self.cohorts[lender.cohort as usize];
+        lender.weight * lender.shares * (cohort.interest_index - lender.interest_checkpoint)
+    }
+
+    /// Get the lender's return amount, include the released interest.
+    pub fn amount_of(&self, lender: &Lender) -> Decimal {
+        self.cohorts[lender.cohort as usize].pool.to_amount(lender.shares) + self.pending_interest(lender)
+    }
+
+    /// Settle the lender's released interest into the lender's shares, this must be called before the lender's shares change.
+    pub fn settle(&mut self, lender: &mut Lender) {
+        let interest = self.pending_interest(lender);
+        lender.interest_checkpoint = self.cohorts[lender.cohort as usize].interest_index;
+        self.lend(lender, interest)
+    }
+
+    /// Add a return amount into the lender's cohort for the lender.
+    pub fn lend(&mut self, lender: &mut Lender, amount: Decimal) {
+        if amount == Decimal::ZERO { return }
+        let cohort = &mut self.cohorts[lender.cohort as usize];
+        let shares = cohort.pool.mint(amount);
+        lender.shares += shares;
+        cohort.weighted_shares += shares * lender.weight;
+    }
+
+    /// Take a return amount out of the lender's shares.
+    pub fn take(&mut self, lender: &mut Lender, amount: Decimal) {
+        let cohort = &mut self.cohorts[lender.cohort as usize];
+        let shares = cohort.pool.burn(amount);
+        lender.shares -= shares;
+        cohort.weighted_shares -= shares * lender.weight;
+    }
+
+    /// Remove all the lender's shares and return the lender's return amount.
+    pub fn remove(&mut self, lender: &mut Lender) -> Decimal {
+        self.settle(lender);
+        let cohort = &mut self.cohorts[lender.cohort as usize];
+        let amount = cohort.pool.to_amount(lender.shares);
+        cohort.pool.shares -= lender.shares;
+        cohort.pool.value -= amount;
+        cohort.weighted_shares -= lender.shares * lender.weight;
+        lender.shares = Decimal::ZERO;
+        amount
+    }
+}
+
+/// The struct keep track of the capital×time of the lenders eligible for a loan, checkpointed when the loan is taken and at each repayment.
+/// 
+/// The interest repaid on the loan is shared by the capital×time each eligible cohort (and the pool token holders) accumulated since the last checkpoint.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct LoanCheckpoint {
+
+    /// The capital×time of the senior cohorts eligible for the loan.
+    senior: Vec<Decimal>,
+    /// The capital×time of the junior cohorts eligible for the loan.
+    junior: Vec<Decimal>,
+    /// The pool token holders' capital×time.
+    token: Decimal
+
+}
+
+/// The lending tranche.
+/// 
+/// The junior tranche absorbs loan defaults first and earns a higher share of interest, 
+/// the senior tranche has priority on repayments of written-off loans and on queued withdrawals.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
+pub enum Tranche {
+    Senior,
+    Junior
+}
+
+/// The deposit term of a lending account.
+/// 
+/// Term deposits cannot be withdrawn until maturity and earn a bonus share of interest.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq)]
+pub enum DepositTerm {
+    Flexible,
+    ThreeMonths,
+    SixMonths,
+    TwelveMonths
+}
+
+impl DepositTerm {
+
+    /// The lockup length of the deposit term. (seconds)
+    pub fn length(&self) -> u64 {
+        match self {
+            DepositTerm::Flexible => 0,
+            DepositTerm::ThreeMonths => 3 * MONTH,
+            DepositTerm::SixMonths => 6 * MONTH,
+            DepositTerm::TwelveMonths => 12 * MONTH
+        }
+    }
+}
+
+/// The bonus share of interest for term deposits.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct TermBonuses {
+
+    /// The 3 months term deposits' extra interest weight. (%)
+    pub three_months: Decimal,
+    /// The 6 months term deposits' extra interest weight. (%)
+    pub six_months: Decimal,
+    /// The 12 months term deposits' extra interest weight. (%)
+    pub twelve_months: Decimal
+
+}
+
+impl TermBonuses {
+
+    pub fn check_bonuses(&self) {
+        assert!(self.three_months >= Decimal::ZERO && self.six_months >= Decimal::ZERO && self.twelve_months >= Decimal::ZERO, "Wrong data!");
+    }
+
+    pub fn bonuses_aggregrate(&mut self) {
+        self.three_months = self.three_months / dec!("100");
+        self.six_months = self.six_months / dec!("100");
+        self.twelve_months = self.twelve_months / dec!("100");
+    }
+
+    /// Get the extra interest weight of a deposit term.
+    pub fn bonus(&self, term: DepositTerm) -> Decimal {
+        match term {
+            DepositTerm::Flexible => Decimal::ZERO,
+            DepositTerm::ThreeMonths => self.three_months,
+            DepositTerm::SixMonths => self.six_months,
+            DepositTerm::TwelveMonths => self.twelve_months
+        }
+    }
+}
+
+/// The tranche policy of the lending protocol.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct TranchePolicy {
+
+    /// The minimum junior tranche's return amount over the total return amount. (%)
+    /// 
+    /// Senior deposits and junior withdrawals cannot make the ratio drop below this.
+    pub min_junior_ratio: Decimal,
+    /// The junior tranche's extra interest weight. (%)
+    /// 
+    /// E.g: 50% means each junior stable coin earns 1.5 times the interest of a senior stable coin.
+    pub junior_interest_premium: Decimal
+
+}
+
+impl TranchePolicy {
+
+    pub fn check_policy(&self) {
+        assert_rate(self.min_junior_ratio);
+        assert!(self.junior_interest_premium >= Decimal::ZERO, "Wrong data!");
+    }
+
+    pub fn policy_aggregrate(&mut self) {
+        self.min_junior_ratio = self.min_junior_ratio / dec!("100");
+        self.junior_interest_premium = self.junior_interest_premium / dec!("100");
+    }
+}
+
+/// Actions of the lending protocol which can be paused by the protocol's guardian.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq, Debug)]
+pub enum LendingAction {
+    Deposit,
+    Borrow,
+    Repay,
+    Withdraw,
+    Compensate
+}
+
+/// The circuit breakers of the lending protocol.
+/// 
+/// The breakers are checked whenever resource is taken out of the protocol's vault, an outflow that would trip the breakers is rejected.
+/// Since a rejected outflow is reverted with its transaction, the vault drop breaker works as a per-epoch outflow rate limit on borrowing and withdrawal.
+/// 
+/// To persist a trip, anyone (a keeper) can call the "check_circuit_breakers" method, 
+/// the breakers then pause borrowing and withdrawal on anomalies until the protocol's guardian resume them.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct CircuitBreaker {
+
+    /// The maximum oracle time passed in one ledger epoch. (seconds)
     /// 
-    /// Lenders are only eligible for any borrow made after this time data.
-    start_time: u64
+    /// The breaker trip if the oracle time move backward or jump more than this.
+    pub max_time_jump: u64,
+    /// The maximum drop of the protocol's vault in one ledger epoch. (%)
+    pub max_vault_drop: Decimal
+
+}
+
+impl CircuitBreaker {
+
+    pub fn check_breaker(&self) {
+        assert!(self.max_time_jump > 0, "Wrong data!");
+        assert_rate(self.max_vault_drop);
+    }
+
+    pub fn breaker_aggregrate(&mut self) {
+        self.max_vault_drop = self.max_vault_drop / dec!("100");
+    }
+}
+
+/// Credit products of the lending protocol, used to group the borrowers' exposure.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CreditProduct {
+    Monthly,
+    Yearly,
+    Installment
+}
+
+impl CreditProduct {
+
+    /// Get the credit product of a credit type.
+    pub fn of(credit_type: &CreditType) -> Self {
+        match credit_type {
+            CreditType::Revolving(RevolvingTypes::Monthly) => CreditProduct::Monthly,
+            CreditType::Revolving(RevolvingTypes::Yearly) => CreditProduct::Yearly,
+            CreditType::Installment(_) => CreditProduct::Installment
+        }
+    }
+}
+
+/// The sources of a borrower's loan, used to track each part of the borrower's exposure.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoanSource {
+    /// Loans from the protocol's pool.
+    Pool,
+    /// Loans in the listed stablecoin markets, counted at the peg value when lent.
+    Market,
+    /// Loans through credit delegations.
+    Delegation
+}
+
+/// The exposure limits of the lending protocol's pool.
+/// 
+/// The limits are counted on the total return amount of the pool and enforced on every revolving, installment, stablecoin market and delegated loan.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct ExposureLimits {
+
+    /// The maximum share of the pool lent to a single borrower. (%)
+    pub max_borrower_share: Decimal,
+    /// The maximum share of the pool lent through a single credit product. (%)
+    pub max_product_share: Decimal,
+    /// The maximum share of the pool lent to Business and Organization identities. (%)
+    pub max_business_share: Decimal
+
+}
+
+impl ExposureLimits {
+
+    pub fn check_limits(&self) {
+        assert_rate(self.max_borrower_share);
+        assert_rate(self.max_product_share);
+        assert_rate(self.max_business_share);
+    }
+
+    pub fn limits_aggregrate(&mut self) {
+        self.max_borrower_share = self.max_borrower_share / dec!("100");
+        self.max_product_share = self.max_product_share / dec!("100");
+        self.max_business_share = self.max_business_share / dec!("100");
+    }
+}
+
+/// The struct keep track of a borrower's outstanding principal on the protocol's pool.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct Exposure {
+    /// The borrower's credit product.
+    pub product: CreditProduct,
+    /// The borrower is a Business or Organization identity or not.
+    pub business: bool,
+    /// The outstanding principal.
+    pub amount: Decimal,
+    /// The part of the outstanding principal lent in the stablecoin markets. (primary stablecoin unit)
+    pub asset: Decimal,
+    /// The part of the outstanding principal lent through credit delegations.
+    pub delegated: Decimal
+}
+
+/// The struct show how much a borrower can still take from the protocol's pool.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct ExposureHeadroom {
+
+    /// The headroom left by the protocol's borrower exposure limit.
+    pub borrower: Decimal,
+    /// The headroom left by the protocol's credit product exposure limit.
+    pub product: Decimal,
+    /// The headroom left by the protocol's business exposure limit, None if the borrower is not a Business or Organization identity.
+    pub business: Option<Decimal>,
+    /// The headroom left by the protocol's tolerance threshold.
+    pub pool: Decimal,
+    /// The amount the borrower can still take, limited by all the above and the borrower's credit allowance.
+    pub available: Decimal
+
+}
+
+/// The peg feed validation policy of a listed stablecoin market.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct PegPolicy {
+
+    /// The minimum accepted peg. (primary stablecoin unit)
+    pub min_peg: Decimal,
+    /// The maximum accepted peg. (primary stablecoin unit)
+    pub max_peg: Decimal,
+    /// The maximum age of a peg quote before it's considered stale. (seconds)
+    pub max_age: u64
 
 }
 
-impl Lender {
-    pub fn interest(&mut self, interest: Decimal) {
-        self.lending_amount *= interest
+impl PegPolicy {
+
+    pub fn check_policy(&self) {
+        assert!(self.min_peg > Decimal::ZERO && self.max_peg >= self.min_peg && self.max_age > 0, "Wrong data!");
     }
 }
 
+/// The struct keep track of a listed stablecoin market of the lending protocol.
+/// 
+/// Each market has its own vault, lender accounting and interest rates. 
+/// Borrowers' credit limits are counted in the protocol's primary stablecoin through the market's peg feed.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct AssetMarket {
+
+    /// Vault keep the market's remain lending amount.
+    vault: Vault,
+    /// Fee vault of the market.
+    fee_vault: Vault,
+    /// The market's insurance reserve, receive the reserve factor share of the market's interest and cover the market's written-off principal.
+    reserve_vault: Vault,
+    /// The market's share pool, the market share token's supply is the pool's shares.
+    pool: SharePool,
+    /// The market share token address.
+    share_token: ResourceAddress,
+    /// The market's revolving credit interest rates.
+    interest_rates: RevolvingCreditInterestRates,
+    /// The NeuRacle data badge of the market's peg feed (the price of one asset unit in the protocol's primary stablecoin).
+    peg_badge: Vault,
+    /// The market's peg feed validation policy.
+    peg_policy: PegPolicy
+}
+
+/// The struct keep track of a borrower's loan in a listed stablecoin market.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct AssetDebt {
+
+    /// The unpaid principal. (asset unit)
+    pub principal: Decimal,
+    /// The unpaid interest. (asset unit)
+    pub interest: Decimal,
+    /// The unpaid principal counted on the borrower's credit SBT. (primary stablecoin unit)
+    pub value: Decimal,
+    /// The unpaid interest counted on the borrower's credit SBT. (primary stablecoin unit)
+    pub value_interest: Decimal
+
+}
+
+/// The struct keep track of a credit delegation from a lender to a borrower.
+/// 
+/// The lender earmark part of their lending amount for the borrower at the interest rate they set. 
+/// The interest and any loss from the delegated loan only flow to that lender.
+#[derive(TypeId, Encode, Decode, Describe, Clone)]
+pub struct Delegation {
+
+    /// The lender's Account NFT ID.
+    pub lender: NonFungibleId,
+    /// The borrower's Identity SBT ID.
+    pub borrower: NonFungibleId,
+    /// The delegated allowance.
+    pub allowance: Decimal,
+    /// The current drawn amount.
+    pub drawn: Decimal,
+    /// The current debt interest.
+    pub debt_interest: Decimal,
+    /// The interest rate set by the lender.
+    pub interest_rate: Decimal,
+    /// The loan term. (seconds)
+    pub term: u64,
+    /// The due time of the current drawn amount, 0 if the borrower has no debt. (unix)
+    pub due_time: u64,
+    /// The delegation has been revoked by the lender or not.
+    /// 
+    /// The borrower cannot draw a revoked delegation, the repaid principal is released back to the lender's account.
+    pub revoked: bool,
+    /// The delegated loan has been written off or not.
+    /// 
+    /// The written-off debt is kept on the delegation, later repayments are recovered to the delegating lender's account.
+    pub defaulted: bool
+
+}
+
+/// The struct show the borrower's credit health on the protocol.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct CreditHealth {
+
+    /// The borrower's credit limit (the total loan if the borrower is using installment credit).
+    pub limit: Decimal,
+    /// The current drawn amount.
+    pub drawn: Decimal,
+    /// The current debt interest.
+    pub debt_interest: Decimal,
+    /// The current extra debt by late repayment.
+    pub extra_debt: Decimal,
+    /// The credit utilization. (%)
+    /// 
+    /// ```utilization = (drawn + debt_interest) / limit```
+    pub utilization: Decimal,
+    /// The number of days before the debt is overdue, 0 if the borrower has no debt or is late on repayment.
+    pub days_to_due: u64,
+    /// The borrower is late on repayment or not.
+    pub late: bool,
+    /// The borrower is blacklisted on the credit service or not.
+    pub blacklisted: bool,
+    /// The borrower's credit has been declared defaulted or not.
+    pub defaulted: bool,
+    /// The late repayment penalty. 
+    /// 
+    /// If the borrower is not late yet, this is the penalty the borrower would get right after the due time.
+    pub projected_late_penalty: Decimal
+
+}
+
 /// The NFT keep track of user's lending account on the protocol.
 /// 
 /// ## Uses:
 /// To get new Account NFT, user use the "new_lending_account" method with a bucket of stable coin.
 /// 
 /// Lenders can also use the NFT to withdraw the return amount (lending amount + interest) from the protocol. Method: "withdraw" or "withdraw_all".
+/// 
+/// Term deposits can only be withdrawn after maturity.
+#[derive(NonFungibleData)]
+pub struct Account {
+    /// The deposit term.
+    pub term: DepositTerm,
+    /// The maturity time of the deposit, 0 for flexible deposits. (unix)
+    pub maturity: u64
+}
+
+impl Account {
+
+    /// Assert the term deposit has been matured.
+    pub fn assert_matured(&self, current: u64) {
+        assert!(self.maturity <= current, "Your term deposit will be matured at {} (unix time).", self.maturity);
+    }
+}
+
+/// The NFT keep track of a lender's queued withdrawal.
+/// 
+/// ## Uses:
+/// When the protocol's vault is short, lenders can use the "enqueue_withdrawal" method to get the ticket. 
+/// Every inflow into the protocol's vault (repayments, deposits, recoveries, compensations, insurance payouts and flash loan repayments) will fill the queue first in FIFO order.
+/// 
+/// Ticket holders can claim the filled amount with "claim_withdrawal" or cancel the unfilled amount with "cancel_withdrawal".
+#[derive(NonFungibleData)]
+pub struct WithdrawalTicket {
+    /// The ticket id on the withdrawal queue.
+    pub ticket_id: u64,
+    /// The lender's Account NFT ID.
+    pub account: NonFungibleId,
+    /// The queued withdrawal amount.
+    pub amount: Decimal
+}
+
+/// The NFT record a defaulted position packaged as a debt claim.
+/// 
+/// ## Uses:
+/// The protocol operator can package a defaulted position with the "auction_debt_claim" method, the claim is sold in a descending price auction with the "buy_debt_claim" method.
+/// 
+/// Any future repayment by the borrower is routed to the claim holder, who can take it with the "claim_debt_repayments" method.
+#[derive(NonFungibleData)]
+pub struct DebtClaim {
+    /// The debt claim id.
+    pub claim_id: u64,
+    /// The borrower's Credit SBT ID.
+    pub credit_id: NonFungibleId,
+    /// The unpaid principal when the claim was packaged.
+    pub principal: Decimal,
+    /// The unpaid interest when the claim was packaged.
+    pub interest: Decimal,
+    /// The unpaid extra debt when the claim was packaged.
+    pub extra_debt: Decimal
+}
+
+/// The transient NFT record a flash loan.
+/// 
+/// ## Uses:
+/// The receipt is returned with the "flash_loan" method and cannot be deposited into any account, 
+/// it must be burnt with the "repay_flash_loan" method in the same transaction.
 #[derive(NonFungibleData)]
-pub struct Account {}
+pub struct FlashLoan {
+    /// The loan amount.
+    pub amount: Decimal,
+    /// The flash loan fee.
+    pub fee: Decimal
+}
+
+/// The struct keep track of a debt claim's descending price auction.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct ClaimAuction {
+    /// The borrower's Credit SBT ID.
+    pub credit_id: NonFungibleId,
+    /// The auction's start price.
+    pub start_price: Decimal,
+    /// The auction's floor price.
+    pub floor_price: Decimal,
+    /// The auction's start time. (unix)
+    pub start_time: u64,
+    /// The time the price reach the floor price. (unix)
+    pub end_time: u64,
+    /// The claim has been sold or not.
+    pub sold: bool
+}
+
+impl ClaimAuction {
+
+    /// Get the auction's current price, the price decrease linearly from the start price to the floor price.
+    pub fn price(&self, current: u64) -> Decimal {
+        if current >= self.end_time {
+            self.floor_price
+        } else {
+            self.start_price - (self.start_price - self.floor_price) * Decimal::from(current - self.start_time) / Decimal::from(self.end_time - self.start_time)
+        }
+    }
+}
+
+/// The struct keep track of a queued withdrawal.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct QueuedWithdrawal {
+    /// The lender's Account NFT ID.
+    pub account: NonFungibleId,
+    /// The unfilled amount.
+    pub remaining: Decimal,
+    /// The filled amount which hasn't been claimed.
+    pub filled: Decimal,
+    /// The lender's tranche.
+    pub tranche: Tranche
+}
 
 /// On-chain revolving credit interest rate of the lending protocol.
 #[derive(TypeId, Encode, Decode, Describe)]
@@ -136,26 +904,198 @@ impl RevolvingCreditInterestRates {
     }
 }
 
-blueprint! {
+/// The utilization-based interest rate model of the lending protocol.
+/// 
+/// The revolving credit interest rates are used as the per-product base rates, the borrow rate is a kinked curve of the pool utilization:
+/// 
+/// ```utilization = (total_return - vault) / total_return```
+/// 
+/// ```rate = base_rate + slope * utilization / optimal_utilization``` (utilization <= optimal_utilization)
+/// 
+/// ```rate = base_rate + slope + jump_slope * (utilization - optimal_utilization) / (1 - optimal_utilization)``` (utilization > optimal_utilization)
+#[derive(TypeId, Encode, Decode, Describe, Clone)]
+pub struct InterestRateModel {
 
-    struct GroundLending {
+    /// The utilization where the rate curve kinks. (%)
+    pub optimal_utilization: Decimal,
+    /// The rate increase from 0 to the optimal utilization. (%)
+    pub slope: Decimal,
+    /// The rate increase from the optimal utilization to full utilization. (%)
+    pub jump_slope: Decimal
 
-        /// Component controller badge
-        controller_badge: Vault,
-        /// The lending account NFT address
-        account_nft: ResourceAddress,
-        /// Lending protocol revolving credit interest rates.
-        interest_rates: RevolvingCreditInterestRates, 
-        /// Vault keep the total remain amount from the lenders's return amount (lending amount + interest) subtract the total unpaid credit.
-        /// 
-        /// Borrowers will take the whitelisted credit amount from this vault and make repayment into the vault.
-        vault: Vault,
-        /// The total return amount. 
-        /// 
-        /// total_return = total_deposited + total_interest.
-        total_return: Decimal,
-        /// Fee vault for GroundFi operator to maintain their service.
-        fee_vault: Vault,
+}
+
+impl InterestRateModel {
+
+    pub fn check_model(&self) {
+        assert!(self.optimal_utilization > Decimal::ZERO && self.optimal_utilization < dec!("100"), "Wrong data!");
+        assert_rate(self.slope);
+        assert_rate(self.jump_slope)
+    }
+
+    pub fn model_aggregrate(&mut self) {
+        self.optimal_utilization = self.optimal_utilization / dec!("100");
+        self.slope = self.slope / dec!("100");
+        self.jump_slope = self.jump_slope / dec!("100");
+    }
+
+    /// Get the borrow rate from the base rate and the pool utilization.
+    pub fn rate(&self, base_rate: Decimal, utilization: Decimal) -> Decimal {
+        if utilization <= self.optimal_utilization {
+            base_rate + self.slope * utilization / self.optimal_utilization
+        } else {
+            base_rate + self.slope + self.jump_slope * (utilization - self.optimal_utilization) / (Decimal::ONE - self.optimal_utilization)
+        }
+    }
+}
+
+/// The struct show the current interest rate curve and quotes of the lending protocol.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct InterestRateQuote {
+
+    /// The pool utilization after the quoted borrow amount. (%)
+    pub utilization: Decimal,
+    /// The interest rate model, None if the protocol is using static rates.
+    pub model: Option<InterestRateModel>,
+    /// Monthly revolving credit interest rate quote. (%)
+    pub monthly: Decimal,
+    /// Yearly revolving credit interest rate quote. (%)
+    pub yearly: Decimal
+
+}
+
+/// The policy of the lending protocol to declare a loan default.
+/// 
+/// A loan can be declared defaulted when the borrower has missed the number of repayment periods **or** has been overdue for the number of days.
+#[derive(TypeId, Encode, Decode, Describe)]
+pub struct DefaultPolicy {
+
+    /// The number of missed repayment periods.
+    pub missed_periods: u64,
+    /// The number of days overdue.
+    pub overdue_days: u64,
+    /// Automatically take the compensation from the DAO treasury to cover part of the written-off principal.
+    pub auto_compensate: bool
+
+}
+
+impl DefaultPolicy {
+
+    pub fn check_policy(&self) {
+        assert!(self.missed_periods > 0 && self.overdue_days > 0, "Wrong data provided!");
+    }
+}
+
+blueprint! {
+
+    struct GroundLending {
+
+        /// Component controller badge
+        controller_badge: Vault,
+        /// The lending account NFT address
+        account_nft: ResourceAddress,
+        /// The withdrawal ticket NFT address
+        withdrawal_ticket: ResourceAddress,
+        /// The guardian badge address, the guardian can pause and resume the protocol's actions.
+        guardian: Option<ResourceAddress>,
+        /// The protocol's paused actions.
+        paused: Vec<LendingAction>,
+        /// The protocol's circuit breakers, None if the protocol doesn't use circuit breakers.
+        circuit_breaker: Option<CircuitBreaker>,
+        /// The circuit breakers' checkpoint.
+        /// 
+        /// **Syntax**:
+        /// ```(ledger_epoch, oracle_time, vault_amount)```
+        /// 
+        /// The vault amount is taken at the start of the ledger epoch.
+        breaker_checkpoint: (u64, u64, Decimal),
+        /// The protocol's exposure limits, None if the protocol doesn't limit the exposure.
+        exposure_limits: Option<ExposureLimits>,
+        /// The map keep track of borrowers' exposure on the protocol's pool.
+        /// 
+        /// **Syntax**:
+        /// ```HashMap<credit_id, exposure>```
+        exposures: HashMap<NonFungibleId, Exposure>,
+        /// The map keep track of the protocol's exposure by credit products.
+        product_exposures: HashMap<CreditProduct, Decimal>,
+        /// The protocol's exposure to Business and Organization identities.
+        business_exposure: Decimal,
+        /// The flash loan receipt address
+        flash_loan_receipt: ResourceAddress,
+        /// The flash loan fee, initial 0.09%.
+        flash_loan_fee: Decimal,
+        /// The flash loans taken and not repaid yet in the current transaction.
+        outstanding_flash_loans: Decimal,
+        /// The debt claim NFT address
+        debt_claim: ResourceAddress,
+        /// Vault keep the debt claims which haven't been sold.
+        claim_vault: Vault,
+        /// The map keep track of debt claim auctions.
+        /// 
+        /// **Syntax**:
+        /// ```HashMap<claim_id, claim_auction>```
+        claim_auctions: HashMap<u64, ClaimAuction>,
+        /// The map route the defaulted borrowers' repayments to their debt claim.
+        /// 
+        /// **Syntax**:
+        /// ```HashMap<credit_id, claim_id>```
+        claim_routes: HashMap<NonFungibleId, u64>,
+        /// Vaults keep the repayments routed to the debt claim holders, in the primary stablecoin and in the listed stablecoins.
+        /// 
+        /// **Syntax**:
+        /// ```HashMap<claim_id, HashMap<stablecoin_address, vault>>```
+        claim_payouts: HashMap<u64, HashMap<ResourceAddress, Vault>>,
+        /// Debt claim id counter
+        claim_id_counter: u64,
+        /// The map keep track of queued withdrawals.
+        /// 
+        /// **Syntax**:
+        /// ```HashMap<ticket_id, queued_withdrawal>```
+        withdrawals: HashMap<u64, QueuedWithdrawal>,
+        /// The senior tranche's withdrawal queue in FIFO order.
+        withdrawal_queue: Vec<u64>,
+        /// The index of the first unfilled withdrawal on the senior queue.
+        withdrawal_queue_head: u64,
+        /// The junior tranche's withdrawal queue in FIFO order, only filled after the senior queue.
+        junior_withdrawal_queue: Vec<u64>,
+        /// The index of the first unfilled withdrawal on the junior queue.
+        junior_withdrawal_queue_head: u64,
+        /// Withdrawal ticket id counter
+        withdrawal_id_counter: u64,
+        /// Vault keep the filled amount of the queued withdrawals.
+        withdrawal_vault: Vault,
+        /// Lending protocol revolving credit interest rates.
+        interest_rates: RevolvingCreditInterestRates, 
+        /// Lending protocol utilization-based interest rate model, None if the protocol is using static rates.
+        interest_rate_model: Option<InterestRateModel>,
+        /// The revolving credit interest rate snapshotted at borrow time.
+        /// 
+        /// **Syntax**:
+        /// ```LazyMap<credit_id, interest_rate>```
+        borrow_rates: LazyMap<NonFungibleId, Decimal>,
+        /// The capital×time checkpoints of the loans taken from the protocol's vault.
+        /// 
+        /// **Syntax**:
+        /// ```LazyMap<credit_id, loan_checkpoint>```
+        loan_checkpoints: LazyMap<NonFungibleId, LoanCheckpoint>,
+        /// Vault keep the total remain amount from the lenders's return amount (lending amount + interest) subtract the total unpaid credit.
+        /// 
+        /// Borrowers will take the whitelisted credit amount from this vault and make repayment into the vault.
+        vault: Vault,
+        /// The total return amount. 
+        /// 
+        /// total_return = total_deposited + total_interest.
+        total_return: Decimal,
+        /// Fee vault for GroundFi operator to maintain their service.
+        fee_vault: Vault,
+        /// Insurance reserve vault, receive a share of all interest and cover written-off principal before any loss reaches lenders.
+        reserve_vault: Vault,
+        /// The share of all interest that goes to the insurance reserve. (%)
+        reserve_factor: Decimal,
+        /// The reserve's payouts which haven't been refilled by recoveries from defaulted loans.
+        reserve_payouts: Decimal,
+        /// The DAO's compensations which haven't been repaid by recoveries from defaulted loans.
+        dao_payouts: Decimal,
         /// Fee percent for lenders when they made a withdrawal. (%)
         fee: Decimal,
         /// The map keep track of protocol lender's data.
@@ -163,6 +1103,48 @@ blueprint! {
         /// **Syntax**:
         /// ```HashMap<lender_account_id, lender_data>```
         lenders: HashMap<NonFungibleId, Lender>,
+        /// The senior lending tranche.
+        senior: TranchePool,
+        /// The junior lending tranche.
+        junior: TranchePool,
+        /// The interest which hasn't been released to the lenders.
+        /// 
+        /// The interest which isn't from a loan is released over the interest release period, so lenders earn the interest by their time-weighted capital.
+        locked_interest: Decimal,
+        /// The pool token holders' share of the loans' interest which hasn't been released into the pool token's value.
+        /// 
+        /// The pool token holders can't be grouped into cohorts, so their share is released over the interest release period.
+        token_locked_interest: Decimal,
+        /// The last time the locked interest was released. (unix)
+        interest_release_time: u64,
+        /// The pool token holders' accumulated capital×time (return amount × days).
+        token_capital_time: Decimal,
+        /// The last time the pool token holders' capital×time was accumulated. (unix)
+        token_capital_time_update: u64,
+        /// The fungible pool-share token address, None if the protocol doesn't use the pool token mode.
+        pool_token: Option<ResourceAddress>,
+        /// The share pool of the pool-share token holders, the pool token's supply is the pool's shares.
+        /// 
+        /// The pool token holders are in the senior tranche.
+        token_pool: Option<SharePool>,
+        /// The listed stablecoin markets.
+        /// 
+        /// **Syntax**:
+        /// ```HashMap<asset_address, asset_market>```
+        asset_markets: HashMap<ResourceAddress, AssetMarket>,
+        /// The map keep track of borrowers' loans in the listed stablecoin markets.
+        /// 
+        /// **Syntax**:
+        /// ```LazyMap<credit_id, HashMap<asset_address, asset_debt>>```
+        asset_debts: LazyMap<NonFungibleId, HashMap<ResourceAddress, AssetDebt>>,
+        /// The lending protocol's tranche policy, None if the protocol doesn't enforce the junior ratio or the junior interest premium.
+        tranche_policy: Option<TranchePolicy>,
+        /// The bonus share of interest for term deposits.
+        term_bonuses: TermBonuses,
+        /// The senior tranche's written-off amount which hasn't been recovered.
+        /// 
+        /// Recoveries from defaulted loans will go to the senior tranche first until the shortfall is covered.
+        senior_shortfall: Decimal,
         /// Initial minimum remaining rate percent allowed for any credit request.
         /// 
         /// ```remaining_rate = vault_remain / total_return```
@@ -171,10 +1153,26 @@ blueprint! {
         credit_service: ComponentAddress,
         /// The on-using Oracle ```(component_address, oracle_user_badge)```
         oracle: (ComponentAddress, Vault),
+        /// The on-using time source component address, None if the protocol read the time directly from the oracle.
+        time_source: Option<ComponentAddress>,
         /// The DAO Component Address
         dao: Option<ComponentAddress>,
         /// The compensate rate in case of loan default
-        compensate_rate: Decimal
+        compensate_rate: Decimal,
+        /// The policy to declare a loan default.
+        default_policy: DefaultPolicy,
+        /// The map keep track of credit delegations from lenders to borrowers.
+        /// 
+        /// **Syntax**:
+        /// ```HashMap<delegation_id, delegation_data>```
+        delegations: HashMap<u64, Delegation>,
+        /// Delegation id counter
+        delegation_id_counter: u64,
+        /// Listed checkout components can take the revolving loan on behalf of borrowers to pay merchants.
+        /// 
+        /// **Syntax**:
+        /// ```Vec<checkout_controller_badge_address>```
+        checkouts: Vec<ResourceAddress>
 
     }
 
@@ -195,6 +1193,9 @@ blueprint! {
         /// - oracle: initial oracle component address and the time data badge.
         /// - dao: the DAO run this lending protocol (None if this is a protocol run by an individual).
         /// - compensate_rate: initial compensate rate of the protocol.
+        /// - default_policy: initial policy to declare a loan default.
+        /// 
+        /// Syntax: ```Struct({missed_periods}u64, {overdue_days}u64, {auto_compensate})```
         /// 
         /// ```remaining rate = vault remain / total return```
         /// ### Output: Component address and the controller badge resource address (for test purpose).
@@ -208,10 +1209,12 @@ blueprint! {
             credit_service: ComponentAddress,
             oracle: (ComponentAddress, Bucket),
             dao: Option<ComponentAddress>,
-            compensate_rate: Decimal
+            compensate_rate: Decimal,
+            default_policy: DefaultPolicy
         ) -> (ComponentAddress, ResourceAddress) {
 
             interest_rates.check_rates();
+            default_policy.check_policy();
             interest_rates.rates_aggregrate();
             assert_rate(tolerance_threshold); assert_rate(compensate_rate); assert_rate(fee); 
 
@@ -227,14 +1230,51 @@ blueprint! {
                 .updateable_non_fungible_data(rule!(require(controller_badge.resource_address())), LOCKED)
                 .no_initial_supply();
 
+            let withdrawal_ticket = ResourceBuilder::new_non_fungible()
+                .metadata("name", name.clone() + "'s Withdrawal Ticket")
+                .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
+            let flash_loan_receipt = ResourceBuilder::new_non_fungible()
+                .metadata("name", name.clone() + "'s Flash Loan Receipt")
+                .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .restrict_deposit(rule!(deny_all), LOCKED)
+                .no_initial_supply();
+
+            let debt_claim = ResourceBuilder::new_non_fungible()
+                .metadata("name", name.clone() + "'s Debt Claim")
+                .mintable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(controller_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
             let rules = AccessRules::new()
                 .method("use_dao", rule!(require(admin_badge)))
                 .method("use_oracle", rule!(require(admin_badge)))
+                .method("use_time_source", rule!(require(admin_badge)))
                 .method("change_interest_rates", rule!(require(admin_badge)))
+                .method("change_interest_rate_model", rule!(require(admin_badge)))
                 .method("change_fee", rule!(require(admin_badge)))
                 .method("change_tolerance_threshold", rule!(require(admin_badge)))
                 .method("change_compensate_rate", rule!(require(admin_badge)))
+                .method("change_default_policy", rule!(require(admin_badge)))
+                .method("change_tranche_policy", rule!(require(admin_badge)))
+                .method("change_term_bonuses", rule!(require(admin_badge)))
+                .method("change_reserve_factor", rule!(require(admin_badge)))
+                .method("auction_debt_claim", rule!(require(admin_badge)))
+                .method("change_exposure_limits", rule!(require(admin_badge)))
+                .method("change_guardian", rule!(require(admin_badge)))
+                .method("change_flash_loan_fee", rule!(require(admin_badge)))
+                .method("change_circuit_breaker", rule!(require(admin_badge)))
+                .method("list_checkout", rule!(require(admin_badge)))
+                .method("delist_checkout", rule!(require(admin_badge)))
                 .method("withdraw_fee", rule!(require(admin_badge)))
+                .method("list_asset", rule!(require(admin_badge)))
+                .method("change_asset_interest_rates", rule!(require(admin_badge)))
+                .method("change_peg_policy", rule!(require(admin_badge)))
+                .method("withdraw_asset_fee", rule!(require(admin_badge)))
+                .method("use_pool_token", rule!(require(admin_badge)))
                 .default(rule!(allow_all));
 
             let controller_badge_resource_address = controller_badge.resource_address();
@@ -243,17 +1283,68 @@ blueprint! {
 
                 controller_badge: Vault::with_bucket(controller_badge),
                 account_nft: account_nft,
+                withdrawal_ticket: withdrawal_ticket,
+                guardian: None,
+                paused: Vec::new(),
+                circuit_breaker: None,
+                breaker_checkpoint: (0, 0, Decimal::ZERO),
+                exposure_limits: None,
+                exposures: HashMap::new(),
+                product_exposures: HashMap::new(),
+                business_exposure: Decimal::ZERO,
+                flash_loan_receipt: flash_loan_receipt,
+                flash_loan_fee: dec!("0.0009"),
+                outstanding_flash_loans: Decimal::ZERO,
+                debt_claim: debt_claim,
+                claim_vault: Vault::new(debt_claim),
+                claim_auctions: HashMap::new(),
+                claim_routes: HashMap::new(),
+                claim_payouts: HashMap::new(),
+                claim_id_counter: 0,
+                withdrawals: HashMap::new(),
+                withdrawal_queue: Vec::new(),
+                withdrawal_queue_head: 0,
+                junior_withdrawal_queue: Vec::new(),
+                junior_withdrawal_queue_head: 0,
+                tranche_policy: None,
+                asset_markets: HashMap::new(),
+                asset_debts: LazyMap::new(),
+                senior_shortfall: Decimal::ZERO,
+                term_bonuses: TermBonuses { three_months: Decimal::ZERO, six_months: Decimal::ZERO, twelve_months: Decimal::ZERO },
+                withdrawal_id_counter: 0,
+                withdrawal_vault: Vault::new(stablecoin),
                 interest_rates: interest_rates,
+                interest_rate_model: None,
+                borrow_rates: LazyMap::new(),
+                loan_checkpoints: LazyMap::new(),
                 vault: Vault::new(stablecoin),
                 total_return: Decimal::zero(),
                 fee_vault: Vault::new(stablecoin),
+                reserve_vault: Vault::new(stablecoin),
+                reserve_factor: Decimal::ZERO,
+                reserve_payouts: Decimal::ZERO,
+                dao_payouts: Decimal::ZERO,
                 fee: fee / dec!("100"),
                 lenders: HashMap::new(),
+                senior: TranchePool { cohorts: Vec::new() },
+                junior: TranchePool { cohorts: Vec::new() },
+                locked_interest: Decimal::ZERO,
+                token_locked_interest: Decimal::ZERO,
+                interest_release_time: 0,
+                token_capital_time: Decimal::ZERO,
+                token_capital_time_update: 0,
+                pool_token: None,
+                token_pool: None,
                 tolerance_threshold: tolerance_threshold / dec!("100"),
                 credit_service: credit_service,
                 oracle: (oracle.0, Vault::with_bucket(oracle.1)),
+                time_source: None,
                 dao: dao,
-                compensate_rate: compensate_rate / dec!("100")
+                compensate_rate: compensate_rate / dec!("100"),
+                default_policy: default_policy,
+                delegations: HashMap::new(),
+                delegation_id_counter: 0,
+                checkouts: Vec::new()
 
             }
             .instantiate()
@@ -273,6 +1364,16 @@ blueprint! {
             bucket
         }
 
+        /// This method is for the protocol operator to change the time source using.
+        /// 
+        /// The time source validate the time before the protocol use it, 
+        /// the protocol's controller badge must be listed on the time source.
+        /// 
+        /// Input: the GroundTimeSource component address, None to read the time directly from the oracle.
+        pub fn use_time_source(&mut self, time_source: Option<ComponentAddress>) {
+            self.time_source = time_source
+        }
+
         /// This method is for the protocol operator to refund the oracle account.
         pub fn refund_oracle_account(&self, bucket: Bucket) -> Bucket {
             let neuracle: NeuRacle = self.oracle.0.into();
@@ -281,28 +1382,53 @@ blueprint! {
         }
 
         /// This method is for users to lend their stable coin to the protocol and get the lending badge to become protocol's lenders.
-        /// input: the stablecoin bucket
+        /// input: the stablecoin bucket and the deposit term (term deposits cannot be withdrawn until maturity and earn a bonus share of interest)
         /// output: The Lending Account NFT.
-        pub fn new_lending_account(&mut self, stablecoin: Bucket) -> Bucket {
+        pub fn new_lending_account(&mut self, stablecoin: Bucket, term: DepositTerm) -> Bucket {
+            self.new_tranche_lending_account(stablecoin, Tranche::Senior, term)
+        }
+
+        /// This method is for lenders to lend their stable coin into a tranche of the protocol.
+        /// 
+        /// The junior tranche absorbs loan defaults first and earns a higher share of interest, the senior tranche is protected by the junior tranche.
+        /// ### Input: 
+        /// - stablecoin: the stablecoin bucket.
+        /// - tranche: the lending tranche.
+        /// - term: the deposit term.
+        /// ### Output: 
+        /// The Account NFT.
+        pub fn new_tranche_lending_account(&mut self, stablecoin: Bucket, tranche: Tranche, term: DepositTerm) -> Bucket {
 
             assert!(stablecoin.resource_address() == self.vault.resource_address(), "Wrong resource!");
 
-            let neuracle: NeuRacle = self.oracle.0.into();
-            let data_proof = self.oracle.1.create_proof();
-            let current = neuracle.get_data(data_proof);
-            let current: u64 = current.parse().expect("Wrong data!");
+            self.assert_active(LendingAction::Deposit);
+
+            let current = self.current_time();
 
             let amount = stablecoin.amount();
             self.vault.put(stablecoin);
             info!("You have lent {} stable coin to the protocol", amount);
             self.total_return += amount;
             let id = NonFungibleId::random();
-            self.lenders.insert(id.clone(), Lender{ lending_amount: amount, start_time: current });
+            self.release_interest(current);
+            let weight = Decimal::ONE + self.term_bonuses.bonus(term);
+            let pool = self.tranche_mut(tranche);
+            let mut lender = pool.new_lender(tranche, weight, current);
+            pool.lend(&mut lender, amount);
+            self.lenders.insert(id.clone(), lender);
+            if tranche == Tranche::Senior {
+                self.check_tranche_ratio();
+            }
+            self.fill_withdrawal_queue();
+            let maturity = if term == DepositTerm::Flexible { 0 } else { current + term.length() };
+            if maturity != 0 {
+                info!("Your term deposit will be matured at {} (unix time)", maturity);
+            }
             self.controller_badge.authorize(|| {
                 borrow_resource_manager!(self.account_nft)
                     .mint_non_fungible(
                         &id,
-                        Account {}
+                        Account { term, maturity }
                 )
             })
 
@@ -322,24 +1448,44 @@ blueprint! {
 
             self.assert_protocol_vault(amount);
 
+            self.check_outflow(LendingAction::Withdraw, amount);
+
             let id = account_proof.non_fungible::<Account>().id();
 
+            let current = self.current_time();
+
+            account_proof.non_fungible::<Account>().data().assert_matured(current);
+
+            self.release_interest(current);
+
             if let Some(lender) = self.lenders.get_mut(&id) {
 
                 let fee = amount * self.fee;
 
                 let return_amount = amount - fee;
 
-                info!("Your current account have {} stable coins", lender.lending_amount);
+                let tranche = lender.tranche;
+
+                let pool = match tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
+
+                pool.settle(lender);
+
+                let pool_amount = pool.amount_of(lender);
+
+                info!("Your current account have {} stable coins", pool_amount + lender.delegated);
 
                 info!("Withdrawing {} stable coins", amount);
 
-                assert!(lender.lending_amount >= amount, "Your account amount is not enough!");
+                assert!(pool_amount >= amount, "Your account amount is not enough! You have {} stable coins delegated.", lender.delegated);
 
-                lender.lending_amount -= amount;
+                pool.take(lender, amount);
 
                 self.total_return -= amount;
 
+                if tranche == Tranche::Junior {
+                    self.check_tranche_ratio();
+                }
+
                 let mut bucket = self.vault.take(amount);
 
                 self.deposit_fee(bucket.take(fee));
@@ -357,6 +1503,10 @@ blueprint! {
         /// output: Lender's withdrawal
         pub fn withdraw_all(&mut self, account_badges: Bucket) -> Bucket {
 
+            if Some(account_badges.resource_address()) == self.pool_token {
+                return self.redeem_pool_token(account_badges)
+            }
+
             assert!(account_badges.resource_address() == self.account_nft, "Wrong resource");
 
             let account_badges_data = account_badges.non_fungibles::<Account>();
@@ -365,17 +1515,29 @@ blueprint! {
 
             let mut withdraw_amount = Decimal::ZERO;
 
+            let mut junior = false;
+
+            let current = self.current_time();
+
             for account_badge in account_badges_data {
 
+                account_badge.data().assert_matured(current);
+
                 let id = account_badge.id();
 
-                if let Some(lender) = self.lenders.remove(&id) {
+                if let Some(mut lender) = self.lenders.remove(&id) {
+
+                    assert!(lender.delegated == Decimal::ZERO, "You have to revoke all your credit delegations first.");
+
+                    junior = junior || lender.tranche == Tranche::Junior;
+
+                    let amount = self.remove_shares(&mut lender, current);
 
-                    withdraw_amount += lender.lending_amount;
+                    withdraw_amount += amount;
     
-                    fee += withdraw_amount * self.fee;
+                    fee += amount * self.fee;
     
-                    self.total_return -= withdraw_amount;
+                    self.total_return -= amount;
     
                 } else {panic!("The protocol don't have your lender account.")}
 
@@ -385,8 +1547,14 @@ blueprint! {
                 account_badges.burn()
             });
 
+            if junior {
+                self.check_tranche_ratio();
+            }
+
             self.assert_protocol_vault(withdraw_amount);
 
+            self.check_outflow(LendingAction::Withdraw, withdraw_amount);
+
             let return_amount = withdraw_amount - fee;
 
             let mut bucket = self.vault.take(withdraw_amount);
@@ -401,165 +1569,1593 @@ blueprint! {
 
         }
 
-        // The following method will not work...
-        // /// This method is for the permissioned borrower to take a loan from their revolving credit.
-        // /// ### Input: 
-        // /// - id_proof: the Identity SBT proof.
-        // /// - credit_sbt: the Credit SBT proof.
-        // /// - amount: the amount borrower wish to loan.
-        // /// ### Output: 
-        // /// borrower's loan by amount.
-        // pub fn revolving_credit(&mut self, id_proof: Proof, credit_sbt: Proof, amount: Decimal) -> Bucket {
-            
-        //     assert!(amount > Decimal::zero(), "Wrong data provided!");
+        /// This method is for lenders to queue a withdrawal when the protocol's vault is short.
+        /// 
+        /// The queued amount is taken out of the lender's account right away and won't earn any more interest.
+        /// The withdrawal can only be queued when the amount is more than the protocol's vault, otherwise the lender should withdraw directly.
+        /// ### Input: 
+        /// - account_proof: the lender's Account NFT proof.
+        /// - amount: the withdrawal amount.
+        /// ### Output: 
+        /// The withdrawal ticket NFT.
+        pub fn enqueue_withdrawal(&mut self, account_proof: Proof, amount: Decimal) -> Bucket {
 
-        //     let credit_service: GroundCredit = self.credit_service.into();
+            assert!(account_proof.resource_address() == self.account_nft, "Wrong resource");
 
-        //     assert!(self.vault.amount() / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+            self.assert_active(LendingAction::Withdraw);
 
-        //     let credit_data = credit_sbt.non_fungible::<Credit>().data();
+            assert!(amount > Decimal::ZERO, "Wrong data provided!");
 
-        //     let (interest_rate, time) = match credit_data.credit_type {
+            assert!(amount > self.vault.amount(), "The protocol's vault is not short, please withdraw directly instead.");
 
-        //         CreditType::Revolving(revolving_types) => {
-        //             match revolving_types {
-        //                 RevolvingTypes::Monthly => {(self.interest_rates.monthly.interest_rate, MONTH)}
+            let account = account_proof.non_fungible::<Account>().id();
 
-        //                 RevolvingTypes::Yearly => {(self.interest_rates.yearly.interest_rate, YEAR)}
-        //             }
-        //         }
+            let current = self.current_time();
 
-        //         _ => {panic!("Wrong credit type!")}
-        //     };
+            account_proof.non_fungible::<Account>().data().assert_matured(current);
 
-        //     let neuracle: NeuRacle = self.oracle.0.into();
-        //     let data_proof = self.oracle.1.create_proof();
-        //     let current = neuracle.get_data(data_proof);
-        //     let current: u64 = current.parse().unwrap();
+            account_proof.drop();
 
-        //     let due_time = credit_data.due_time;
+            self.release_interest(current);
 
-        //     assert!(due_time == 0 || due_time > current, "Your credit is overdue, please repay your loan first!");
+            let lender = self.lenders.get_mut(&account).expect("The protocol don't have your lender account.");
 
-        //     let (_, allowance) = credit_service.get_revolving_credit_amount(id_proof.clone(), credit_sbt.clone());
-            
-        //     assert!(allowance >= amount, "Your current credit allowance is not enough!");
+            let tranche = lender.tranche;
 
-        //     let increase_debt_interest = amount * interest_rate;
+            let pool = match tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
 
-        //     credit_service.update_debt(credit_sbt.clone(), self.controller_badge.create_proof(), credit_data.current_debt + amount, credit_data.debt_interest + increase_debt_interest, Decimal::ZERO);
+            pool.settle(lender);
 
-        //     if due_time == 0 {
-        //         credit_service.update_debt_time(credit_sbt.clone(), self.controller_badge.create_proof(), current + time, current);
-                
-        //     } else {
-                
-        //     }
+            assert!(pool.amount_of(lender) >= amount, "Your account amount is not enough! You have {} stable coins delegated.", lender.delegated);
 
-        //     credit_sbt.drop(); id_proof.drop();
+            pool.take(lender, amount);
 
-        //     info!("You have taken a {} loan from your credit", amount);
+            self.total_return -= amount;
 
-        //     self.vault.take(amount)
-            
-        // }
+            let ticket_id = self.withdrawal_id_counter;
 
-        // /// This method is for the permissioned borrower to take their installment loan.
-        // /// ### Input: 
-        // /// - id_proof: the Identity SBT proof.
-        // /// - credit_sbt: the Credit SBT proof.
-        // /// - installment_credit_badge: the Installment Credit Badge.
-        // /// ### Output: 
-        // /// All the borrower's installment loan.
-        // pub fn installment_credit(&mut self, id_proof: Proof, credit_sbt: Proof, installment_credit_badge: Bucket) -> Bucket {
+            self.withdrawal_id_counter += 1;
 
-        //     let credit_service: GroundCredit = self.credit_service.into();
+            self.withdrawals.insert(ticket_id, QueuedWithdrawal { account: account.clone(), remaining: amount, filled: Decimal::ZERO, tranche });
 
-        //     assert!(self.vault.amount() / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+            match tranche {
+                Tranche::Senior => self.withdrawal_queue.push(ticket_id),
+                Tranche::Junior => {
+                    self.check_tranche_ratio();
+                    self.junior_withdrawal_queue.push(ticket_id)
+                }
+            }
 
-        //     let neuracle: NeuRacle = self.oracle.0.into();
-        //     let data_proof = self.oracle.1.create_proof();
-        //     let current = neuracle.get_data(data_proof);
-        //     let current: u64 = current.parse().unwrap();
+            self.fill_withdrawal_queue();
 
-        //     let amount = credit_service.use_installment_credit(id_proof, credit_sbt, self.controller_badge.create_proof(), installment_credit_badge, current);
+            info!("You have queued a {} stable coins withdrawal. Ticket id: {}", amount, ticket_id);
 
-        //     info!("You have taken a {} loan from your installment credit.", amount);
+            self.controller_badge.authorize(|| {
+                borrow_resource_manager!(self.withdrawal_ticket)
+                    .mint_non_fungible(
+                        &NonFungibleId::from_u64(ticket_id),
+                        WithdrawalTicket { ticket_id, account, amount }
+                )
+            })
 
-        //     self.vault.take(amount)
+        }
 
-        // }
+        /// This method is for ticket holders to claim the filled amount of their queued withdrawal.
+        /// 
+        /// Input: the withdrawal ticket NFT.
+        /// 
+        /// Output: the filled withdrawal and the ticket (None if the withdrawal has been filled and claimed in full, the ticket is burnt in that case).
+        pub fn claim_withdrawal(&mut self, ticket: Bucket) -> (Bucket, Option<Bucket>) {
 
-        // /// This method is for the permissioned borrower to repay their loan.
-        // /// ### Input: 
-        // /// - id_proof: the Identity SBT proof.
-        // /// - credit_sbt: the Credit SBT proof.
-        // /// - repayment: the repayment stablecoin bucket.
-        // /// ### Output: 
-        // /// Remainder of borrower stablecoin bucket.
-        // /// 
-        // /// From this method, when the borrower repaid their debt in full, 
-        // /// the method will automatically increase lender's lending amount if they're eligible for the interest 
-        // /// (the borrow is made after lender's create the lending account).
-        // /// 
-        // /// Borrower can also make a period installment repayment in advance, their credit data will automatically updated through the method.
-        // pub fn repay(&mut self, id_proof: Proof, credit_proof: Proof, mut repayment: Bucket) -> Bucket {
+            assert!(ticket.resource_address() == self.withdrawal_ticket, "Wrong resource");
 
-        //     assert!(repayment.resource_address() == self.vault.resource_address(), "Wrong resource.");
+            self.assert_active(LendingAction::Withdraw);
 
-        //     let credit_service: GroundCredit = self.credit_service.into();
+            let ticket_id = ticket.non_fungible::<WithdrawalTicket>().data().ticket_id;
 
-        //     credit_service.check_id_and_credit(id_proof.clone(), credit_proof.clone());
+            let withdrawal = self.withdrawals.get_mut(&ticket_id).expect("The withdrawal doesn't exist.");
 
-        //     let credit_data = credit_proof.non_fungible::<Credit>().data();
+            let mut bucket = self.withdrawal_vault.take(withdrawal.filled);
 
-        //     match credit_data.credit_type {
+            withdrawal.filled = Decimal::ZERO;
 
-        //         CreditType::Installment(_) => {
+            let done = withdrawal.remaining == Decimal::ZERO;
 
-        //             let mut total_repaid = Decimal::ZERO;
+            let fee = bucket.amount() * self.fee;
 
-        //             let mut new_debt = Decimal::ZERO;
+            self.deposit_fee(bucket.take(fee));
 
-        //             let mut new_debt_interest = Decimal::ZERO;
-                    
-        //             let mut new_extra_debt = Decimal::ZERO;
+            info!("You have paid {} protocol fee and withdrawed {} stable coins from the protocol", fee, bucket.amount());
 
-        //             let mut amount = repayment.amount();
+            if done {
+                self.withdrawals.remove(&ticket_id);
+                self.controller_badge.authorize(|| {
+                    ticket.burn()
+                });
+                (bucket, None)
+            } else {
+                (bucket, Some(ticket))
+            }
 
-        //             while new_debt > Decimal::ZERO {
+        }
 
-        //                 (new_debt, new_debt_interest, new_extra_debt) = self.get_total_debt(credit_proof.clone());
+        /// This method is for ticket holders to cancel their queued withdrawal.
+        /// 
+        /// The unfilled amount is lent back into the lender's account, the filled amount is withdrawn.
+        /// 
+        /// Input: the withdrawal ticket NFT.
+        /// 
+        /// Output: the filled withdrawal.
+        pub fn cancel_withdrawal(&mut self, ticket: Bucket) -> Bucket {
 
-        //                 if new_debt + new_debt_interest <= amount {
+            assert!(ticket.resource_address() == self.withdrawal_ticket, "Wrong resource");
 
-        //                     self.vault.put(repayment.take(new_debt + new_debt_interest));
+            self.assert_active(LendingAction::Withdraw);
 
-        //                     amount -= new_debt + new_debt_interest;
+            let ticket_id = ticket.non_fungible::<WithdrawalTicket>().data().ticket_id;
 
-        //                     total_repaid += new_debt;
+            let withdrawal = self.withdrawals.remove(&ticket_id).expect("The withdrawal doesn't exist.");
 
-        //                     let debt_start = credit_data.current_debt_start_time;
+            if withdrawal.remaining > Decimal::ZERO {
 
-        //                     let mut eligible_return = Decimal::ZERO;
-                            
-        //                     self.lenders.values().for_each(|lender| {
-        //                         if lender.start_time < debt_start {
-        //                             eligible_return += lender.lending_amount
-        //                         }
-        //                     });
+                let current = self.current_time();
 
-        //                     let interest = Decimal::ONE + new_debt_interest / eligible_return;
+                self.release_interest(current);
 
-        //                     self.lenders.values_mut().for_each(|lender| {
-        //                         if &lender.start_time < &debt_start {
-        //                             lender.lending_amount *= interest
-        //                         }
-        //                     });
+                let lender = self.lenders.get_mut(&withdrawal.account).expect("Your lender account has been closed, please claim your withdrawal instead.");
 
-        //                     if new_extra_debt != Decimal::ZERO {
+                let pool = match lender.tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
 
-        //                         if new_extra_debt <= amount {
+                pool.settle(lender);
+
+                pool.lend(lender, withdrawal.remaining);
+
+                self.total_return += withdrawal.remaining;
+
+                info!("{} stable coins unfilled withdrawal has been lent back into your account", withdrawal.remaining);
+
+            }
+
+            let mut bucket = self.withdrawal_vault.take(withdrawal.filled);
+
+            let fee = bucket.amount() * self.fee;
+
+            self.deposit_fee(bucket.take(fee));
+
+            self.controller_badge.authorize(|| {
+                ticket.burn()
+            });
+
+            info!("You have paid {} protocol fee and withdrawed {} stable coins from the protocol", fee, bucket.amount());
+
+            bucket
+
+        }
+
+        /// Read only method to get a queued withdrawal.
+        pub fn get_queued_withdrawal(&self, ticket_id: u64) -> Option<QueuedWithdrawal> {
+            self.withdrawals.get(&ticket_id).map(|withdrawal| QueuedWithdrawal {
+                account: withdrawal.account.clone(),
+                remaining: withdrawal.remaining,
+                filled: withdrawal.filled,
+                tranche: withdrawal.tranche
+            })
+        }
+
+        /// Fill the withdrawal queues from the protocol's vault in FIFO order, the senior queue is filled first.
+        fn fill_withdrawal_queue(&mut self) {
+            if self.fill_queue(Tranche::Senior) {
+                self.fill_queue(Tranche::Junior);
+            }
+        }
+
+        /// Fill a tranche's withdrawal queue from the protocol's vault in FIFO order.
+        /// 
+        /// Return true if the queue has been filled in full.
+        fn fill_queue(&mut self, tranche: Tranche) -> bool {
+
+            loop {
+
+                let (queue, head) = match tranche {
+                    Tranche::Senior => (&self.withdrawal_queue, self.withdrawal_queue_head),
+                    Tranche::Junior => (&self.junior_withdrawal_queue, self.junior_withdrawal_queue_head)
+                };
+
+                if head as usize >= queue.len() { return true }
+
+                if self.vault.amount() == Decimal::ZERO { return false }
+
+                let ticket_id = queue[head as usize];
+
+                let fill = match self.withdrawals.get_mut(&ticket_id) {
+                    None => Decimal::ZERO,
+                    Some(withdrawal) => {
+                        let fill = if withdrawal.remaining <= self.vault.amount() { withdrawal.remaining } else { self.vault.amount() };
+                        withdrawal.remaining -= fill;
+                        withdrawal.filled += fill;
+                        if withdrawal.remaining != Decimal::ZERO {
+                            self.withdrawal_vault.put(self.vault.take(fill));
+                            return false
+                        }
+                        fill
+                    }
+                };
+
+                self.withdrawal_vault.put(self.vault.take(fill));
+
+                match tranche {
+                    Tranche::Senior => self.withdrawal_queue_head += 1,
+                    Tranche::Junior => self.junior_withdrawal_queue_head += 1
+                }
+
+            }
+        }
+
+        /// This method is for the protocol operator to start the pool token mode.
+        /// 
+        /// Lenders can then lend to the protocol for a fungible pool-share token instead of the Account NFT.
+        /// The pool-share token's redemption value rises as borrowers repay their loan.
+        pub fn use_pool_token(&mut self) {
+
+            assert!(self.pool_token.is_none(), "The protocol is already using the pool token mode.");
+
+            let current = self.current_time();
+
+            let pool_token = ResourceBuilder::new_fungible()
+                .metadata("name", "Ground Lending Pool Share")
+                .mintable(rule!(require(self.controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(self.controller_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
+            self.pool_token = Some(pool_token);
+
+            self.release_interest(current);
+
+            self.token_pool = Some(SharePool { shares: Decimal::ZERO, value: Decimal::ZERO });
+
+            info!("The protocol is using the pool token mode, pool token address: {}", pool_token);
+
+        }
+
+        /// This method is for users to lend their stable coin to the protocol for the fungible pool-share token.
+        /// 
+        /// The interest released before the lending is already in the pool token's value, 
+        /// the new pool token holders only take their part of the locked interest released after the lending, the same as new lending accounts.
+        /// 
+        /// Input: the stablecoin bucket.
+        /// 
+        /// Output: the pool-share token.
+        pub fn lend_for_pool_token(&mut self, stablecoin: Bucket) -> Bucket {
+
+            assert!(self.pool_token.is_some(), "The protocol doesn't use the pool token mode.");
+
+            assert!(stablecoin.resource_address() == self.vault.resource_address(), "Wrong resource!");
+
+            self.assert_active(LendingAction::Deposit);
+
+            let current = self.current_time();
+
+            let amount = stablecoin.amount();
+
+            self.vault.put(stablecoin);
+
+            self.total_return += amount;
+
+            info!("You have lent {} stable coin to the protocol", amount);
+
+            let pool_token = self.mint_pool_token(amount, current);
+
+            self.check_tranche_ratio();
+
+            self.fill_withdrawal_queue();
+
+            pool_token
+
+        }
+
+        /// This method is for lenders to redeem their pool-share token for the return amount.
+        /// 
+        /// Input: the pool-share token bucket.
+        /// 
+        /// Output: Lender's withdrawal.
+        pub fn redeem_pool_token(&mut self, pool_token: Bucket) -> Bucket {
+
+            assert!(Some(pool_token.resource_address()) == self.pool_token, "Wrong resource");
+
+            let current = self.current_time();
+
+            let withdraw_amount = self.burn_pool_token(pool_token, current);
+
+            self.assert_protocol_vault(withdraw_amount);
+
+            self.check_outflow(LendingAction::Withdraw, withdraw_amount);
+
+            let fee = withdraw_amount * self.fee;
+
+            let mut bucket = self.vault.take(withdraw_amount);
+
+            self.deposit_fee(bucket.take(fee));
+
+            info!("You have paid {} protocol fee and withdrawed {} stable coins from the protocol", fee, withdraw_amount - fee);
+
+            bucket
+
+        }
+
+        /// This method is for lenders to convert their Account NFTs into the pool-share token.
+        /// 
+        /// The conversion is one-way, the Account NFTs will be burnt.
+        /// 
+        /// Input: the Account NFT bucket.
+        /// 
+        /// Output: the pool-share token.
+        pub fn convert_to_pool_token(&mut self, account_badges: Bucket) -> Bucket {
+
+            assert!(self.pool_token.is_some(), "The protocol doesn't use the pool token mode.");
+
+            assert!(account_badges.resource_address() == self.account_nft, "Wrong resource");
+
+            let mut amount = Decimal::ZERO;
+
+            let current = self.current_time();
+
+            for account_badge in account_badges.non_fungibles::<Account>() {
+
+                account_badge.data().assert_matured(current);
+
+                let mut lender = self.lenders.remove(&account_badge.id()).expect("The protocol don't have your lender account.");
+
+                assert!(lender.delegated == Decimal::ZERO, "You have to revoke all your credit delegations first.");
+
+                assert!(lender.tranche == Tranche::Senior, "Only senior tranche accounts can be converted into the pool token.");
+
+                amount += self.remove_shares(&mut lender, current);
+
+            }
+
+            self.controller_badge.authorize(|| {
+                account_badges.burn()
+            });
+
+            info!("You have converted {} stable coins return amount into the pool token", amount);
+
+            self.mint_pool_token(amount, current)
+
+        }
+
+        /// This method is for lenders to get their lending accounts' return amount, include the released interest.
+        /// 
+        /// The locked interest is released until the current time first.
+        /// 
+        /// Input: the Account NFT proof.
+        /// 
+        /// Output: (return amount, delegated amount)
+        pub fn get_account_return(&mut self, account_proof: Proof) -> (Decimal, Decimal) {
+
+            assert!(account_proof.resource_address() == self.account_nft, "Wrong resource");
+
+            let current = self.current_time();
+
+            self.release_interest(current);
+
+            let mut amount = Decimal::ZERO;
+
+            let mut delegated = Decimal::ZERO;
+
+            for account in account_proof.non_fungibles::<Account>() {
+                let lender = self.lenders.get(&account.id()).expect("The protocol don't have your lender account.");
+                let pool = match lender.tranche { Tranche::Senior => &self.senior, Tranche::Junior => &self.junior };
+                amount += pool.amount_of(lender);
+                delegated += lender.delegated;
+            }
+
+            account_proof.drop();
+
+            (amount, delegated)
+
+        }
+
+        /// Read only method to get the senior and junior tranches' return amount.
+        /// 
+        /// Output: (senior return amount, junior return amount, senior shortfall)
+        pub fn tranche_returns(&self) -> (Decimal, Decimal, Decimal) {
+            let (senior, junior) = self.tranche_returns_of();
+            (senior, junior, self.senior_shortfall)
+        }
+
+        /// Read only method to get the redemption value of one pool-share token.
+        pub fn pool_token_value(&self) -> Decimal {
+            match &self.token_pool {
+                Some(pool) if pool.shares != Decimal::ZERO => pool.value / pool.shares,
+                _ => Decimal::ONE
+            }
+        }
+
+        /// This method is for integrators to take a flash loan from the protocol's vault.
+        /// 
+        /// The loan must be repaid with the flash loan fee in the same transaction through the "repay_flash_loan" method, 
+        /// the flash loan receipt cannot be deposited into any account so the transaction will fail otherwise.
+        /// 
+        /// Input: the loan amount.
+        /// 
+        /// Output: the loan and the flash loan receipt.
+        pub fn flash_loan(&mut self, amount: Decimal) -> (Bucket, Bucket) {
+
+            assert!(amount > Decimal::zero(), "Wrong data provided!");
+
+            self.assert_active(LendingAction::Borrow);
+
+            self.assert_protocol_vault(amount);
+
+            let fee = amount * self.flash_loan_fee;
+
+            let receipt = self.controller_badge.authorize(|| {
+                borrow_resource_manager!(self.flash_loan_receipt)
+                    .mint_non_fungible(
+                        &NonFungibleId::random(),
+                        FlashLoan { amount, fee }
+                )
+            });
+
+            self.outstanding_flash_loans += amount;
+
+            info!("You have taken a {} stable coins flash loan, please repay {} stable coins in this transaction.", amount, amount + fee);
+
+            (self.vault.take(amount), receipt)
+
+        }
+
+        /// This method is for integrators to repay their flash loan.
+        /// 
+        /// The flash loan fee is distributed to all lenders as interest.
+        /// ### Input: 
+        /// - repayment: the repayment stablecoin bucket.
+        /// - receipt: the flash loan receipt.
+        /// ### Output: 
+        /// Remainder of the repayment.
+        pub fn repay_flash_loan(&mut self, mut repayment: Bucket, receipt: Bucket) -> Bucket {
+
+            assert!(repayment.resource_address() == self.vault.resource_address(), "Wrong resource.");
+
+            assert!(receipt.resource_address() == self.flash_loan_receipt, "Wrong resource");
+
+            let loan = receipt.non_fungible::<FlashLoan>().data();
+
+            assert!(repayment.amount() >= loan.amount + loan.fee, "You have to repay {} stable coins for the flash loan.", loan.amount + loan.fee);
+
+            self.vault.put(repayment.take(loan.amount));
+
+            self.outstanding_flash_loans -= loan.amount;
+
+            if self.total_return == Decimal::ZERO {
+                self.deposit_fee(repayment.take(loan.fee));
+            } else {
+                self.vault.put(repayment.take(loan.fee));
+                let interest = self.take_reserve(loan.fee);
+                let current = self.current_time();
+                self.distribute_interest(current, interest);
+                self.total_return += interest;
+            }
+
+            self.fill_withdrawal_queue();
+
+            self.controller_badge.authorize(|| {
+                receipt.burn()
+            });
+
+            info!("You have repaid the {} stable coins flash loan with {} stable coins fee.", loan.amount, loan.fee);
+
+            repayment
+
+        }
+
+        /// This method is for the protocol operator to package a defaulted position as a debt claim NFT and auction it.
+        /// 
+        /// The claim is sold in a descending price auction, the price decrease linearly from the start price to the floor price.
+        /// ### Input: 
+        /// - id: the defaulted borrower's Identity SBT ID.
+        /// - start_price: the auction's start price.
+        /// - floor_price: the auction's floor price.
+        /// - duration: the time the price reach the floor price. (seconds)
+        /// ### Output: 
+        /// The debt claim id.
+        pub fn auction_debt_claim(&mut self, id: NonFungibleId, start_price: Decimal, floor_price: Decimal, duration: u64) -> u64 {
+
+            assert!(start_price >= floor_price && floor_price > Decimal::ZERO && duration > 0, "Wrong data provided!");
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            let credit_id = credit_service.get_credit_id(id.clone()).expect("The user doesn't have a credit SBT.");
+
+            assert!(!self.claim_routes.contains_key(&credit_id), "The defaulted position has already been packaged as a debt claim.");
+
+            let data = borrow_resource_manager!(credit_service.credit_sbt_address()).get_non_fungible_data::<Credit>(&credit_id).data;
+
+            assert!(data.defaulted, "The loan hasn't been declared defaulted.");
+
+            let current = self.current_time();
+
+            let claim_id = self.claim_id_counter;
+
+            self.claim_id_counter += 1;
+
+            let claim = self.controller_badge.authorize(|| {
+                borrow_resource_manager!(self.debt_claim)
+                    .mint_non_fungible(
+                        &NonFungibleId::from_u64(claim_id),
+                        DebtClaim { 
+                            claim_id, 
+                            credit_id: credit_id.clone(), 
+                            principal: data.current_debt, 
+                            interest: data.debt_interest, 
+                            extra_debt: data.extra_debt 
+                        }
+                )
+            });
+
+            self.claim_vault.put(claim);
+
+            self.claim_auctions.insert(claim_id, ClaimAuction {
+                credit_id: credit_id.clone(),
+                start_price,
+                floor_price,
+                start_time: current,
+                end_time: current + duration,
+                sold: false
+            });
+
+            self.claim_routes.insert(credit_id, claim_id);
+
+            info!("The defaulted position of the user ID {} has been packaged as the debt claim no.{}, auction start at {} stable coins.", id, claim_id, start_price);
+
+            claim_id
+
+        }
+
+        /// This method is for collectors to buy a debt claim at the auction's current price.
+        /// 
+        /// The proceeds are distributed to the lenders as recovery.
+        /// ### Input: 
+        /// - claim_id: the debt claim id.
+        /// - payment: the stablecoin bucket.
+        /// ### Output: 
+        /// The debt claim NFT and the payment's remainder.
+        pub fn buy_debt_claim(&mut self, claim_id: u64, mut payment: Bucket) -> (Bucket, Bucket) {
+
+            assert!(payment.resource_address() == self.vault.resource_address(), "Wrong resource.");
+
+            let current = self.current_time();
+
+            let auction = self.claim_auctions.get_mut(&claim_id).expect("The debt claim doesn't exist.");
+
+            assert!(!auction.sold, "The debt claim has already been sold.");
+
+            let price = auction.price(current);
+
+            assert!(payment.amount() >= price, "The debt claim's current price is {} stable coins.", price);
+
+            auction.sold = true;
+
+            self.claim_payouts.insert(claim_id, HashMap::new());
+
+            self.recover(current, payment.take(price));
+
+            info!("You have bought the debt claim no.{} for {} stable coins.", claim_id, price);
+
+            (self.claim_vault.take_non_fungible(&NonFungibleId::from_u64(claim_id)), payment)
+
+        }
+
+        /// This method is for debt claim holders to take the repayments routed to their claim.
+        /// 
+        /// Input: the debt claim NFT proof.
+        /// 
+        /// Output: the routed repayments, a bucket for each stablecoin the borrower has repaid in.
+        pub fn claim_debt_repayments(&mut self, claim_proof: Proof) -> Vec<Bucket> {
+
+            assert!(claim_proof.resource_address() == self.debt_claim, "Wrong resource");
+
+            let claim_id = claim_proof.non_fungible::<DebtClaim>().data().claim_id;
+
+            claim_proof.drop();
+
+            let buckets: Vec<Bucket> = self.claim_payouts.get_mut(&claim_id).expect("The debt claim doesn't have any repayment.")
+                .values_mut()
+                .map(|vault| vault.take_all())
+                .collect();
+
+            info!("You have taken the repayments from the debt claim no.{}.", claim_id);
+
+            buckets
+
+        }
+
+        /// Read only method to get a debt claim auction and its current price.
+        pub fn get_debt_claim_auction(&self, claim_id: u64) -> (ClaimAuction, Decimal) {
+            let auction = self.claim_auctions.get(&claim_id).expect("The debt claim doesn't exist.");
+            let price = auction.price(self.current_time());
+            (ClaimAuction { credit_id: auction.credit_id.clone(), ..*auction }, price)
+        }
+
+        /// Route a defaulted borrower's repayment to the sold debt claim, or distribute it to the lenders as recovery.
+        /// 
+        /// The repayments in a listed stablecoin are recovered to that market's lenders.
+        fn route_recovery(&mut self, credit_id: &NonFungibleId, current: u64, recovery: Bucket) {
+            match self.claim_routes.get(credit_id) {
+                Some(claim_id) if self.claim_auctions.get(claim_id).unwrap().sold => {
+                    info!("The repayment has been routed to the debt claim no.{}.", claim_id);
+                    let payouts = self.claim_payouts.get_mut(claim_id).unwrap();
+                    match payouts.get_mut(&recovery.resource_address()) {
+                        Some(vault) => vault.put(recovery),
+                        None => {
+                            payouts.insert(recovery.resource_address(), Vault::with_bucket(recovery));
+                        }
+                    }
+                }
+                _ if recovery.resource_address() != self.vault.resource_address() => {
+                    let market = self.asset_markets.get_mut(&recovery.resource_address()).unwrap();
+                    market.pool.value += recovery.amount();
+                    market.vault.put(recovery)
+                }
+                _ => self.recover(current, recovery)
+            }
+        }
+
+        /// Close the debt claim route when the defaulted debt has been repaid in full, the claim is burnt if it hasn't been sold.
+        fn close_debt_claim(&mut self, credit_id: &NonFungibleId) {
+            if let Some(claim_id) = self.claim_routes.remove(credit_id) {
+                if !self.claim_auctions.get(&claim_id).unwrap().sold {
+                    self.claim_auctions.remove(&claim_id);
+                    let claim = self.claim_vault.take_non_fungible(&NonFungibleId::from_u64(claim_id));
+                    self.controller_badge.authorize(|| {
+                        claim.burn()
+                    });
+                }
+            }
+        }
+
+        /// This method is for the protocol operator to list a stablecoin market.
+        /// ### Input: 
+        /// - asset: the stablecoin address.
+        /// - interest_rates: the market's revolving credit interest rates.
+        /// - peg_badge: the NeuRacle data badge of the market's peg feed (the price of one asset unit in the protocol's primary stablecoin).
+        /// - peg_policy: the market's peg feed validation policy.
+        pub fn list_asset(&mut self, asset: ResourceAddress, mut interest_rates: RevolvingCreditInterestRates, peg_badge: Bucket, peg_policy: PegPolicy) {
+
+            assert!(asset != self.vault.resource_address() && !self.asset_markets.contains_key(&asset), "The asset is already listed on the protocol.");
+
+            interest_rates.check_rates();
+
+            interest_rates.rates_aggregrate();
+
+            peg_policy.check_policy();
+
+            let share_token = ResourceBuilder::new_fungible()
+                .metadata("name", "Ground Lending Market Share")
+                .metadata("asset", asset.to_string())
+                .mintable(rule!(require(self.controller_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(self.controller_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
+            self.asset_markets.insert(asset, AssetMarket {
+                vault: Vault::new(asset),
+                fee_vault: Vault::new(asset),
+                reserve_vault: Vault::new(asset),
+                pool: SharePool { shares: Decimal::ZERO, value: Decimal::ZERO },
+                share_token,
+                interest_rates,
+                peg_badge: Vault::with_bucket(peg_badge),
+                peg_policy
+            });
+
+            info!("The stablecoin {} is listed on the protocol, market share token address: {}", asset, share_token);
+
+        }
+
+        /// Read only method to get the price of one asset unit in the protocol's primary stablecoin.
+        /// 
+        /// The peg feed's data is "<peg>,<quote unix time>", the quote is stale when it's older than the market's maximum peg age, 
+        /// so a peg that legitimately stays at the same value is still accepted as long as the feed keeps quoting it.
+        /// 
+        /// Output: the peg, None if the peg feed's data is malformed, out of the market's peg bounds, stale or the protocol's time can't be read.
+        pub fn asset_peg(&self, asset: ResourceAddress) -> Option<Decimal> {
+
+            let market = self.asset_markets.get(&asset).expect("The asset is not listed on the protocol.");
+
+            let neuracle: NeuRacle = self.oracle.0.into();
+
+            let data = neuracle.get_data(market.peg_badge.create_proof());
+
+            let (peg, quoted) = data.split_once(',')?;
+
+            let peg: Decimal = peg.trim().parse().ok()?;
+
+            let quoted: u64 = quoted.trim().parse().ok()?;
+
+            if peg < market.peg_policy.min_peg || peg > market.peg_policy.max_peg {
+                return None
+            }
+
+            let current = self.read_time()?;
+
+            if current > quoted + market.peg_policy.max_age {
+                return None
+            }
+
+            Some(peg)
+
+        }
+
+        /// Read the market's validated peg.
+        fn read_peg(&self, asset: ResourceAddress) -> Decimal {
+            self.asset_peg(asset).expect("The market's peg feed is invalid or stale, please come back later.")
+        }
+
+        /// Read only method to get a stablecoin market.
+        /// 
+        /// Output: (market return amount, market vault amount, market share token address, market share token value)
+        pub fn get_asset_market(&self, asset: ResourceAddress) -> (Decimal, Decimal, ResourceAddress, Decimal) {
+            let market = self.asset_markets.get(&asset).expect("The asset is not listed on the protocol.");
+            let value = if market.pool.shares == Decimal::ZERO { Decimal::ONE } else { market.pool.value / market.pool.shares };
+            (market.pool.value, market.vault.amount(), market.share_token, value)
+        }
+
+        /// This method is for lenders to lend a listed stablecoin to its market for the market share token.
+        /// 
+        /// Input: the stablecoin bucket.
+        /// 
+        /// Output: the market share token.
+        pub fn lend_asset(&mut self, stablecoin: Bucket) -> Bucket {
+
+            self.assert_active(LendingAction::Deposit);
+
+            let market = self.asset_markets.get_mut(&stablecoin.resource_address()).expect("The asset is not listed on the protocol.");
+
+            let amount = stablecoin.amount();
+
+            market.vault.put(stablecoin);
+
+            let shares = market.pool.mint(amount);
+
+            let share_token = market.share_token;
+
+            info!("You have lent {} stable coin to the market", amount);
+
+            self.controller_badge.authorize(|| {
+                borrow_resource_manager!(share_token).mint(shares)
+            })
+
+        }
+
+        /// This method is for lenders to redeem their market share token for the return amount.
+        /// 
+        /// Input: the market share token bucket.
+        /// 
+        /// Output: Lender's withdrawal.
+        pub fn redeem_asset(&mut self, share_token: Bucket) -> Bucket {
+
+            self.assert_active(LendingAction::Withdraw);
+
+            let (_, market) = self.asset_markets.iter_mut()
+                .find(|(_, market)| market.share_token == share_token.resource_address())
+                .expect("Wrong resource");
+
+            let amount = market.pool.to_amount(share_token.amount());
+
+            let remain = market.vault.amount();
+
+            assert!(amount <= remain, "Current there are only {} stablecoin left on the market's vault, please come back later!", remain);
+
+            market.pool.shares -= share_token.amount();
+
+            market.pool.value -= amount;
+
+            let fee = amount * self.fee;
+
+            let mut bucket = market.vault.take(amount);
+
+            market.fee_vault.put(bucket.take(fee));
+
+            self.controller_badge.authorize(|| {
+                share_token.burn()
+            });
+
+            info!("You have paid {} protocol fee and withdrawed {} stable coins from the market", fee, amount - fee);
+
+            bucket
+
+        }
+
+        /// This method is for borrowers to take a revolving loan in a listed stablecoin.
+        /// 
+        /// The loan is counted on the borrower's credit in the protocol's primary stablecoin through the market's peg feed.
+        /// ### Input: 
+        /// - id_proof: the borrower's Identity SBT proof.
+        /// - credit_sbt: the borrower's Credit SBT proof.
+        /// - asset: the stablecoin address.
+        /// - amount: the loan amount. (asset unit)
+        /// ### Output: 
+        /// The loan bucket.
+        pub fn asset_credit(&mut self, id_proof: Proof, credit_sbt: Proof, asset: ResourceAddress, amount: Decimal) -> Bucket {
+
+            assert!(amount > Decimal::zero(), "Wrong data provided!");
+
+            let market = self.asset_markets.get(&asset).expect("The asset is not listed on the protocol.");
+
+            assert!(market.pool.value != Decimal::ZERO, "Currently there's no resource on the market's vault, please come back later.");
+
+            assert!((market.vault.amount() - amount) / market.pool.value > self.tolerance_threshold, "Currently you cannot take your credit from this market, please come back later.");
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Identity>().id(), id_proof.resource_address(), credit_sbt.non_fungible::<Credit>().id(), credit_sbt.resource_address());
+
+            let id_data = id_proof.non_fungible::<Identity>().data().data;
+
+            let id = id_proof.non_fungible::<Identity>().id();
+
+            let credit_id = credit_sbt.non_fungible::<Credit>().id();
+
+            credit_sbt.drop(); id_proof.drop();
+
+            self.draw_revolving_credit(id, id_data, credit_id, amount, Some(asset))
+
+        }
+
+        /// This method is for borrowers to repay their loan in a listed stablecoin, in the same stablecoin.
+        /// 
+        /// The principal is repaid first, then the interest. The repayment is settled on the borrower's credit the same as the repayments in the protocol's primary stablecoin: 
+        /// the late repayment penalty is applied, the extra debt is paid from the repayment's surplus at the market's peg into the market's fee vault 
+        /// and on-time repayments restore the borrower's credit score.
+        /// 
+        /// If the borrower's credit is defaulted, the extra debt is also paid from the repayment's surplus at the market's peg 
+        /// and the whole recovery goes to the market's lenders, or to the debt claim holder if the position has been sold as a debt claim.
+        /// ### Input: 
+        /// - id_proof: the borrower's Identity SBT proof.
+        /// - credit_proof: the borrower's Credit SBT proof.
+        /// - repayment: the repayment bucket.
+        /// ### Output: 
+        /// The remainder of the repayment.
+        pub fn repay_asset(&mut self, id_proof: Proof, credit_proof: Proof, mut repayment: Bucket) -> Bucket {
+
+            let asset = repayment.resource_address();
+
+            assert!(self.asset_markets.contains_key(&asset), "The asset is not listed on the protocol.");
+
+            self.assert_active(LendingAction::Repay);
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Identity>().id(), id_proof.resource_address(), credit_proof.non_fungible::<Credit>().id(), credit_proof.resource_address());
+
+            let current = self.current_time();
+
+            let credit_id = credit_proof.non_fungible::<Credit>().id();
+
+            if self.may_recover() {
+                credit_service.recover_credit_score_by_data(self.controller_badge.create_proof(), id_proof.non_fungible::<Identity>().id());
+            }
+
+            let data = credit_proof.non_fungible::<Credit>().data().data;
+
+            let mut debts = self.asset_debts.get(&credit_id).unwrap_or(HashMap::new());
+
+            let debt = debts.get_mut(&asset).expect("You don't have any loan in this stablecoin.");
+
+            let amount = repayment.amount();
+
+            let (principal, interest) = self.split_repayment(amount, debt.principal, debt.interest);
+
+            let value_interest = if debt.interest == Decimal::ZERO { Decimal::ZERO } else { debt.value_interest * interest / debt.interest };
+
+            let value = if debt.principal == Decimal::ZERO { Decimal::ZERO } else { debt.value * principal / debt.principal };
+
+            debt.interest -= interest;
+            debt.principal -= principal;
+            debt.value_interest -= value_interest;
+            debt.value -= value;
+
+            if debt.principal == Decimal::ZERO && debt.interest == Decimal::ZERO {
+                debts.remove(&asset);
+            }
+
+            self.asset_debts.insert(credit_id.clone(), debts);
+
+            if data.defaulted {
+
+                let current_debt = if data.current_debt > value { data.current_debt - value } else { Decimal::ZERO };
+
+                let debt_interest = if data.debt_interest > value_interest { data.debt_interest - value_interest } else { Decimal::ZERO };
+
+                let surplus = amount - principal - interest;
+
+                let (extra_value, extra_paid) = if data.extra_debt == Decimal::ZERO || surplus == Decimal::ZERO {
+                    (Decimal::ZERO, Decimal::ZERO)
+                } else {
+                    let peg = self.read_peg(asset);
+                    if surplus * peg >= data.extra_debt { (data.extra_debt, data.extra_debt / peg) } else { (surplus * peg, surplus) }
+                };
+
+                let extra_debt = data.extra_debt - extra_value;
+
+                let repaid_all = current_debt + debt_interest + extra_debt == Decimal::ZERO;
+
+                let (due_time, current_debt_start_time, defaulted) = if repaid_all {
+                    (0, 0, false)
+                } else {
+                    (data.due_time, data.current_debt_start_time, data.defaulted)
+                };
+
+                credit_service.update_credit(self.controller_badge.create_proof(), credit_id.clone(), 
+                    CreditData {
+                        current_debt,
+                        debt_interest,
+                        extra_debt,
+                        due_time,
+                        current_debt_start_time,
+                        defaulted,
+                        ..data
+                    }
+                );
+
+                id_proof.drop(); credit_proof.drop();
+
+                self.route_recovery(&credit_id, current, repayment.take(interest + principal + extra_paid));
+
+                if repaid_all {
+                    self.close_debt_claim(&credit_id);
+                    info!("You have repaid all your defaulted debt, your credit can be used again.")
+                }
+
+            } else {
+
+                let peg = self.read_peg(asset);
+
+                let surplus = amount - principal - interest;
+
+                let (extra_paid, id_proof, credit_proof) = self.settle_revolving_repayment(id_proof, credit_proof, value, value_interest, surplus * peg);
+
+                id_proof.drop(); credit_proof.drop();
+
+                let extra_paid = if extra_paid / peg <= surplus { extra_paid / peg } else { surplus };
+
+                let market = self.asset_markets.get_mut(&asset).unwrap();
+
+                market.fee_vault.put(repayment.take(extra_paid));
+
+                let reserve = interest * self.reserve_factor;
+
+                if reserve != Decimal::ZERO {
+                    market.reserve_vault.put(repayment.take(reserve));
+                }
+
+                market.pool.value += interest - reserve;
+
+                if value > Decimal::ZERO {
+                    self.release_exposure(&credit_id, value, LoanSource::Market);
+                }
+
+                self.asset_markets.get_mut(&asset).unwrap().vault.put(repayment.take(interest - reserve + principal));
+
+            }
+
+            info!("You have repaid {} stable coins interest and {} stable coins principal of your loan in the market.", interest, principal);
+
+            repayment
+
+        }
+
+        /// Read only method to get the borrower's loans in the stablecoin markets.
+        /// 
+        /// Input: the borrower's Credit SBT ID.
+        pub fn get_asset_debts(&self, credit_id: NonFungibleId) -> HashMap<ResourceAddress, AssetDebt> {
+            self.asset_debts.get(&credit_id).unwrap_or(HashMap::new())
+        }
+
+        /// Write off the borrower's unpaid principal in the stablecoin markets against each market's lenders, 
+        /// each market's insurance reserve covers the loss first.
+        /// 
+        /// Return the written off value counted on the borrower's credit SBT.
+        fn write_off_asset_debts(&mut self, credit_id: &NonFungibleId) -> Decimal {
+
+            let mut value = Decimal::ZERO;
+
+            if let Some(debts) = self.asset_debts.get(credit_id) {
+
+                for (asset, debt) in debts.iter() {
+
+                    let market = self.asset_markets.get_mut(asset).unwrap();
+
+                    let covered = if debt.principal > market.reserve_vault.amount() { market.reserve_vault.amount() } else { debt.principal };
+
+                    if covered != Decimal::ZERO {
+                        let cover = market.reserve_vault.take(covered);
+                        market.vault.put(cover);
+                        info!("The market's insurance reserve has covered {} stablecoins of the loss.", covered);
+                    }
+
+                    let uncovered = debt.principal - covered;
+
+                    let loss = if uncovered <= market.pool.value { uncovered } else { market.pool.value };
+
+                    market.pool.value -= loss;
+
+                    value += debt.value;
+
+                }
+            }
+
+            value
+        }
+
+        /// This method is for lenders to earmark part of their lending amount for a specific borrower at the interest rate they set.
+        /// ### Input: 
+        /// - account_proof: the lender's Account NFT proof.
+        /// - borrower: the borrower's Identity SBT ID.
+        /// - allowance: the delegated allowance.
+        /// - interest_rate: the interest rate of the delegated loan. (%)
+        /// - term: the loan term. (seconds)
+        /// ### Output: 
+        /// The delegation id.
+        /// 
+        /// The delegated amount is not eligible for the pool's interest, the lender will only earn the interest (and take the loss) from the delegated loan.
+        pub fn delegate_credit(&mut self, account_proof: Proof, borrower: NonFungibleId, allowance: Decimal, interest_rate: Decimal, term: u64) -> u64 {
+
+            assert!(account_proof.resource_address() == self.account_nft, "Wrong resource");
+
+            assert!(allowance > Decimal::ZERO && term > 0, "Wrong data provided!");
+
+            assert_rate(interest_rate);
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            credit_service.get_credit_id(borrower.clone()).expect("The borrower doesn't have a credit SBT.");
+
+            let lender_id = account_proof.non_fungible::<Account>().id();
+
+            account_proof.drop();
+
+            let current = self.current_time();
+
+            self.release_interest(current);
+
+            let lender = self.lenders.get_mut(&lender_id).expect("The protocol don't have your lender account.");
+
+            let tranche = lender.tranche;
+
+            let pool = match tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
+
+            pool.settle(lender);
+
+            assert!(pool.amount_of(lender) >= allowance, "Your account amount is not enough!");
+
+            pool.take(lender, allowance);
+
+            lender.delegated += allowance;
+
+            if tranche == Tranche::Junior {
+                self.check_tranche_ratio();
+            }
+
+            let id = self.delegation_id_counter;
+
+            self.delegation_id_counter += 1;
+
+            self.delegations.insert(id, Delegation {
+                lender: lender_id,
+                borrower: borrower.clone(),
+                allowance,
+                drawn: Decimal::ZERO,
+                debt_interest: Decimal::ZERO,
+                interest_rate: interest_rate / dec!("100"),
+                term,
+                due_time: 0,
+                revoked: false,
+                defaulted: false
+            });
+
+            info!("You have delegated {} stable coins credit to the user ID {} at {}% interest rate. Delegation id: {}", allowance, borrower, interest_rate, id);
+
+            id
+
+        }
+
+        /// This method is for lenders to revoke a credit delegation.
+        /// 
+        /// The undrawn part is released back to the lender's account, the borrower cannot draw the delegation anymore. 
+        /// The drawn part is released when the borrower repay it.
+        /// ### Input: 
+        /// - account_proof: the lender's Account NFT proof.
+        /// - delegation_id: the delegation id.
+        pub fn revoke_delegation(&mut self, account_proof: Proof, delegation_id: u64) {
+
+            assert!(account_proof.resource_address() == self.account_nft, "Wrong resource");
+
+            let lender_id = account_proof.non_fungible::<Account>().id();
+
+            account_proof.drop();
+
+            let delegation = self.delegations.get_mut(&delegation_id).expect("The delegation doesn't exist.");
+
+            assert!(delegation.lender == lender_id, "This is not your delegation.");
+
+            assert!(!delegation.revoked, "The delegation has already been revoked.");
+
+            let release = delegation.allowance - delegation.drawn;
+
+            delegation.allowance = delegation.drawn;
+
+            delegation.revoked = true;
+
+            let remove = delegation.drawn == Decimal::ZERO;
+
+            let current = self.current_time();
+
+            self.release_interest(current);
+
+            let lender = self.lenders.get_mut(&lender_id).unwrap();
+
+            lender.delegated -= release;
+
+            let pool = match lender.tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
+
+            pool.settle(lender);
+
+            pool.lend(lender, release);
+
+            if remove {
+                self.delegations.remove(&delegation_id);
+            }
+
+            info!("You have revoked {} stable coins undrawn credit from the delegation no.{}", release, delegation_id);
+
+        }
+
+        /// This method is for the delegated borrower to take a loan from the credit delegation.
+        /// ### Input: 
+        /// - id_proof: the Identity SBT proof.
+        /// - credit_sbt: the Credit SBT proof.
+        /// - delegation_id: the delegation id.
+        /// - amount: the amount borrower wish to loan.
+        /// ### Output: 
+        /// borrower's loan by amount.
+        pub fn delegated_credit(&mut self, id_proof: Proof, credit_sbt: Proof, delegation_id: u64, amount: Decimal) -> Bucket {
+
+            assert!(amount > Decimal::zero(), "Wrong data provided!");
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Identity>().id(), id_proof.resource_address(), credit_sbt.non_fungible::<Credit>().id(), credit_sbt.resource_address());
+
+            let borrower = id_proof.non_fungible::<Identity>().id();
+
+            let business = matches!(id_proof.non_fungible::<Identity>().data().data.identity, IdentityType::Business | IdentityType::Organization);
+
+            let credit_id = credit_sbt.non_fungible::<Credit>().id();
+
+            let credit_data = credit_sbt.non_fungible::<Credit>().data().data;
+
+            id_proof.drop(); credit_sbt.drop();
+
+            assert!(!credit_data.defaulted, "Your credit is defaulted, please repay your loan first!");
+
+            let current = self.current_time();
+
+            self.assert_protocol_vault(amount);
+
+            self.check_outflow(LendingAction::Borrow, amount);
+
+            self.take_exposure(credit_id.clone(), CreditProduct::of(&credit_data.credit_type), business, amount, LoanSource::Delegation);
+
+            credit_service.record_borrow(self.controller_badge.create_proof(), ProtocolAction::Revolving, credit_id, amount);
+
+            let delegation = self.delegations.get_mut(&delegation_id).expect("The delegation doesn't exist.");
+
+            assert!(delegation.borrower == borrower, "This credit is not delegated to you.");
+
+            assert!(!delegation.defaulted, "The delegated loan has been written off, please repay your loan first!");
+
+            assert!(!delegation.revoked, "The delegation has been revoked.");
+
+            assert!(delegation.due_time == 0 || delegation.due_time > current, "Your delegated credit is overdue, please repay your loan first!");
+
+            assert!(delegation.allowance - delegation.drawn >= amount, "Out of credit, you have to repay your debt first!");
+
+            delegation.drawn += amount;
+
+            delegation.debt_interest += amount * delegation.interest_rate;
+
+            if delegation.due_time == 0 {
+                delegation.due_time = current + delegation.term
+            }
+
+            info!("You have taken a {} stable coins loan from the delegation no.{}. Your debt will be over due in {} (unix time)", amount, delegation_id, delegation.due_time);
+
+            self.vault.take(amount)
+
+        }
+
+        /// This method is for anyone to repay a delegated loan.
+        /// ### Input: 
+        /// - delegation_id: the delegation id.
+        /// - repayment: the repayment stablecoin bucket.
+        /// ### Output: 
+        /// Remainder of the stablecoin bucket.
+        /// 
+        /// The principal is repaid first, the interest will go only to the delegating lender. 
+        /// If the delegation has been revoked, the repaid principal is also released back to the lender's account.
+        /// 
+        /// If the delegated loan has been written off, the repayment is recovered to the delegating lender's account 
+        /// after the insurance reserve's payouts are refilled.
+        pub fn repay_delegated_credit(&mut self, delegation_id: u64, mut repayment: Bucket) -> Bucket {
+
+            assert!(repayment.resource_address() == self.vault.resource_address(), "Wrong resource.");
+
+            self.assert_active(LendingAction::Repay);
+
+            let delegation = self.delegations.get_mut(&delegation_id).expect("The delegation doesn't exist.");
+
+            let total_debt = delegation.drawn + delegation.debt_interest;
+
+            assert!(total_debt != Decimal::ZERO, "The delegation currently don't have any debt!");
+
+            let amount = if repayment.amount() >= total_debt { total_debt } else { repayment.amount() };
+
+            let (principal, interest) = if amount <= delegation.drawn {
+                (amount, Decimal::ZERO)
+            } else { (delegation.drawn, amount - delegation.drawn) };
+
+            delegation.drawn -= principal;
+
+            delegation.debt_interest -= interest;
+
+            if delegation.drawn + delegation.debt_interest == Decimal::ZERO {
+                delegation.due_time = 0;
+                info!("The delegation no.{} has been repaid in full.", delegation_id)
+            } else {
+                info!("You have repaid {} stable coins for the delegation no.{}.", amount, delegation_id)
+            }
+
+            let lender_id = delegation.lender.clone();
+
+            if delegation.defaulted {
+
+                if delegation.drawn + delegation.debt_interest == Decimal::ZERO {
+                    self.delegations.remove(&delegation_id);
+                }
+
+                let current = self.current_time();
+
+                self.recover_delegation(&lender_id, current, repayment.take(amount));
+
+                return repayment
+
+            }
+
+            let borrower = delegation.borrower.clone();
+
+            let release = if delegation.revoked { principal } else { Decimal::ZERO };
+
+            delegation.allowance -= release;
+
+            if delegation.revoked && delegation.drawn + delegation.debt_interest == Decimal::ZERO {
+                self.delegations.remove(&delegation_id);
+            }
+
+            self.vault.put(repayment.take(amount));
+
+            if principal > Decimal::ZERO {
+                let credit_service: GroundCredit = self.credit_service.into();
+                if let Some(credit_id) = credit_service.get_credit_id(borrower) {
+                    credit_service.record_repay(self.controller_badge.create_proof(), credit_id.clone(), principal);
+                    self.release_exposure(&credit_id, principal, LoanSource::Delegation);
+                }
+            }
+
+            let interest = self.take_reserve(interest);
+
+            let current = self.current_time();
+
+            self.release_interest(current);
+
+            let lender = self.lenders.get_mut(&lender_id).unwrap();
+
+            lender.delegated -= release;
+
+            let pool = match lender.tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
+
+            pool.settle(lender);
+
+            pool.lend(lender, interest + release);
+
+            self.total_return += interest;
+
+            self.fill_withdrawal_queue();
+
+            repayment
+
+        }
+
+        /// This method is for anyone to write off a delegated loan when the borrower is overdue past the protocol's default policy.
+        /// ### Input: 
+        /// - delegation_id: the delegation id.
+        /// ### Output: 
+        /// - The drawn amount is written off only from the delegating lender's account, the undrawn allowance is released back to the lender's account.
+        /// - The debt is kept on the delegation as defaulted, later repayments are recovered to the delegating lender's account.
+        /// - The borrower's credit score is degraded if the credit service allows the protocol to.
+        pub fn write_off_delegation(&mut self, delegation_id: u64) {
+
+            let current = self.current_time();
+
+            let delegation = self.delegations.get_mut(&delegation_id).expect("The delegation doesn't exist.");
+
+            assert!(!delegation.defaulted, "The delegated loan has already been written off.");
+
+            assert!(delegation.due_time != 0 && current >= delegation.due_time + self.default_policy.overdue_days * DAY, "The delegated loan cannot be written off yet.");
+
+            let loss = delegation.drawn;
+
+            let release = delegation.allowance - delegation.drawn;
+
+            let lender_id = delegation.lender.clone();
+
+            let borrower = delegation.borrower.clone();
+
+            delegation.allowance = delegation.drawn;
+            delegation.revoked = true;
+            delegation.defaulted = true;
+
+            let covered = self.cover_loss(loss);
+
+            self.release_interest(current);
+
+            let lender = self.lenders.get_mut(&lender_id).unwrap();
+
+            lender.delegated -= loss + release;
+
+            let pool = match lender.tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
+            pool.settle(lender);
+            pool.lend(lender, covered + release);
+
+            self.total_return -= loss - covered;
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            if let Some(credit_id) = credit_service.get_credit_id(borrower.clone()) {
+                credit_service.record_repay(self.controller_badge.create_proof(), credit_id.clone(), loss);
+                self.release_exposure(&credit_id, loss, LoanSource::Delegation);
+            }
+
+            if !self.may_degrade() {
+                info!("The credit service doesn't allow the protocol to degrade the borrower's credit score.");
+            } else if let Some(credit_id) = credit_service.get_credit_id(borrower.clone()) {
+
+                let resource_manager = borrow_resource_manager!(credit_service.credit_sbt_address());
+
+                let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+                let degrade_rate = credit_service.credit_scoring_rate().degrade_rate(&data.credit_type);
+
+                let credit_score = if data.credit_score >= degrade_rate {
+                    data.credit_score - degrade_rate
+                } else { Decimal::zero() };
+
+                credit_service.update_credit(self.controller_badge.create_proof(), credit_id, 
+                    CreditData {
+                        credit_score,
+                        ..data
+                    }
+                );
+
+            }
+
+            info!("The delegated loan no.{} of the user ID {} has been written off, the lender has lost {} stable coins.", delegation_id, borrower, loss - covered);
+
+        }
+
+        /// Recover the repayment of a written-off delegated loan to the delegating lender's account.
+        /// 
+        /// The insurance reserve's payouts are refilled first, 
+        /// the recovery is distributed to the lending tranches if the lender has closed the account.
+        fn recover_delegation(&mut self, lender_id: &NonFungibleId, current: u64, mut recovery: Bucket) {
+
+            let refill = if recovery.amount() > self.reserve_payouts { self.reserve_payouts } else { recovery.amount() };
+
+            if refill != Decimal::ZERO {
+                self.reserve_payouts -= refill;
+                self.reserve_vault.put(recovery.take(refill));
+            }
+
+            if !self.lenders.contains_key(lender_id) {
+                return self.recover(current, recovery)
+            }
+
+            let amount = recovery.amount();
+
+            self.vault.put(recovery);
+
+            self.release_interest(current);
+
+            let lender = self.lenders.get_mut(lender_id).unwrap();
+
+            let pool = match lender.tranche { Tranche::Senior => &mut self.senior, Tranche::Junior => &mut self.junior };
+
+            pool.settle(lender);
+
+            pool.lend(lender, amount);
+
+            self.total_return += amount;
+
+            self.fill_withdrawal_queue();
+
+            info!("{} stable coins have been recovered to the delegating lender's account.", amount);
+
+        }
+
+        /// Read only method to get a credit delegation data.
+        pub fn get_delegation(&self, delegation_id: u64) -> Delegation {
+            self.delegations.get(&delegation_id).expect("The delegation doesn't exist.").clone()
+        }
+
+        // The following method will not work...
+        // /// This method is for the permissioned borrower to take a loan from their revolving credit.
+        // /// ### Input: 
+        // /// - id_proof: the Identity SBT proof.
+        // /// - credit_sbt: the Credit SBT proof.
+        // /// - amount: the amount borrower wish to loan.
+        // /// ### Output: 
+        // /// borrower's loan by amount.
+        // pub fn revolving_credit(&mut self, id_proof: Proof, credit_sbt: Proof, amount: Decimal) -> Bucket {
+            
+        //     assert!(amount > Decimal::zero(), "Wrong data provided!");
+
+        //     let credit_service: GroundCredit = self.credit_service.into();
+
+        //     assert!(self.vault.amount() / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+
+        //     let credit_data = credit_sbt.non_fungible::<Credit>().data();
+
+        //     let (interest_rate, time) = match credit_data.credit_type {
+
+        //         CreditType::Revolving(revolving_types) => {
+        //             match revolving_types {
+        //                 RevolvingTypes::Monthly => {(self.interest_rates.monthly.interest_rate, MONTH)}
+
+        //                 RevolvingTypes::Yearly => {(self.interest_rates.yearly.interest_rate, YEAR)}
+        //             }
+        //         }
+
+        //         _ => {panic!("Wrong credit type!")}
+        //     };
+
+        //     let neuracle: NeuRacle = self.oracle.0.into();
+        //     let data_proof = self.oracle.1.create_proof();
+        //     let current = neuracle.get_data(data_proof);
+        //     let current: u64 = current.parse().unwrap();
+
+        //     let due_time = credit_data.due_time;
+
+        //     assert!(due_time == 0 || due_time > current, "Your credit is overdue, please repay your loan first!");
+
+        //     let (_, allowance) = credit_service.get_revolving_credit_amount(id_proof.clone(), credit_sbt.clone());
+            
+        //     assert!(allowance >= amount, "Your current credit allowance is not enough!");
+
+        //     let increase_debt_interest = amount * interest_rate;
+
+        //     credit_service.update_debt(credit_sbt.clone(), self.controller_badge.create_proof(), credit_data.current_debt + amount, credit_data.debt_interest + increase_debt_interest, Decimal::ZERO);
+
+        //     if due_time == 0 {
+        //         credit_service.update_debt_time(credit_sbt.clone(), self.controller_badge.create_proof(), current + time, current);
+                
+        //     } else {
+                
+        //     }
+
+        //     credit_sbt.drop(); id_proof.drop();
+
+        //     info!("You have taken a {} loan from your credit", amount);
+
+        //     self.vault.take(amount)
+            
+        // }
+
+        // /// This method is for the permissioned borrower to take their installment loan.
+        // /// ### Input: 
+        // /// - id_proof: the Identity SBT proof.
+        // /// - credit_sbt: the Credit SBT proof.
+        // /// - installment_credit_badge: the Installment Credit Badge.
+        // /// ### Output: 
+        // /// All the borrower's installment loan.
+        // pub fn installment_credit(&mut self, id_proof: Proof, credit_sbt: Proof, installment_credit_badge: Bucket) -> Bucket {
+
+        //     let credit_service: GroundCredit = self.credit_service.into();
+
+        //     assert!(self.vault.amount() / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+
+        //     let neuracle: NeuRacle = self.oracle.0.into();
+        //     let data_proof = self.oracle.1.create_proof();
+        //     let current = neuracle.get_data(data_proof);
+        //     let current: u64 = current.parse().unwrap();
+
+        //     let amount = credit_service.use_installment_credit(id_proof, credit_sbt, self.controller_badge.create_proof(), installment_credit_badge, current);
+
+        //     info!("You have taken a {} loan from your installment credit.", amount);
+
+        //     self.vault.take(amount)
+
+        // }
+
+        // /// This method is for the permissioned borrower to repay their loan.
+        // /// ### Input: 
+        // /// - id_proof: the Identity SBT proof.
+        // /// - credit_sbt: the Credit SBT proof.
+        // /// - repayment: the repayment stablecoin bucket.
+        // /// ### Output: 
+        // /// Remainder of borrower stablecoin bucket.
+        // /// 
+        // /// From this method, when the borrower repaid their debt in full, 
+        // /// the method will automatically increase lender's lending amount if they're eligible for the interest 
+        // /// (the borrow is made after lender's create the lending account).
+        // /// 
+        // /// Borrower can also make a period installment repayment in advance, their credit data will automatically updated through the method.
+        // pub fn repay(&mut self, id_proof: Proof, credit_proof: Proof, mut repayment: Bucket) -> Bucket {
+
+        //     assert!(repayment.resource_address() == self.vault.resource_address(), "Wrong resource.");
+
+        //     let credit_service: GroundCredit = self.credit_service.into();
+
+        //     credit_service.check_id_and_credit(id_proof.clone(), credit_proof.clone());
+
+        //     let credit_data = credit_proof.non_fungible::<Credit>().data();
+
+        //     match credit_data.credit_type {
+
+        //         CreditType::Installment(_) => {
+
+        //             let mut total_repaid = Decimal::ZERO;
+
+        //             let mut new_debt = Decimal::ZERO;
+
+        //             let mut new_debt_interest = Decimal::ZERO;
+                    
+        //             let mut new_extra_debt = Decimal::ZERO;
+
+        //             let mut amount = repayment.amount();
+
+        //             while new_debt > Decimal::ZERO {
+
+        //                 (new_debt, new_debt_interest, new_extra_debt) = self.get_total_debt(credit_proof.clone());
+
+        //                 if new_debt + new_debt_interest <= amount {
+
+        //                     self.vault.put(repayment.take(new_debt + new_debt_interest));
+
+        //                     amount -= new_debt + new_debt_interest;
+
+        //                     total_repaid += new_debt;
+
+        //                     let debt_start = credit_data.current_debt_start_time;
+
+        //                     let mut eligible_return = Decimal::ZERO;
+                            
+        //                     self.lenders.values().for_each(|lender| {
+        //                         if lender.start_time < debt_start {
+        //                             eligible_return += lender.lending_amount
+        //                         }
+        //                     });
+
+        //                     let interest = Decimal::ONE + new_debt_interest / eligible_return;
+
+        //                     self.lenders.values_mut().for_each(|lender| {
+        //                         if &lender.start_time < &debt_start {
+        //                             lender.lending_amount *= interest
+        //                         }
+        //                     });
+
+        //                     if new_extra_debt != Decimal::ZERO {
+
+        //                         if new_extra_debt <= amount {
 
         //                             self.deposit_fee(repayment.take(new_extra_debt));
         //                             new_extra_debt = Decimal::ZERO;
@@ -568,978 +3164,2219 @@ blueprint! {
         //                             new_debt = credit_service.update_installment_credit_data(credit_proof.clone(), self.controller_badge.create_proof(), true);
                                     
     
-        //                         } else {
+        //                         } else {
+    
+        //                             self.deposit_fee(repayment.take(amount));
+        //                             total_repaid += amount;
+        //                             new_extra_debt -= amount;
+        //                             break
+    
+        //                         }
+
+        //                     } else {
+        //                         new_debt = credit_service.update_installment_credit_data(credit_proof.clone(), self.controller_badge.create_proof(), false);
+        //                     }
+        
+        //                 } else {
+            
+        //                     self.vault.put(repayment.take(amount));
+        //                     total_repaid += amount;
+
+        //                     if amount <= new_debt {
+        //                         new_debt -= amount
+        //                     } else {
+        //                         let remain = amount - new_debt;
+        //                         new_debt = Decimal::ONE;
+        //                         new_debt_interest -= remain
+        //                     }
+
+        //                     break
+            
+        //                 };
+                        
+        //             }
+
+        //             if new_debt + new_debt_interest + new_extra_debt > Decimal::ZERO {
+        //                 credit_service.update_debt(credit_proof.clone(), self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
+        //                 info!("You have repaid {} token for the protocol.", total_repaid)
+        //             }
+        //         }
+
+        //         CreditType::Revolving(_) => {
+
+        //             let (current_debt, debt_interest, extra_debt) = self.get_total_debt(credit_proof.clone());
+
+        //             let mut amount = repayment.amount();
+
+        //             let (new_debt, new_debt_interest, new_extra_debt, total_repaid) = if current_debt + debt_interest <= amount {
+
+        //                 if current_debt + debt_interest != Decimal::ZERO {
+                            
+        //                     self.vault.put(repayment.take(current_debt + debt_interest));
+
+        //                     amount -= current_debt + debt_interest;
+
+        //                     let debt_start = credit_data.current_debt_start_time;
+
+        //                     let mut eligible_return = Decimal::ZERO;
+                            
+        //                     self.lenders.values().for_each(|lender| {
+        //                         if lender.start_time < debt_start {
+        //                             eligible_return += lender.lending_amount
+        //                         }
+        //                     });
+
+        //                     let interest = Decimal::ONE + debt_interest / eligible_return;
+
+        //                     self.lenders.values_mut().for_each(|lender| {
+        //                         if &lender.start_time < &debt_start {
+        //                             lender.lending_amount *= interest
+        //                         }
+        //                     });
+        //                 } 
+
+        //                 if extra_debt != Decimal::ZERO {
+
+        //                     if extra_debt <= amount {
+
+        //                         self.deposit_fee(repayment.take(extra_debt));
+        //                         (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, current_debt + debt_interest + extra_debt)
+    
+        //                     } else {
+    
+        //                         self.deposit_fee(repayment.take(amount));
+        //                         (Decimal::ZERO, Decimal::ZERO, extra_debt - amount, current_debt + debt_interest + amount)
+    
+        //                     }
+                            
+        //                 } else {
+        //                     credit_service.update_revolving_credit_repaid_accumulate(id_proof.clone(), credit_proof.clone(), self.controller_badge.create_proof(), current_debt + debt_interest);
+        //                     (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, current_debt + debt_interest)
+        //                 }
+    
+        //             } else {
+        
+        //                 self.vault.put(repayment.take(amount));
+
+        //                 if extra_debt == Decimal::ZERO {
+        //                     credit_service.update_revolving_credit_repaid_accumulate(id_proof.clone(), credit_proof.clone(), self.controller_badge.create_proof(), amount);
+        //                 }
+
+        //                 if amount <= current_debt {
+        //                     (current_debt - amount, debt_interest, extra_debt, amount)
+        //                 } else {
+        //                     let remain = amount - current_debt;
+        //                     (Decimal::ZERO, debt_interest - remain, extra_debt, amount)
+        //                 }
+        
+        //             };
+
+        //             credit_service.update_debt(credit_proof.clone(), self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
+
+        //             if new_debt + new_debt_interest + new_extra_debt == Decimal::ZERO {
+                        
+        //                 credit_service.update_debt_time(credit_proof.clone(), self.controller_badge.create_proof(), 0, 0);
+        //                 info!("You have repaid all your current debt.")
+
+        //             } else {
+
+        //                 info!("You have repaid {} token for the protocol.", total_repaid)
+
+        //             }
+        //         }
+        //     } 
+
+        //     id_proof.drop(); credit_proof.drop();
+
+        //     return repayment
+
+        // }
+
+        // /// This method is for the permissioned borrower to get their total debt.
+        // /// ### Input: 
+        // /// - credit_sbt: the Credit SBT proof.
+        // /// ### Output: 
+        // /// The borrower's initial debt, debt interest, and extra debt from late repayment.
+        // /// 
+        // /// From this method, if the protocol see the borrower are late on repayment for the first time, 
+        // /// the protocol will automatically degrade the borrower's credit score, 
+        // /// calculate the extra debt from late repayment and update borrower's debt data.
+        // pub fn get_total_debt(&self, credit_proof: Proof) -> (Decimal, Decimal, Decimal) {
+
+        //     let data = credit_proof.non_fungible::<Credit>().data();
+
+        //     let neuracle: NeuRacle = self.oracle.0.into();
+        //     let data_proof = self.oracle.1.create_proof();
+        //     let current = neuracle.get_data(data_proof);
+        //     let current: u64 = current.parse().expect("Wrong data!");
+
+        //     let due_time = data.due_time;
+
+        //     let current_debt = data.current_debt;
+
+        //     let mut extra_debt = data.extra_debt;
+            
+        //     let debt_interest = data.debt_interest;
+
+        //     assert!(current_debt + debt_interest + extra_debt != Decimal::ZERO, "You currently don't have any debt!");
+
+        //     if due_time <= current && extra_debt == Decimal::ZERO {
+
+        //         let credit_service: GroundCredit = self.credit_service.into();
+
+        //         credit_service.degrade_credit(credit_proof.clone(), self.controller_badge.create_proof());
+
+        //         extra_debt = match data.credit_type {
+
+        //             CreditType::Revolving(types) => {
+
+        //                 match types {
+
+        //                     RevolvingTypes::Monthly => {
+
+        //                         let number = (Decimal::from(current - due_time)  / Decimal::from(MONTH)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
+        //                         let rate = self.interest_rates.monthly.interest_rate_late;
+        //                         let mutiply = expo(rate, number);
+        //                         current_debt * (mutiply - Decimal::ONE)
+
+        //                     }
+
+        //                     RevolvingTypes::Yearly => {
+        //                         let number = (Decimal::from(current - due_time)  / Decimal::from(YEAR)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
+        //                         let rate = dec!("1") + self.interest_rates.yearly.interest_rate_late;
+        //                         let mutiply = expo(rate, number);
+        //                         current_debt * (mutiply - Decimal::ONE)
+        //                     }
+
+        //                 }
+        //             }
+
+        //             CreditType::Installment(data) => {
+
+        //                 let number = (Decimal::from(current - due_time) / Decimal::from(data.period_length)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
+        //                     let rate = data.interest_rate_late;
+        //                     let mutiply = expo(rate, number);
+        //                     current_debt * (mutiply - Decimal::ONE)
+
+        //             }
+        //         };
+
+        //         credit_service.update_debt(credit_proof.clone(), self.controller_badge.create_proof(), current_debt, debt_interest, extra_debt);
+
+        //     }
+
+        //     credit_proof.drop();
+
+        //     info!("Your current debt is {}.", current_debt + debt_interest + extra_debt);
+
+        //     (current_debt, debt_interest, extra_debt)
+        // }
+
+        /// This method is for the permissioned borrower to take a loan from their revolving credit.
+        /// ### Input: 
+        /// - id_proof: the Identity SBT proof.
+        /// - credit_sbt: the Credit SBT proof.
+        /// - amount: the amount borrower wish to loan.
+        /// ### Output: 
+        /// borrower's loan by amount.
+        pub fn revolving_credit(&mut self, id_proof: Proof, credit_sbt: Proof, amount: Decimal) -> Bucket {
+            
+            assert!(amount > Decimal::zero(), "Wrong data provided!");
+            
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            assert!(self.total_return != Decimal::ZERO, "Currently there's no resource on the protocol's vault, please come back later.");
+
+            assert!((self.vault.amount() - amount) / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+
+            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Identity>().id(), id_proof.resource_address(), credit_sbt.non_fungible::<Credit>().id(), credit_sbt.resource_address());
+
+            let id_data = id_proof.non_fungible::<Identity>().data().data;
+
+            let id = id_proof.non_fungible::<Identity>().id();
+
+            let credit_id = credit_sbt.non_fungible::<Credit>().id();
+
+            credit_sbt.drop(); id_proof.drop();
+
+            self.draw_revolving_credit(id, id_data, credit_id, amount, None)
+            
+        }
+
+        /// This method is for listed checkout components to take a revolving loan on behalf of the borrower 
+        /// and pay the merchant directly.
+        /// 
+        /// A workaround method for restrictive proof.
+        /// ### Input: 
+        /// - checkout_proof: the checkout component's controller badge proof.
+        /// - id: the borrower's Identity SBT ID.
+        /// - id_resource: the borrower's Identity SBT resource address.
+        /// - credit_id: the borrower's Credit SBT ID.
+        /// - credit_resource: the borrower's Credit SBT resource address.
+        /// - amount: the purchase amount.
+        /// ### Output: 
+        /// - The purchase payment.
+        /// - The interest rate booked on the purchase.
+        /// - The start time of the borrower's debt the purchase is booked on.
+        pub fn checkout_credit(&mut self, checkout_proof: Proof, id: NonFungibleId, id_resource: ResourceAddress, credit_id: NonFungibleId, credit_resource: ResourceAddress, amount: Decimal) -> (Bucket, Decimal, u64) {
+
+            self.check_checkout(checkout_proof);
+
+            assert!(amount > Decimal::zero(), "Wrong data provided!");
+            
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            assert!(self.total_return != Decimal::ZERO, "Currently there's no resource on the protocol's vault, please come back later.");
+
+            assert!((self.vault.amount() - amount) / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+
+            credit_service.check_id_and_credit_by_data(id.clone(), id_resource, credit_id.clone(), credit_resource);
+
+            let id_data = borrow_resource_manager!(id_resource).get_non_fungible_data::<Identity>(&id).data;
+
+            let resource_manager = borrow_resource_manager!(credit_resource);
+
+            let debt_interest = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data.debt_interest;
+
+            let payment = self.draw_revolving_credit(id, id_data, credit_id.clone(), amount, None);
+
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+            (payment, (data.debt_interest - debt_interest) / amount, data.current_debt_start_time)
+
+        }
+
+        /// This method is for listed checkout components to reverse a borrower's debt from a refunded purchase.
+        /// 
+        /// Only the purchase's own principal and the interest booked on it are reversed. 
+        /// If the debt the purchase was booked on has been repaid in full (or defaulted) since, nothing is reversed.
+        /// ### Input: 
+        /// - checkout_proof: the checkout component's controller badge proof.
+        /// - id: the borrower's Identity SBT ID.
+        /// - interest_rate: the interest rate booked on the purchase.
+        /// - debt_start: the start time of the debt the purchase was booked on.
+        /// - refund: the refund bucket.
+        /// ### Output: 
+        /// The remainder of the refund if the borrower has repaid part (or all) of the purchase.
+        pub fn refund_checkout_credit(&mut self, checkout_proof: Proof, id: NonFungibleId, interest_rate: Decimal, debt_start: u64, mut refund: Bucket) -> Bucket {
+
+            self.check_checkout(checkout_proof);
+
+            assert!(refund.resource_address() == self.vault.resource_address(), "Wrong resource.");
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            let credit_id = credit_service.get_credit_id(id.clone()).expect("The user doesn't have a credit SBT.");
+
+            let resource_manager = borrow_resource_manager!(credit_service.credit_sbt_address());
+
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+            if data.defaulted || data.current_debt_start_time != debt_start {
+                info!("The purchase's debt of the user ID {} has been settled, the refund will be kept for the user.", id);
+                return refund
+            }
+
+            let amount = if refund.amount() <= data.current_debt { refund.amount() } else { data.current_debt };
+
+            let interest = if amount * interest_rate <= data.debt_interest { amount * interest_rate } else { data.debt_interest };
+
+            let (current_debt, debt_interest) = (data.current_debt - amount, data.debt_interest - interest);
+
+            if let Some(rate) = self.borrow_rates.get(&credit_id) {
+                if current_debt > Decimal::ZERO {
+                    let snapshot_rate = (data.current_debt * rate - amount * interest_rate) / current_debt;
+                    self.borrow_rates.insert(credit_id.clone(), if snapshot_rate > Decimal::ZERO { snapshot_rate } else { Decimal::ZERO });
+                }
+            }
+
+            let (due_time, current_debt_start_time) = if current_debt + debt_interest + data.extra_debt == Decimal::ZERO {
+                (0, 0)
+            } else {
+                (data.due_time, data.current_debt_start_time)
+            };
+
+            credit_service.update_credit(self.controller_badge.create_proof(), credit_id.clone(), 
+                CreditData {
+                    current_debt,
+                    debt_interest,
+                    due_time,
+                    current_debt_start_time,
+                    ..data
+                }
+            );
+
+            self.vault.put(refund.take(amount));
+
+            self.fill_withdrawal_queue();
+
+            self.release_exposure(&credit_id, amount, LoanSource::Pool);
+
+            info!("The debt of the user ID {} has been reduced by {} stable coins from a refunded purchase.", id, amount + interest);
+
+            refund
+
+        }
+
+        /// Take a revolving loan from the borrower's credit, in the primary stablecoin or in a listed stablecoin market.
+        /// 
+        /// Business identities take the loan from the business credit underwritten by their owners.
+        fn draw_revolving_credit(&mut self, id: NonFungibleId, id_data: IdentityData, credit_id: NonFungibleId, amount: Decimal, asset: Option<ResourceAddress>) -> Bucket {
+
+            self.check_outflow(LendingAction::Borrow, if asset.is_none() { amount } else { Decimal::ZERO });
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            let current = self.current_time();
+
+            credit_service.recover_credit_score_by_data(self.controller_badge.create_proof(), id.clone());
+
+            let resource_manager = borrow_resource_manager!(credit_service.credit_sbt_address());
+
+            let credit_data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+            assert!(!credit_data.defaulted, "Your credit is defaulted, please repay your loan first!");
+
+            let interest_rates = match asset {
+                None => &self.interest_rates,
+                Some(asset) => &self.asset_markets.get(&asset).expect("The asset is not listed on the protocol.").interest_rates
+            };
+
+            let (interest_rate, time) = match credit_data.credit_type {
+
+                CreditType::Revolving(revolving_types) => {
+                    match revolving_types {
+                        RevolvingTypes::Monthly => {(interest_rates.monthly.interest_rate, MONTH)}
+
+                        RevolvingTypes::Yearly => {(interest_rates.yearly.interest_rate, YEAR)}
+                    }
+                }
+
+                _ => {panic!("Wrong credit type!")}
+            };
+
+            let (value, interest_rate) = match asset {
+                None => (amount, self.borrow_rate(interest_rate, amount)),
+                Some(asset) => (amount * self.read_peg(asset), interest_rate)
+            };
+
+            let due_time = credit_data.due_time;
+
+            assert!(due_time == 0 || due_time > current, "Your credit is overdue, please repay your loan first!");
+
+            if asset.is_none() {
+
+                let snapshot_rate = match self.borrow_rates.get(&credit_id) {
+                    Some(rate) if credit_data.current_debt > Decimal::ZERO => {
+                        (credit_data.current_debt * rate + amount * interest_rate) / (credit_data.current_debt + amount)
+                    }
+                    _ => interest_rate
+                };
+
+                self.borrow_rates.insert(credit_id.clone(), snapshot_rate);
+
+                if credit_data.current_debt <= self.asset_debt_value(&credit_id).0 || self.loan_checkpoints.get(&credit_id).is_none() {
+                    let checkpoint = self.loan_checkpoint(current);
+                    self.loan_checkpoints.insert(credit_id.clone(), checkpoint);
+                }
+            }
+
+            let business = matches!(id_data.identity, IdentityType::Business | IdentityType::Organization);
+
+            let (_, allowance) = match id_data.identity {
+                IdentityType::Business => {credit_service.get_business_credit_amount_by_data(id, id_data, credit_data)}
+                _ => {credit_service.get_revolving_credit_amount_by_data(id_data, credit_data)}
+            };
+            
+            assert!(allowance >= value, "Out of credit, you have to repay your debt first!");
+
+            self.take_exposure(credit_id.clone(), CreditProduct::of(&credit_data.credit_type), business, value, if asset.is_none() { LoanSource::Pool } else { LoanSource::Market });
+
+            let increase_debt_interest = value * interest_rate;
+
+            let (due_time, current_debt_start_time) = if due_time == 0 {
+                (current + time, current)
+            } else {
+                (due_time, credit_data.current_debt_start_time)
+            };
+
+            credit_service.update_credit(self.controller_badge.create_proof(), credit_id.clone(), 
+                CreditData {
+                    current_debt: credit_data.current_debt + value,
+                    debt_interest: credit_data.debt_interest + increase_debt_interest,
+                    extra_debt: Decimal::ZERO,
+                    due_time,
+                    current_debt_start_time,
+                    ..credit_data
+                }
+            );
+
+            info!("Your current debt is {}", credit_data.current_debt + value + credit_data.debt_interest + increase_debt_interest);
+
+            info!("Your debt will be over due in {} (unix time)", due_time);
+
+            info!("You have taken a {} stable coins loan from your credit at {}% interest rate", amount, interest_rate * dec!("100"));
+
+            match asset {
+                None => self.vault.take(amount),
+                Some(asset) => {
+
+                    let mut debts = self.asset_debts.get(&credit_id).unwrap_or(HashMap::new());
+
+                    let debt = debts.entry(asset).or_insert(AssetDebt { principal: Decimal::ZERO, interest: Decimal::ZERO, value: Decimal::ZERO, value_interest: Decimal::ZERO });
+
+                    debt.principal += amount;
+                    debt.interest += amount * interest_rate;
+                    debt.value += value;
+                    debt.value_interest += increase_debt_interest;
+
+                    self.asset_debts.insert(credit_id, debts);
+
+                    self.asset_markets.get_mut(&asset).unwrap().vault.take(amount)
+                }
+            }
+            
+        }
+
+        /// This method is for the permissioned borrower to take their installment loan.
+        /// ### Input: 
+        /// - id_proof: the Identity SBT proof.
+        /// - credit_sbt: the Credit SBT proof.
+        /// - installment_credit_badge: the Installment Credit Badge.
+        /// ### Output: 
+        /// All the borrower's installment loan.
+        pub fn installment_credit(&mut self, id_proof: Proof, credit_sbt: Proof, installment_credit_badge: Bucket) -> Bucket {
+
+            assert!(self.total_return != Decimal::ZERO, "Currently there's no resource on the protocol's vault, please come back later.");
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Identity>().id(), id_proof.resource_address(), credit_sbt.non_fungible::<Credit>().id(), credit_sbt.resource_address());
+
+            let current = self.current_time();
+
+            let credit_id = credit_sbt.non_fungible::<Credit>().id();
+
+            let business = matches!(id_proof.non_fungible::<Identity>().data().data.identity, IdentityType::Business | IdentityType::Organization);
+
+            let amount = self.use_installment_credit(id_proof, credit_sbt, self.controller_badge.create_proof(), installment_credit_badge, current);
+
+            self.check_outflow(LendingAction::Borrow, amount);
+
+            self.take_exposure(credit_id.clone(), CreditProduct::Installment, business, amount, LoanSource::Pool);
+
+            let checkpoint = self.loan_checkpoint(current);
+
+            self.loan_checkpoints.insert(credit_id.clone(), checkpoint);
+
+            assert!((self.vault.amount() - amount) / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+
+            info!("You have taken a {} stable coins loan from your installment credit.", amount);
+
+            self.vault.take(amount)
+
+        }
+
+        /// This method is for the permissioned borrower to repay their loan.
+        /// ### Input: 
+        /// - id_proof: the Identity SBT proof.
+        /// - credit_sbt: the Credit SBT proof.
+        /// - repayment: the repayment stablecoin bucket.
+        /// ### Output: 
+        /// Remainder of borrower stablecoin bucket.
+        /// 
+        /// From this method, the repaid interest is distributed to the lender cohorts closed before the loan was taken, by their capital×time over the loan's life. 
+        /// The borrower's loans in the stablecoin markets are not repaid through this method.
+        /// 
+        /// Borrower can also make a period installment repayment in advance, their credit data will automatically updated through the method.
+        pub fn repay(&mut self, mut id_proof: Proof, credit_proof: Proof, mut repayment: Bucket) -> Bucket {
+
+            assert!(repayment.resource_address() == self.vault.resource_address(), "Wrong resource.");
+
+            self.assert_active(LendingAction::Repay);
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Identity>().id(), id_proof.resource_address(), credit_proof.non_fungible::<Credit>().id(), credit_proof.resource_address());
+
+            let current = self.current_time();
+
+            if self.may_recover() {
+                credit_service.recover_credit_score_by_data(self.controller_badge.create_proof(), id_proof.non_fungible::<Identity>().id());
+            }
+
+            let credit_data = credit_proof.non_fungible::<Credit>().data().data;
+
+            let credit_id = credit_proof.non_fungible::<Credit>().id();
+
+            let mut principal_repaid = Decimal::ZERO;
+
+            let credit_proof = if credit_data.defaulted {
+
+                let (asset_value, asset_value_interest) = self.asset_debt_value(&credit_id);
+
+                let debt = if credit_data.current_debt > asset_value { credit_data.current_debt - asset_value } else { Decimal::ZERO };
+
+                let debt_interest = if credit_data.debt_interest > asset_value_interest { credit_data.debt_interest - asset_value_interest } else { Decimal::ZERO };
+
+                let total_debt = debt + debt_interest + credit_data.extra_debt;
+
+                let amount = if repayment.amount() >= total_debt { total_debt } else { repayment.amount() };
+
+                self.route_recovery(&credit_id, current, repayment.take(amount));
+
+                let (principal, interest) = self.split_repayment(amount, debt, debt_interest);
+
+                let current_debt = credit_data.current_debt - principal;
+
+                let debt_interest = credit_data.debt_interest - interest;
+
+                let extra_debt = credit_data.extra_debt - (amount - principal - interest);
+
+                principal_repaid = principal;
+
+                let credit = credit_proof.non_fungible::<Credit>();
+
+                if current_debt + debt_interest + extra_debt == Decimal::ZERO {
+
+                    let credit_type = match credit_data.credit_type {
+                        CreditType::Installment(_) => CreditType::Revolving(RevolvingTypes::Monthly),
+                        credit_type => credit_type
+                    };
+
+                    credit_service.update_credit(self.controller_badge.create_proof(), credit.id(), 
+                        CreditData {
+                            credit_type,
+                            current_debt_start_time: 0,
+                            current_debt,
+                            debt_interest,
+                            extra_debt,
+                            due_time: 0,
+                            defaulted: false,
+                            ..credit_data
+                        }
+                    );
+
+                    self.close_debt_claim(&credit_id);
+
+                    info!("You have repaid all your defaulted debt, your credit can be used again.")
+
+                } else {
+
+                    credit_service.update_credit(self.controller_badge.create_proof(), credit.id(), 
+                        CreditData {
+                            current_debt,
+                            debt_interest,
+                            extra_debt,
+                            ..credit_data
+                        }
+                    );
+
+                    info!("You have repaid {} stable coins of your defaulted debt.", amount)
+
+                }
+
+                credit_proof
+
+            } else { match credit_data.credit_type {
+
+                CreditType::Installment(_) => {
+
+                    let mut total_repaid = Decimal::ZERO;
+
+                    let mut loan_interest = Decimal::ZERO;
+
+                    let mut amount = repayment.amount();
+
+                    let (mut new_debt, mut new_debt_interest, mut new_extra_debt, mut credit_proof) = self.get_total_debt(credit_proof);
+
+                    while new_debt > Decimal::ZERO {
+
+                        if new_debt + new_debt_interest <= amount {
+
+                            amount -= new_debt + new_debt_interest;
+
+                            total_repaid += new_debt + new_debt_interest;
+
+                            principal_repaid += new_debt;
+
+                            self.vault.put(repayment.take(new_debt + new_debt_interest));
+
+                            loan_interest += new_debt_interest;
+
+                            if new_extra_debt != Decimal::ZERO {
+
+                                if new_extra_debt <= amount {
+
+                                    self.deposit_fee(repayment.take(new_extra_debt));
+                                    new_extra_debt = Decimal::ZERO;
+                                    amount -= new_extra_debt;
+                                    total_repaid += new_extra_debt;
+                                    (new_debt, new_debt_interest, credit_proof) = self.update_installment_credit_data(credit_proof, self.controller_badge.create_proof());
+                                    
+    
+                                } else {
     
-        //                             self.deposit_fee(repayment.take(amount));
-        //                             total_repaid += amount;
-        //                             new_extra_debt -= amount;
-        //                             break
+                                    self.deposit_fee(repayment.take(amount));
+                                    total_repaid += amount;
+                                    new_extra_debt -= amount;
+                                    break
     
-        //                         }
+                                }
 
-        //                     } else {
-        //                         new_debt = credit_service.update_installment_credit_data(credit_proof.clone(), self.controller_badge.create_proof(), false);
-        //                     }
+                            } else {
+                                (new_debt, new_debt_interest, credit_proof) = self.update_installment_credit_data(credit_proof, self.controller_badge.create_proof());
+                            }
         
-        //                 } else {
+                        } else {
             
-        //                     self.vault.put(repayment.take(amount));
-        //                     total_repaid += amount;
+                            self.vault.put(repayment.take(amount));
+                            total_repaid += amount;
+
+                            if amount <= new_debt {
+                                principal_repaid += amount;
+                                new_debt -= amount
+                            } else {
+                                let remain = amount - new_debt;
+                                principal_repaid += new_debt;
+                                new_debt = Decimal::ONE;
+                                new_debt_interest -= remain
+                            }
+
+                            break
+            
+                        };
+                        
+                    }
+
+                    self.distribute_loan_interest(current, &credit_id, loan_interest);
+
+                    if new_debt + new_debt_interest + new_extra_debt > Decimal::ZERO {
+                        credit_proof = self.update_debt(credit_proof, self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
+                        info!("You have repaid {} stable coins for the protocol.", total_repaid)
+                    };
+
+                    credit_proof
+                }
+
+                CreditType::Revolving(_) => {
+
+                    let (asset_value, asset_value_interest) = self.asset_debt_value(&credit_id);
+
+                    let debt = if credit_data.current_debt > asset_value { credit_data.current_debt - asset_value } else { Decimal::ZERO };
+
+                    let debt_interest = if credit_data.debt_interest > asset_value_interest { credit_data.debt_interest - asset_value_interest } else { Decimal::ZERO };
+
+                    let amount = repayment.amount();
+
+                    let (principal, interest) = self.split_repayment(amount, debt, debt_interest);
+
+                    self.vault.put(repayment.take(principal + interest));
+
+                    self.distribute_loan_interest(current, &credit_id, interest);
+
+                    let (extra_paid, new_id_proof, credit_proof) = self.settle_revolving_repayment(id_proof, credit_proof, principal, interest, amount - principal - interest);
+
+                    id_proof = new_id_proof;
+
+                    if extra_paid != Decimal::ZERO {
+                        self.deposit_fee(repayment.take(extra_paid));
+                    }
+
+                    principal_repaid = principal;
+
+                    credit_proof
+                }
+            }};
+
+            if principal_repaid > Decimal::ZERO && !credit_data.defaulted {
+                self.release_exposure(&credit_id, principal_repaid, LoanSource::Pool);
+            }
+
+            id_proof.drop(); credit_proof.drop();
+
+            self.fill_withdrawal_queue();
+
+            return repayment
+
+        }
+
+        /// Split a revolving credit repayment on a loan, the principal is repaid first, then the interest.
+        /// 
+        /// Output: (repaid principal, repaid interest)
+        fn split_repayment(&self, amount: Decimal, principal: Decimal, interest: Decimal) -> (Decimal, Decimal) {
+            let repaid_principal = if amount <= principal { amount } else { principal };
+            let repaid_interest = if amount - repaid_principal <= interest { amount - repaid_principal } else { interest };
+            (repaid_principal, repaid_interest)
+        }
+
+        /// The borrower's loans in the stablecoin markets counted on the credit SBT.
+        /// 
+        /// Output: (unpaid principal, unpaid interest) (primary stablecoin unit)
+        fn asset_debt_value(&self, credit_id: &NonFungibleId) -> (Decimal, Decimal) {
+            self.asset_debts.get(credit_id).map_or((Decimal::ZERO, Decimal::ZERO), |debts| {
+                debts.values().fold((Decimal::ZERO, Decimal::ZERO), |(value, value_interest), debt| (value + debt.value, value_interest + debt.value_interest))
+            })
+        }
+
+        /// Settle a revolving credit repayment on the borrower's credit SBT, 
+        /// shared by the repayments in the protocol's primary stablecoin and in the stablecoin markets.
+        /// 
+        /// The late repayment penalty is applied first, the extra debt is paid from the repayment's surplus after the repaid loan is cleared 
+        /// and the credit service counts the repayment to restore the borrower's credit score if the borrower is not late.
+        /// ### Input: 
+        /// - id_proof: the Identity SBT proof.
+        /// - credit_proof: the Credit SBT proof.
+        /// - principal: the repaid principal. (primary stablecoin unit)
+        /// - interest: the repaid interest. (primary stablecoin unit)
+        /// - surplus: the repayment's surplus after the repaid loan is cleared. (primary stablecoin unit)
+        /// ### Output: 
+        /// (the paid extra debt, the Identity SBT proof, the Credit SBT proof)
+        fn settle_revolving_repayment(&mut self, mut id_proof: Proof, credit_proof: Proof, principal: Decimal, interest: Decimal, surplus: Decimal) -> (Decimal, Proof, Proof) {
+
+            let (current_debt, debt_interest, extra_debt, mut credit_proof) = self.get_total_debt(credit_proof);
+
+            let extra_paid = if extra_debt <= surplus { extra_debt } else { surplus };
+
+            let new_debt = if current_debt > principal { current_debt - principal } else { Decimal::ZERO };
+
+            let new_debt_interest = if debt_interest > interest { debt_interest - interest } else { Decimal::ZERO };
+
+            let new_extra_debt = extra_debt - extra_paid;
+
+            credit_proof = self.update_debt(credit_proof, self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
+
+            if extra_debt == Decimal::ZERO {
+                (id_proof, credit_proof) = self.update_revolving_credit_repaid_accumulate(id_proof, credit_proof, self.controller_badge.create_proof());
+            }
+
+            if new_debt + new_debt_interest + new_extra_debt == Decimal::ZERO {
+
+                credit_proof = self.update_debt_time(credit_proof, self.controller_badge.create_proof(), 0, 0);
+                info!("You have repaid all your current debt.")
+
+            } else {
+
+                info!("You have repaid {} stable coins for the protocol.", principal + interest + extra_paid)
+
+            }
+
+            (extra_paid, id_proof, credit_proof)
+
+        }
+
+        /// This method is for the permissioned borrower to get their total debt.
+        /// ### Input: 
+        /// - credit_sbt: the Credit SBT proof.
+        /// ### Output: 
+        /// The borrower's initial debt, debt interest, and extra debt from late repayment.
+        /// 
+        /// From this method, if the protocol see the borrower are late on repayment for the first time, 
+        /// the protocol will automatically degrade the borrower's credit score, 
+        /// calculate the extra debt from late repayment and update borrower's debt data.
+        pub fn get_total_debt(&self, mut credit_proof: Proof) -> (Decimal, Decimal, Decimal, Proof) {
+
+            let data = credit_proof.non_fungible::<Credit>().data().data;
+
+            let current = self.current_time();
+
+            let due_time = data.due_time;
+
+            let current_debt = data.current_debt;
+
+            let mut extra_debt = data.extra_debt;
+            
+            let debt_interest = data.debt_interest;
+
+            assert!(current_debt + debt_interest + extra_debt != Decimal::ZERO, "You currently don't have any debt!");
 
-        //                     if amount <= new_debt {
-        //                         new_debt -= amount
-        //                     } else {
-        //                         let remain = amount - new_debt;
-        //                         new_debt = Decimal::ONE;
-        //                         new_debt_interest -= remain
-        //                     }
+            if due_time <= current && extra_debt == Decimal::ZERO && !data.defaulted {
 
-        //                     break
+                if self.may_degrade() {
+                    credit_proof = self.degrade_credit(credit_proof, self.controller_badge.create_proof());
+                }
+
+                if self.may_lend(&data.credit_type) {
+                    extra_debt = self.late_penalty(&data, current);
+                    credit_proof = self.update_debt(credit_proof, self.controller_badge.create_proof(), current_debt, debt_interest, extra_debt);
+                } else {
+                    info!("The credit service only allows the protocol to lower the borrower's debt, the late repayment penalty is not applied.");
+                }
+
+            } else {
+                info!("Your current debt is {}.", current_debt + debt_interest + extra_debt);
+            }
             
-        //                 };
-                        
-        //             }
+            (current_debt, debt_interest, extra_debt, credit_proof)
+        }
 
-        //             if new_debt + new_debt_interest + new_extra_debt > Decimal::ZERO {
-        //                 credit_service.update_debt(credit_proof.clone(), self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
-        //                 info!("You have repaid {} token for the protocol.", total_repaid)
-        //             }
-        //         }
+        /// Read only method to get the borrower's credit health on this protocol.
+        /// 
+        /// The method won't panic on any credit state, so wallets and dashboards can display the data without building and failing transactions.
+        /// ### Input: 
+        /// - id: the borrower's Identity SBT ID.
+        /// - id_resource: the borrower's Identity SBT resource address.
+        /// ### Output: 
+        /// The borrower's credit health, None if the Identity SBT is not issued by the credit service's identity service, 
+        /// the borrower doesn't have a credit SBT on the credit service or the time can't be read.
+        pub fn credit_health(&self, id: NonFungibleId, id_resource: ResourceAddress) -> Option<CreditHealth> {
 
-        //         CreditType::Revolving(_) => {
+            let credit_service: GroundCredit = self.credit_service.into();
 
-        //             let (current_debt, debt_interest, extra_debt) = self.get_total_debt(credit_proof.clone());
+            if id_resource != credit_service.identity_sbt_address() || !borrow_resource_manager!(id_resource).non_fungible_exists(&id) {
+                return None
+            }
 
-        //             let mut amount = repayment.amount();
+            let credit_id = match credit_service.get_credit_id(id.clone()) {
+                Some(credit_id) => credit_id,
+                None => return None
+            };
 
-        //             let (new_debt, new_debt_interest, new_extra_debt, total_repaid) = if current_debt + debt_interest <= amount {
+            let current = match self.read_time() {
+                Some(current) => current,
+                None => return None
+            };
 
-        //                 if current_debt + debt_interest != Decimal::ZERO {
-                            
-        //                     self.vault.put(repayment.take(current_debt + debt_interest));
+            let id_data = borrow_resource_manager!(id_resource).get_non_fungible_data::<Identity>(&id).data;
 
-        //                     amount -= current_debt + debt_interest;
+            let data = borrow_resource_manager!(credit_service.credit_sbt_address()).get_non_fungible_data::<Credit>(&credit_id).data;
 
-        //                     let debt_start = credit_data.current_debt_start_time;
+            let limit = credit_service.get_credit_limit_by_data(id.clone(), id_data, data);
 
-        //                     let mut eligible_return = Decimal::ZERO;
-                            
-        //                     self.lenders.values().for_each(|lender| {
-        //                         if lender.start_time < debt_start {
-        //                             eligible_return += lender.lending_amount
-        //                         }
-        //                     });
+            let debt = data.current_debt + data.debt_interest;
 
-        //                     let interest = Decimal::ONE + debt_interest / eligible_return;
+            let utilization = if limit == Decimal::ZERO {
+                if debt == Decimal::ZERO { Decimal::ZERO } else { dec!("100") }
+            } else {
+                debt / limit * dec!("100")
+            };
 
-        //                     self.lenders.values_mut().for_each(|lender| {
-        //                         if &lender.start_time < &debt_start {
-        //                             lender.lending_amount *= interest
-        //                         }
-        //                     });
-        //                 } 
+            let late = data.due_time != 0 && data.due_time <= current;
 
-        //                 if extra_debt != Decimal::ZERO {
+            let days_to_due = if data.due_time == 0 || late { 0 } else { (data.due_time - current) / DAY };
 
-        //                     if extra_debt <= amount {
+            let blacklisted = match credit_service.get_blacklist_entry(id) {
+                Some(entry) => entry.active && entry.expiry.map_or(true, |expiry| expiry > current),
+                None => false
+            };
 
-        //                         self.deposit_fee(repayment.take(extra_debt));
-        //                         (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, current_debt + debt_interest + extra_debt)
-    
-        //                     } else {
-    
-        //                         self.deposit_fee(repayment.take(amount));
-        //                         (Decimal::ZERO, Decimal::ZERO, extra_debt - amount, current_debt + debt_interest + amount)
-    
-        //                     }
-                            
-        //                 } else {
-        //                     credit_service.update_revolving_credit_repaid_accumulate(id_proof.clone(), credit_proof.clone(), self.controller_badge.create_proof(), current_debt + debt_interest);
-        //                     (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, current_debt + debt_interest)
-        //                 }
-    
-        //             } else {
-        
-        //                 self.vault.put(repayment.take(amount));
+            let projected_late_penalty = if data.due_time == 0 || data.defaulted {
+                Decimal::ZERO
+            } else if data.extra_debt != Decimal::ZERO {
+                data.extra_debt
+            } else if late {
+                self.late_penalty(&data, current)
+            } else {
+                self.late_penalty(&data, data.due_time + 1)
+            };
 
-        //                 if extra_debt == Decimal::ZERO {
-        //                     credit_service.update_revolving_credit_repaid_accumulate(id_proof.clone(), credit_proof.clone(), self.controller_badge.create_proof(), amount);
-        //                 }
+            Some(CreditHealth {
+                limit,
+                drawn: data.current_debt,
+                debt_interest: data.debt_interest,
+                extra_debt: data.extra_debt,
+                utilization,
+                days_to_due,
+                late,
+                blacklisted,
+                defaulted: data.defaulted,
+                projected_late_penalty
+            })
 
-        //                 if amount <= current_debt {
-        //                     (current_debt - amount, debt_interest, extra_debt, amount)
-        //                 } else {
-        //                     let remain = amount - current_debt;
-        //                     (Decimal::ZERO, debt_interest - remain, extra_debt, amount)
-        //                 }
-        
-        //             };
+        }
 
-        //             credit_service.update_debt(credit_proof.clone(), self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
+        /// Read only method to get the Identity SBT resource address of the protocol's credit service.
+        pub fn identity_sbt_address(&self) -> ResourceAddress {
+            let credit_service: GroundCredit = self.credit_service.into();
+            credit_service.identity_sbt_address()
+        }
 
-        //             if new_debt + new_debt_interest + new_extra_debt == Decimal::ZERO {
-                        
-        //                 credit_service.update_debt_time(credit_proof.clone(), self.controller_badge.create_proof(), 0, 0);
-        //                 info!("You have repaid all your current debt.")
+        /// Read only method to get how much a borrower can still take from the protocol's pool.
+        /// 
+        /// If the protocol doesn't limit the exposure, the borrower and credit product headroom are counted on the whole pool.
+        /// ### Input: 
+        /// - id: the borrower's Identity SBT ID.
+        /// - id_resource: the borrower's Identity SBT resource address.
+        /// ### Output: 
+        /// The borrower's exposure headroom, None if the Identity SBT is not issued by the credit service's identity service 
+        /// or the borrower doesn't have a credit SBT on the credit service.
+        pub fn exposure_headroom(&self, id: NonFungibleId, id_resource: ResourceAddress) -> Option<ExposureHeadroom> {
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            if id_resource != credit_service.identity_sbt_address() || !borrow_resource_manager!(id_resource).non_fungible_exists(&id) {
+                return None
+            }
+
+            let credit_id = match credit_service.get_credit_id(id.clone()) {
+                Some(credit_id) => credit_id,
+                None => return None
+            };
+
+            let id_data = borrow_resource_manager!(id_resource).get_non_fungible_data::<Identity>(&id).data;
+
+            let data = borrow_resource_manager!(credit_service.credit_sbt_address()).get_non_fungible_data::<Credit>(&credit_id).data;
+
+            let product = match self.exposures.get(&credit_id) {
+                Some(exposure) => exposure.product,
+                None => CreditProduct::of(&data.credit_type)
+            };
+
+            let business = matches!(id_data.identity, IdentityType::Business | IdentityType::Organization);
+
+            let (borrower, product, business_headroom) = self.headroom_of(&credit_id, product);
+
+            let business = if business { Some(business_headroom) } else { None };
+
+            let tolerance = self.total_return * self.tolerance_threshold;
+
+            let pool = if self.vault.amount() > tolerance { self.vault.amount() - tolerance } else { Decimal::ZERO };
+
+            let limit = credit_service.get_credit_limit_by_data(id, id_data, data);
+
+            let allowance = if limit > data.current_debt + data.debt_interest { limit - data.current_debt - data.debt_interest } else { Decimal::ZERO };
+
+            let available = [borrower, product, business.unwrap_or(pool), pool].iter().fold(allowance, |min, headroom| if *headroom < min { *headroom } else { min });
+
+            Some(ExposureHeadroom {
+                borrower,
+                product,
+                business,
+                pool,
+                available
+            })
+
+        }
+
+        /// Get the borrower, credit product and business exposure headroom on the protocol's pool.
+        fn headroom_of(&self, credit_id: &NonFungibleId, product: CreditProduct) -> (Decimal, Decimal, Decimal) {
+
+            let (borrower_share, product_share, business_share) = match &self.exposure_limits {
+                Some(limits) => (limits.max_borrower_share, limits.max_product_share, limits.max_business_share),
+                None => (Decimal::ONE, Decimal::ONE, Decimal::ONE)
+            };
+
+            let headroom = |share: Decimal, exposure: Decimal| {
+                let limit = self.total_return * share;
+                if limit > exposure { limit - exposure } else { Decimal::ZERO }
+            };
+
+            let borrower = self.exposures.get(credit_id).map_or(Decimal::ZERO, |exposure| exposure.amount);
+
+            let product = self.product_exposures.get(&product).copied().unwrap_or(Decimal::ZERO);
+
+            (headroom(borrower_share, borrower), headroom(product_share, product), headroom(business_share, self.business_exposure))
+
+        }
+
+        /// Check the protocol's exposure limits and record the borrower's new exposure from the loan source.
+        fn take_exposure(&mut self, credit_id: NonFungibleId, product: CreditProduct, business: bool, amount: Decimal, source: LoanSource) {
+
+            let product = self.exposures.get(&credit_id).map_or(product, |exposure| exposure.product);
+
+            if self.exposure_limits.is_some() {
+
+                let (borrower, product_headroom, business_headroom) = self.headroom_of(&credit_id, product);
+
+                assert!(amount <= borrower, "The loan is over the protocol's borrower exposure limit, you can only take {} more stable coins.", borrower);
+
+                assert!(amount <= product_headroom, "The loan is over the protocol's {:?} credit exposure limit, only {} more stable coins can be taken.", product, product_headroom);
+
+                assert!(!business || amount <= business_headroom, "The loan is over the protocol's business exposure limit, only {} more stable coins can be taken.", business_headroom);
+
+            }
+
+            self.record_exposure(credit_id, product, business, amount, source)
+
+        }
+
+        /// Record the borrower's new exposure from the loan source without checking the protocol's exposure limits.
+        fn record_exposure(&mut self, credit_id: NonFungibleId, product: CreditProduct, business: bool, amount: Decimal, source: LoanSource) {
+
+            let product = self.exposures.get(&credit_id).map_or(product, |exposure| exposure.product);
+
+            let exposure = self.exposures.entry(credit_id).or_insert(Exposure { product, business, amount: Decimal::ZERO, asset: Decimal::ZERO, delegated: Decimal::ZERO });
+
+            exposure.amount += amount;
+
+            match source {
+                LoanSource::Pool => {}
+                LoanSource::Market => exposure.asset += amount,
+                LoanSource::Delegation => exposure.delegated += amount
+            }
+
+            *self.product_exposures.entry(product).or_insert(Decimal::ZERO) += amount;
+
+            if business {
+                self.business_exposure += amount
+            }
+
+        }
+
+        /// Release the borrower's exposure from the loan source by the repaid (or written off) principal.
+        /// 
+        /// Return the released amount, capped at the borrower's exposure from the loan source.
+        fn release_exposure(&mut self, credit_id: &NonFungibleId, amount: Decimal, source: LoanSource) -> Decimal {
+
+            let (product, business, amount, empty) = match self.exposures.get_mut(credit_id) {
+                Some(exposure) => {
+                    let part = match source {
+                        LoanSource::Pool => exposure.amount - exposure.asset - exposure.delegated,
+                        LoanSource::Market => exposure.asset,
+                        LoanSource::Delegation => exposure.delegated
+                    };
+                    let amount = if amount < part { amount } else { part };
+                    exposure.amount -= amount;
+                    match source {
+                        LoanSource::Pool => {}
+                        LoanSource::Market => exposure.asset -= amount,
+                        LoanSource::Delegation => exposure.delegated -= amount
+                    }
+                    (exposure.product, exposure.business, amount, exposure.amount == Decimal::ZERO)
+                }
+                None => return Decimal::ZERO
+            };
+
+            *self.product_exposures.get_mut(&product).unwrap() -= amount;
+
+            if business {
+                self.business_exposure -= amount
+            }
+
+            if empty {
+                self.exposures.remove(credit_id);
+            }
+
+            amount
+
+        }
+
+        /// Move the borrower's pool exposure of the principal charged on the guarantors to the guarantors' credit, 
+        /// the guarantors repay the charged debt to the protocol's pool.
+        /// 
+        /// The moved exposure isn't checked against the protocol's exposure limits since the guarantors didn't take a new loan.
+        fn move_exposure_to_guarantors(&mut self, credit_id: &NonFungibleId, charges: Vec<(NonFungibleId, Decimal)>) {
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            let id_manager = borrow_resource_manager!(credit_service.identity_sbt_address());
+
+            let credit_manager = borrow_resource_manager!(credit_service.credit_sbt_address());
+
+            for (guarantor, principal) in charges {
+
+                let amount = self.release_exposure(credit_id, principal, LoanSource::Pool);
+
+                if amount == Decimal::ZERO { continue }
+
+                let guarantor_credit_id = credit_service.get_credit_id(guarantor.clone()).unwrap();
+
+                let credit_type = credit_manager.get_non_fungible_data::<Credit>(&guarantor_credit_id).data.credit_type;
+
+                let business = matches!(id_manager.get_non_fungible_data::<Identity>(&guarantor).data.identity, IdentityType::Business | IdentityType::Organization);
+
+                self.record_exposure(guarantor_credit_id, CreditProduct::of(&credit_type), business, amount, LoanSource::Pool);
+
+            }
+
+        }
+
+        /// Check if the credit service allows this protocol to degrade users' credit score.
+        fn may_degrade(&self) -> bool {
+            let credit_service: GroundCredit = self.credit_service.into();
+            match credit_service.get_protocol_permission(self.controller_badge.resource_address()) {
+                None => false,
+                Some(permission) => permission.capabilities.may_degrade
+            }
+        }
+
+        /// Check if the credit service allows this protocol to apply the time-based credit score recovery, winding down protocols cannot.
+        fn may_recover(&self) -> bool {
+            let credit_service: GroundCredit = self.credit_service.into();
+            match credit_service.get_protocol_permission(self.controller_badge.resource_address()) {
+                None => false,
+                Some(permission) => permission.status == ProtocolStatus::Active
+            }
+        }
+
+        /// Check if the credit service allows this protocol to raise the debt of a credit type, winding down protocols can only lower users' debt.
+        fn may_lend(&self, credit_type: &CreditType) -> bool {
+            let credit_service: GroundCredit = self.credit_service.into();
+            match credit_service.get_protocol_permission(self.controller_badge.resource_address()) {
+                None => false,
+                Some(permission) => permission.status == ProtocolStatus::Active && match credit_type {
+                    CreditType::Revolving(_) => permission.capabilities.revolving,
+                    CreditType::Installment(_) => permission.capabilities.installment
+                }
+            }
+        }
+
+        /// Calculate the late repayment penalty of a credit at the current time.
+        /// 
+        /// The penalty count at most 255 late periods and stop compounding at [MAX_LATE_MULTIPLIER] times the debt.
+        fn late_penalty(&self, data: &CreditData, current: u64) -> Decimal {
+
+            let due_time = data.due_time;
+
+            let current_debt = data.current_debt;
+
+            match data.credit_type {
+
+                CreditType::Revolving(types) => {
+
+                    match types {
+
+                        RevolvingTypes::Monthly => {
+
+                            let number = started_periods(current - due_time, MONTH);
+                            let rate = self.interest_rates.monthly.interest_rate_late;
+                            let mutiply = expo_capped(rate, number, Decimal::from(MAX_LATE_MULTIPLIER));
+                            current_debt * (mutiply - Decimal::ONE)
+
+                        }
+
+                        RevolvingTypes::Yearly => {
+                            let number = started_periods(current - due_time, YEAR);
+                            let rate = dec!("1") + self.interest_rates.yearly.interest_rate_late;
+                            let mutiply = expo_capped(rate, number, Decimal::from(MAX_LATE_MULTIPLIER));
+                            current_debt * (mutiply - Decimal::ONE)
+                        }
+
+                    }
+                }
+
+                CreditType::Installment(data) => {
+
+                    let number = started_periods(current - due_time, data.period_length);
+                        let rate = data.interest_rate_late;
+                        let mutiply = expo_capped(rate, number, Decimal::from(MAX_LATE_MULTIPLIER));
+                        current_debt * (mutiply - Decimal::ONE)
+
+                }
+            }
+        }
+
+        /// This method is for anyone to call the guarantees of an overdue borrower.
+        /// ### Input: 
+        /// - id: the borrower's Identity SBT ID.
+        /// ### Output: 
+        /// The borrower's unpaid debt is charged on the guarantors' credit (capped at the total pledged amount) 
+        /// and the borrower's debt is reduced by the same amount.
+        /// 
+        /// The guarantors will have to repay the charged debt through this protocol.
+        pub fn call_guarantee(&mut self, id: NonFungibleId) {
+
+            let credit_service: GroundCredit = self.credit_service.into();
+
+            let credit_id = credit_service.get_credit_id(id.clone()).expect("The user doesn't have a credit SBT.");
+
+            let resource_manager = borrow_resource_manager!(credit_service.credit_sbt_address());
+
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+            let current = self.current_time();
+
+            assert!(data.due_time != 0 && data.due_time <= current, "The borrower is not overdue on repayment.");
+
+            let (principal, interest, charges) = credit_service.call_guarantees(self.controller_badge.create_proof(), id.clone(), data.current_debt, data.debt_interest, current);
+
+            assert!(principal + interest != Decimal::ZERO, "The borrower doesn't have any guarantee to call.");
+
+            self.move_exposure_to_guarantors(&credit_id, charges);
+
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
+
+            let (current_debt, debt_interest) = (data.current_debt - principal, data.debt_interest - interest);
+
+            let (due_time, current_debt_start_time) = if current_debt + debt_interest + data.extra_debt == Decimal::ZERO {
+                (0, 0)
+            } else {
+                (data.due_time, data.current_debt_start_time)
+            };
+
+            credit_service.update_credit(self.controller_badge.create_proof(), credit_id, 
+                CreditData {
+                    current_debt,
+                    debt_interest,
+                    due_time,
+                    current_debt_start_time,
+                    ..data
+                }
+            );
+
+            info!("The guarantors have taken over {} stable coins of the user ID {}'s debt.", principal + interest, id);
+
+        }
+
+        /// This method is for anyone to declare a default on a loan taken from this protocol when the borrower is overdue past the protocol's default policy.
+        /// ### Input: 
+        /// - id: the borrower's Identity SBT ID.
+        /// ### Output: 
+        /// - The borrower's guarantees are called first.
+        /// - The remaining principal is written off against the lender cohorts closed before the loan was made, the junior tranche absorbs the loss first.
+        /// - The borrower's Credit SBT is flagged as defaulted, later repayments will be distributed to the lenders as recovery.
+        /// - If the default policy allowed, the protocol will automatically take the compensation from the DAO treasury to cover part of the written-off principal, the compensation is repaid to the DAO from later recoveries.
+        pub fn declare_default(&mut self, id: NonFungibleId) {
 
-        //             } else {
+            let credit_service: GroundCredit = self.credit_service.into();
 
-        //                 info!("You have repaid {} token for the protocol.", total_repaid)
+            let credit_id = credit_service.get_credit_id(id.clone()).expect("The user doesn't have a credit SBT.");
 
-        //             }
-        //         }
-        //     } 
+            let resource_manager = borrow_resource_manager!(credit_service.credit_sbt_address());
 
-        //     id_proof.drop(); credit_proof.drop();
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
 
-        //     return repayment
+            let current = self.current_time();
 
-        // }
+            assert!(!data.defaulted, "The loan has already been declared defaulted.");
 
-        // /// This method is for the permissioned borrower to get their total debt.
-        // /// ### Input: 
-        // /// - credit_sbt: the Credit SBT proof.
-        // /// ### Output: 
-        // /// The borrower's initial debt, debt interest, and extra debt from late repayment.
-        // /// 
-        // /// From this method, if the protocol see the borrower are late on repayment for the first time, 
-        // /// the protocol will automatically degrade the borrower's credit score, 
-        // /// calculate the extra debt from late repayment and update borrower's debt data.
-        // pub fn get_total_debt(&self, credit_proof: Proof) -> (Decimal, Decimal, Decimal) {
+            assert!(self.exposures.contains_key(&credit_id), "The loan wasn't taken from this protocol.");
 
-        //     let data = credit_proof.non_fungible::<Credit>().data();
+            assert!(data.due_time != 0 && data.due_time <= current, "The borrower is not overdue on repayment.");
 
-        //     let neuracle: NeuRacle = self.oracle.0.into();
-        //     let data_proof = self.oracle.1.create_proof();
-        //     let current = neuracle.get_data(data_proof);
-        //     let current: u64 = current.parse().expect("Wrong data!");
+            let (period, installment_remain) = match data.credit_type {
+                CreditType::Revolving(RevolvingTypes::Monthly) => {(MONTH, Decimal::ZERO)}
+                CreditType::Revolving(RevolvingTypes::Yearly) => {(YEAR, Decimal::ZERO)}
+                CreditType::Installment(installment_data) => {
+                    let remain_periods = if installment_data.period_max > installment_data.period_counter {
+                        installment_data.period_max - installment_data.period_counter
+                    } else { 0 };
+                    (installment_data.period_length, installment_data.total_loan / installment_data.period_max * remain_periods)
+                }
+            };
 
-        //     let due_time = data.due_time;
+            let overdue = current - data.due_time;
 
-        //     let current_debt = data.current_debt;
+            let missed_periods = overdue / period + 1;
 
-        //     let mut extra_debt = data.extra_debt;
-            
-        //     let debt_interest = data.debt_interest;
+            assert!(
+                missed_periods >= self.default_policy.missed_periods || overdue >= self.default_policy.overdue_days * DAY, 
+                "The borrower has missed {} repayment periods and is {} days overdue, the loan cannot be declared defaulted yet.", missed_periods, overdue / DAY
+            );
 
-        //     assert!(current_debt + debt_interest + extra_debt != Decimal::ZERO, "You currently don't have any debt!");
+            let (principal, interest, charges) = credit_service.call_guarantees(self.controller_badge.create_proof(), id.clone(), data.current_debt + installment_remain, data.debt_interest, current);
 
-        //     if due_time <= current && extra_debt == Decimal::ZERO {
+            self.move_exposure_to_guarantors(&credit_id, charges);
 
-        //         let credit_service: GroundCredit = self.credit_service.into();
+            let data = resource_manager.get_non_fungible_data::<Credit>(&credit_id).data;
 
-        //         credit_service.degrade_credit(credit_proof.clone(), self.controller_badge.create_proof());
+            let current_debt = data.current_debt + installment_remain - principal;
 
-        //         extra_debt = match data.credit_type {
+            let debt_interest = data.debt_interest - interest;
 
-        //             CreditType::Revolving(types) => {
+            let asset_loss = self.write_off_asset_debts(&credit_id);
 
-        //                 match types {
+            // The borrower's loans in the stablecoin markets are written off against the markets' lenders, 
+            // the delegated loans are only written off through the delegations.
+            let (exposure, asset_exposure) = self.exposures.get(&credit_id).map_or((Decimal::ZERO, Decimal::ZERO), |exposure| {
+                (exposure.amount - exposure.asset - exposure.delegated, exposure.asset)
+            });
 
-        //                     RevolvingTypes::Monthly => {
+            let mut loss = if current_debt > asset_loss { current_debt - asset_loss } else { Decimal::ZERO };
 
-        //                         let number = (Decimal::from(current - due_time)  / Decimal::from(MONTH)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
-        //                         let rate = self.interest_rates.monthly.interest_rate_late;
-        //                         let mutiply = expo(rate, number);
-        //                         current_debt * (mutiply - Decimal::ONE)
+            if loss > exposure {
+                loss = exposure
+            }
 
-        //                     }
+            if self.default_policy.auto_compensate && loss > Decimal::ZERO {
+                if let Some(dao) = self.dao {
+                    let dao: GroundBusinessDAO = dao.into();
+                    let compensation = dao.compensate(self.controller_badge.create_proof(), loss * self.compensate_rate);
+                    info!("The protocol has been compensated {} stable coins from the DAO.", compensation.amount());
+                    loss -= compensation.amount();
+                    self.dao_payouts += compensation.amount();
+                    self.vault.put(compensation);
+                    self.fill_withdrawal_queue();
+                }
+            }
 
-        //                     RevolvingTypes::Yearly => {
-        //                         let number = (Decimal::from(current - due_time)  / Decimal::from(YEAR)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
-        //                         let rate = dec!("1") + self.interest_rates.yearly.interest_rate_late;
-        //                         let mutiply = expo(rate, number);
-        //                         current_debt * (mutiply - Decimal::ONE)
-        //                     }
+            let written_off = self.write_off(current, data.current_debt_start_time, loss);
 
-        //                 }
-        //             }
+            self.release_exposure(&credit_id, exposure, LoanSource::Pool);
 
-        //             CreditType::Installment(data) => {
+            self.release_exposure(&credit_id, asset_exposure, LoanSource::Market);
 
-        //                 let number = (Decimal::from(current - due_time) / Decimal::from(data.period_length)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
-        //                     let rate = data.interest_rate_late;
-        //                     let mutiply = expo(rate, number);
-        //                     current_debt * (mutiply - Decimal::ONE)
+            credit_service.degrade_business_owners(self.controller_badge.create_proof(), id.clone(), current);
 
-        //             }
-        //         };
+            let credit_scoring_rates = credit_service.credit_scoring_rate();
 
-        //         credit_service.update_debt(credit_proof.clone(), self.controller_badge.create_proof(), current_debt, debt_interest, extra_debt);
+            let degrade_rate = credit_scoring_rates.degrade_rate(&data.credit_type);
 
-        //     }
+            let credit_score = if data.credit_score >= degrade_rate {
+                data.credit_score - degrade_rate
+            } else { Decimal::zero() };
 
-        //     credit_proof.drop();
+            credit_service.update_credit(self.controller_badge.create_proof(), credit_id, 
+                CreditData {
+                    credit_score,
+                    current_debt,
+                    debt_interest,
+                    defaulted: true,
+                    ..data
+                }
+            );
 
-        //     info!("Your current debt is {}.", current_debt + debt_interest + extra_debt);
+            info!("The loan of the user ID {} has been declared defaulted, {} stable coins principal has been written off from the lenders.", id, written_off);
 
-        //     (current_debt, debt_interest, extra_debt)
-        // }
+        }
 
-        /// This method is for the permissioned borrower to take a loan from their revolving credit.
+        /// This method is to check if the protocol is listed or not.
+        /// 
+        /// Input: The protocol controller badge proof.
+        pub fn check_protocol(&self, protocol_proof: Proof) {
+            assert!(protocol_proof.resource_address() == self.controller_badge.resource_address(), "This protocol is not allowed to use on-chain credit service.");
+            protocol_proof.drop();
+        }
+
+        /// This method is for protocols to check and update user's installment credit data
+        /// before user take the installment loan.
         /// ### Input: 
-        /// - id_proof: the Identity SBT proof.
-        /// - credit_sbt: the Credit SBT proof.
-        /// - amount: the amount borrower wish to loan.
+        /// - id_sbt_proof: The Proof of the user's Credit SBT.
+        /// - credit_proof: the Proof of the user's Credit SBT.
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// - installment_credit_badge: The Bucket of user's Installment Credit NFT.
+        /// - current: Current time data fed in through the protocol. (unix)
         /// ### Output: 
-        /// borrower's loan by amount.
-        pub fn revolving_credit(&mut self, id_proof: Proof, credit_sbt: Proof, amount: Decimal) -> Bucket {
-            
-            assert!(amount > Decimal::zero(), "Wrong data provided!");
-            
-            let credit_service: GroundCredit = self.credit_service.into();
+        /// Return the installment credit amount.
+        pub fn use_installment_credit(&self, id_sbt_proof: Proof, credit_proof: Proof, protocol_proof: Proof, installment_credit_badge: Bucket, current: u64) -> Decimal {
 
-            assert!(self.total_return != Decimal::ZERO, "Currently there's no resource on the protocol's vault, please come back later.");
+            self.check_protocol(protocol_proof);
 
-            assert!((self.vault.amount() - amount) / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+            let credit = credit_proof.non_fungible::<Credit>();
 
-            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Credit>().id(), id_proof.resource_address(), credit_sbt.non_fungible::<Credit>().id(), credit_sbt.resource_address());
+            let data = credit.data().data;
 
-            let credit_data = credit_sbt.non_fungible::<Credit>().data().data;
+            let id_sbt = id_sbt_proof.non_fungible::<Identity>().id();
 
-            let id_data = id_proof.non_fungible::<Identity>().data().data;
+            id_sbt_proof.drop(); 
 
-            let (interest_rate, time) = match credit_data.credit_type {
+            let credit_service: GroundCredit = self.credit_service.into();
 
-                CreditType::Revolving(revolving_types) => {
-                    match revolving_types {
-                        RevolvingTypes::Monthly => {(self.interest_rates.monthly.interest_rate, MONTH)}
+            credit_service.check_installment_credit(installment_credit_badge.resource_address(), installment_credit_badge.non_fungible::<InstallmentCredit>().id());
 
-                        RevolvingTypes::Yearly => {(self.interest_rates.yearly.interest_rate, YEAR)}
-                    }
-                }
+            let installment_data = installment_credit_badge.non_fungible::<InstallmentCredit>().data();
 
-                _ => {panic!("Wrong credit type!")}
-            };
+            assert!(installment_data.activation_deadline == 0 || current <= installment_data.activation_deadline, "Your Installment Credit Badge has expired.");
 
-            let neuracle: NeuRacle = self.oracle.0.into();
-            let data_proof = self.oracle.1.create_proof();
-            let current = neuracle.get_data(data_proof);
-            let current: u64 = current.parse().unwrap();
+            let installment_id = installment_data.sbt_id;
 
-            let due_time = credit_data.due_time;
+            assert!(id_sbt == installment_id, "Wrong Installment Credit Badge provided.");
 
-            assert!(due_time == 0 || due_time > current, "Your credit is overdue, please repay your loan first!");
+            assert!(data.due_time == 0, "You have to repay all your current debt first.");
 
-            let (_, allowance) = credit_service.get_revolving_credit_amount_by_data(id_data, credit_data);
-            
-            assert!(allowance >= amount, "Out of credit, you have to repay your debt first!");
+            assert!(data.pledged_amount == Decimal::ZERO, "You have to release all your guarantees for other users first.");
+
+            let mut installment_data = installment_data.data;
 
-            let increase_debt_interest = amount * interest_rate;
+            installment_data.period_counter = 1;
+            
+            let current_debt = installment_data.total_loan / installment_data.period_max;
 
-            let credit_sbt = self.update_debt(credit_sbt, self.controller_badge.create_proof(), credit_data.current_debt + amount, credit_data.debt_interest + increase_debt_interest, Decimal::ZERO);
+            let debt_interest = current_debt * installment_data.interest_rate;
 
-            let credit_sbt = if due_time == 0 {
+            let due_time = current + installment_data.period_length;
 
-                self.update_debt_time(credit_sbt, self.controller_badge.create_proof(), current + time, current)
-                
-            } else {
+            credit_service.burn_installment_credit_badge(self.controller_badge.create_proof(), installment_credit_badge);
 
-                credit_sbt
-                
-            };
+            credit_service.update_credit(self.controller_badge.create_proof(), credit.id(), 
+                CreditData {
+                    credit_type: CreditType::Installment(installment_data),
+                    current_debt_start_time: current,
+                    current_debt,
+                    debt_interest,
+                    due_time,
+                    ..data
+                }
+            );
 
-            credit_sbt.drop(); id_proof.drop();
+            info!("You have changed your credit type into Installment Credit. Your current debt is: {}. Your debt will over due in: {} (unix time)", current_debt + debt_interest, due_time);
 
-            info!("You have taken a {} stable coins loan from your credit", amount);
+            credit_proof.drop();
 
-            self.vault.take(amount)
-            
+            installment_data.total_loan
+                    
         }
 
-        /// This method is for the permissioned borrower to take their installment loan.
+        /// This method is for protocols to check and update user's installment credit data
+        /// after user repaid the period loan.
         /// ### Input: 
-        /// - id_proof: the Identity SBT proof.
-        /// - credit_sbt: the Credit SBT proof.
-        /// - installment_credit_badge: the Installment Credit Badge.
-        /// ### Output: 
-        /// All the borrower's installment loan.
-        pub fn installment_credit(&mut self, id_proof: Proof, credit_sbt: Proof, installment_credit_badge: Bucket) -> Bucket {
+        /// - credit_proof: the Proof of the user's Credit SBT.
+        /// - protocol_proof: The Proof of the protocol's controller badge.
+        /// ### Output:
+        /// - Update the installment credit data based on user's repayment, the credit service restores the credit score if the period is repaid on time.
+        /// - Return the next period debt amount. (include the origin debt and the interest)
+        pub fn update_installment_credit_data(&self, credit_proof: Proof, protocol_proof: Proof) -> (Decimal, Decimal, Proof) {
 
-            assert!(self.total_return != Decimal::ZERO, "Currently there's no resource on the protocol's vault, please come back later.");
+            self.check_protocol(protocol_proof);
 
             let credit_service: GroundCredit = self.credit_service.into();
 
-            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Credit>().id(), id_proof.resource_address(), credit_sbt.non_fungible::<Credit>().id(), credit_sbt.resource_address());
+            let credit = credit_proof.non_fungible::<Credit>();
 
-            let neuracle: NeuRacle = self.oracle.0.into();
-            let data_proof = self.oracle.1.create_proof();
-            let current = neuracle.get_data(data_proof);
-            let current: u64 = current.parse().unwrap();
+            let mut data = credit.data().data;
 
-            let amount = self.use_installment_credit(id_proof, credit_sbt, self.controller_badge.create_proof(), installment_credit_badge, current);
+            let mut installment_data = match data.credit_type {
 
-            assert!((self.vault.amount() - amount) / self.total_return > self.tolerance_threshold, "Currently you cannot take your credit from this protocol, please come back later.");
+                CreditType::Installment(data) => {data}
+                _ => {panic!("Wrong credit type!")}
 
-            info!("You have taken a {} stable coins loan from your installment credit.", amount);
+            };
 
-            self.vault.take(amount)
+            if installment_data.period_counter < installment_data.period_max {
 
-        }
+                installment_data.period_counter += 1;
 
-        /// This method is for the permissioned borrower to repay their loan.
-        /// ### Input: 
-        /// - id_proof: the Identity SBT proof.
-        /// - credit_sbt: the Credit SBT proof.
-        /// - repayment: the repayment stablecoin bucket.
-        /// ### Output: 
-        /// Remainder of borrower stablecoin bucket.
-        /// 
-        /// From this method, when the borrower repaid their debt in full, 
-        /// the method will automatically increase lender's lending amount if they're eligible for the interest 
-        /// (the borrow is made after lender's create the lending account).
-        /// 
-        /// Borrower can also make a period installment repayment in advance, their credit data will automatically updated through the method.
-        pub fn repay(&mut self, mut id_proof: Proof, credit_proof: Proof, mut repayment: Bucket) -> Bucket {
+                let current_debt = installment_data.total_loan / installment_data.period_max;
 
-            assert!(repayment.resource_address() == self.vault.resource_address(), "Wrong resource.");
+                let debt_interest = current_debt * installment_data.interest_rate;
 
-            let credit_service: GroundCredit = self.credit_service.into();
+                let period_length = installment_data.period_length;
 
-            credit_service.check_id_and_credit_by_data(id_proof.non_fungible::<Credit>().id(), id_proof.resource_address(), credit_proof.non_fungible::<Credit>().id(), credit_proof.resource_address());
+                info!("You have repaid all the current period debt from your installment credit, your installment period will be advanced by 1. Current period: {}", installment_data.period_counter);
 
-            let credit_data = credit_proof.non_fungible::<Credit>().data().data;
+                data = CreditData {
+                        credit_type: CreditType::Installment(installment_data),
+                        current_debt_start_time: data.due_time,
+                        current_debt,
+                        debt_interest,
+                        due_time: data.due_time + period_length,
+                        ..data
+                    };
 
-            let credit_proof = match credit_data.credit_type {
+            } else if installment_data.period_counter == installment_data.period_max {
 
-                CreditType::Installment(_) => {
+                installment_data.period_counter += 1;
 
-                    let mut total_repaid = Decimal::ZERO;
+                data = CreditData {
+                        credit_type: CreditType::Revolving(RevolvingTypes::Monthly),
+                        current_debt_start_time: 0,
+                        current_debt: Decimal::ZERO,
+                        debt_interest: Decimal::ZERO,
+                        due_time: 0,
+                        ..data
+                    };
 
-                    let mut amount = repayment.amount();
+                info!("You have repaid all your installment loan, your credit will change into the Monthly Revolving Credit");
 
-                    let (mut new_debt, mut new_debt_interest, mut new_extra_debt, mut credit_proof) = self.get_total_debt(credit_proof);
+            } else {panic!("You have already repaid all the installment credit debt. Please consider changing your credit type into revolving credit.")};
 
-                    while new_debt > Decimal::ZERO {
+            credit_service.update_credit(self.controller_badge.create_proof(), credit.id(), data);
+
+            return (data.current_debt, data.debt_interest, credit_proof)
+
+        }
+
+        /// This method is for lending protocol to update the debt amount of an user.
+        /// ### Input: 
+        /// - credit_proof: the user's credit proof.
+        /// - protocol_proof: the protocol controller's proof.
+        /// - current_debt: new debt amount.
+        /// - extra_debt: new extra debt amount.
+        /// ### Output: 
+        /// update the credit debt amount.
+        pub fn update_debt(&self, credit_proof: Proof, protocol_proof: Proof, current_debt: Decimal, debt_interest: Decimal, extra_debt: Decimal) -> Proof {
+
+                self.check_protocol(protocol_proof);
+                let credit = credit_proof.non_fungible::<Credit>();
+                let data = credit.data().data;
+                let credit_service: GroundCredit = self.credit_service.into();
+    
+                credit_service.update_credit(self.controller_badge.create_proof(), credit.id(), 
+                    CreditData {
+                        current_debt,
+                        debt_interest,
+                        extra_debt,
+                        ..data
+                    }
+                );
+
+                info!("Your current debt is {}", current_debt + debt_interest + extra_debt);
+
+                credit_proof
+    
+        }
+
+        /// This method is for lending protocol to update credit due time of an user.
+        /// ### Input: 
+        /// - credit_proof: the user's credit proof.
+        /// - protocol_proof: the protocol controller's proof.
+        /// - due_time: new credit loan due time
+        /// - current_debt_start_time: new credit loan start time.
+        /// ### Output: 
+        /// update the user's credit due time
+        pub fn update_debt_time(&self, credit_proof: Proof, protocol_proof: Proof, due_time: u64, current_debt_start_time: u64) -> Proof {
 
-                        if new_debt + new_debt_interest <= amount {
+                self.check_protocol(protocol_proof);
+                let credit = credit_proof.non_fungible::<Credit>();
+                let data = credit.data().data;
+                let credit_service: GroundCredit = self.credit_service.into();
+                credit_service.update_credit(self.controller_badge.create_proof(), credit.id(), 
+                    CreditData {
+                        due_time,
+                        current_debt_start_time,
+                        ..data
+                    }
+                );
 
-                            amount -= new_debt + new_debt_interest;
+                if due_time != 0 {info!("Your debt will be over due in {} (unix time)", due_time);}
 
-                            total_repaid += new_debt + new_debt_interest;
+                credit_proof
+    
+        }
 
-                            let debt_start = credit_data.current_debt_start_time;
+        /// This method is for lending protocol to degrade credit score of an user.
+        /// ### Input: 
+        /// - credit_proof: the user's credit proof.
+        /// - protocol_proof: the protocol controller's proof.
+        /// ### Output: 
+        /// degrade the credit score
+        pub fn degrade_credit(&self, credit_proof: Proof, protocol_proof: Proof) -> Proof {
 
-                            let mut eligible_return = Decimal::ZERO;
-                            
-                            for lender in self.lenders.values() {
-                                if lender.start_time < debt_start {
-                                    eligible_return += lender.lending_amount
-                                }
-                            };
+            self.check_protocol(protocol_proof);
+            let credit = credit_proof.non_fungible::<Credit>();
+            let old_data = credit.data().data;
 
-                            if eligible_return != Decimal::ZERO {
+            let credit_service: GroundCredit = self.credit_service.into();
 
-                                self.vault.put(repayment.take(new_debt + new_debt_interest));
+            let credit_scoring_rates = credit_service.credit_scoring_rate();
 
-                                let interest = Decimal::ONE + new_debt_interest / eligible_return;
+            let score = old_data.credit_score;
 
-                                for lender in self.lenders.values_mut() {
+            let degrade_rate = credit_scoring_rates.degrade_rate(&old_data.credit_type);
 
-                                    let start_time = lender.start_time;
+            let new_score = if score >= degrade_rate {
+                score - degrade_rate
+            } else { Decimal::zero() };
 
-                                    if start_time < debt_start {
+            credit_service.update_credit(self.controller_badge.create_proof(), credit.id(), 
+                CreditData {
+                    credit_score: new_score,
+                    ..old_data
+                }
+            );
 
-                                        lender.interest(interest)
+            info!("Your credit score has been degraded to {} because of late repayment", new_score);
 
-                                    };
+            credit_proof
 
-                                };
-                            } else {
-                                self.vault.put(repayment.take(new_debt));
-                                self.deposit_fee(repayment.take(new_debt_interest));
-                            }
+        }
+    
+        /// This method is for lending protocol to restore credit score of an user (if the on-time repaid amount passed the maximum credit amount).
+        /// ### Input: 
+        /// - id_proof: the user's ID proof.
+        /// - credit_proof: the user's credit proof.
+        /// - protocol_proof: the protocol controller's proof.
+        /// ### Output: 
+        /// restore the credit score (if the repaid amount counted by the credit service passed the maximum credit amount).
+        pub fn update_revolving_credit_repaid_accumulate(&self, id_proof: Proof, credit_proof: Proof, protocol_proof: Proof) -> (Proof, Proof) {
 
-                            if new_extra_debt != Decimal::ZERO {
+            self.check_protocol(protocol_proof);
 
-                                if new_extra_debt <= amount {
+            let credit_service: GroundCredit = self.credit_service.into();
 
-                                    self.deposit_fee(repayment.take(new_extra_debt));
-                                    new_extra_debt = Decimal::ZERO;
-                                    amount -= new_extra_debt;
-                                    total_repaid += new_extra_debt;
-                                    (new_debt, new_debt_interest, credit_proof) = self.update_installment_credit_data(credit_proof, self.controller_badge.create_proof(), true);
-                                    
-    
-                                } else {
-    
-                                    self.deposit_fee(repayment.take(amount));
-                                    total_repaid += amount;
-                                    new_extra_debt -= amount;
-                                    break
-    
-                                }
+            credit_service.restore_credit_score(self.controller_badge.create_proof(), id_proof.non_fungible::<Identity>().id());
 
-                            } else {
-                                (new_debt, new_debt_interest, credit_proof) = self.update_installment_credit_data(credit_proof, self.controller_badge.create_proof(), false);
-                            }
-        
-                        } else {
+            (id_proof, credit_proof)
             
-                            self.vault.put(repayment.take(amount));
-                            total_repaid += amount;
+        }
 
-                            if amount <= new_debt {
-                                new_debt -= amount
-                            } else {
-                                let remain = amount - new_debt;
-                                new_debt = Decimal::ONE;
-                                new_debt_interest -= remain
-                            }
+        /// Write off the loss against the lending tranches, the junior tranche absorbs the loss first.
+        /// 
+        /// The insurance reserve covers the loss first. The loss is written off against the cohorts eligible for the loan (closed before the debt start time), 
+        /// pro-rata to their return amount in each tranche. If there is no eligible cohort, the loss will be written off against all cohorts.
+        /// 
+        /// Return the written off amount.
+        fn write_off(&mut self, current: u64, debt_start: u64, loss: Decimal) -> Decimal {
 
-                            break
-            
-                        };
-                        
-                    }
+            if loss <= Decimal::ZERO {
+                return Decimal::ZERO
+            }
 
-                    if new_debt + new_debt_interest + new_extra_debt > Decimal::ZERO {
-                        credit_proof = self.update_debt(credit_proof, self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
-                        info!("You have repaid {} stable coins for the protocol.", total_repaid)
-                    };
+            let loss = loss - self.cover_loss(loss);
 
-                    credit_proof
-                }
+            if loss == Decimal::ZERO {
+                return Decimal::ZERO
+            }
 
-                CreditType::Revolving(_) => {
+            let (mut senior, mut junior) = self.eligible_returns_of(Some(debt_start));
 
-                    let (current_debt, debt_interest, extra_debt, mut credit_proof) = self.get_total_debt(credit_proof);
+            let mut eligible = Some(debt_start);
 
-                    let mut amount = repayment.amount();
+            if senior + junior == Decimal::ZERO {
+                (senior, junior) = self.tranche_returns_of();
+                eligible = None;
+            }
 
-                    let (new_debt, new_debt_interest, new_extra_debt, total_repaid) = if current_debt + debt_interest <= amount {
+            if senior + junior == Decimal::ZERO {
+                return Decimal::ZERO
+            }
 
-                        if current_debt + debt_interest != Decimal::ZERO {
+            let loss = if loss > senior + junior { senior + junior } else { loss };
 
-                            amount -= current_debt + debt_interest;
+            let junior_loss = if loss > junior { junior } else { loss };
 
-                            let debt_start = credit_data.current_debt_start_time;
+            let senior_loss = loss - junior_loss;
 
-                            let mut eligible_return = Decimal::ZERO;
-                            
-                            for lender in self.lenders.values() {
-                                if lender.start_time < debt_start {
-                                    eligible_return += lender.lending_amount
-                                }
-                            };
+            let junior_rate = if junior == Decimal::ZERO { Decimal::ONE } else { Decimal::ONE - junior_loss / junior };
 
-                            if eligible_return != Decimal::ZERO {
+            let senior_rate = if senior == Decimal::ZERO { Decimal::ONE } else { Decimal::ONE - senior_loss / senior };
 
-                                self.vault.put(repayment.take(current_debt + debt_interest));
+            self.apply_rates(current, senior_rate, junior_rate, eligible);
 
-                                let interest = Decimal::ONE + debt_interest / eligible_return;
+            self.senior_shortfall += senior_loss;
 
-                                for lender in self.lenders.values_mut() {
+            self.total_return -= loss;
 
-                                    let start_time = lender.start_time;
+            loss
 
-                                    if start_time < debt_start {
+        }
 
-                                        lender.interest(interest)
-                                    };
+        /// Distribute the recovery from a defaulted loan to the lending tranches.
+        /// 
+        /// The insurance reserve's payouts are refilled first, then the DAO's compensations are repaid. 
+        /// The senior tranche is recovered first until its shortfall is covered, the rest is distributed as interest.
+        fn recover(&mut self, current: u64, mut recovery: Bucket) {
 
-                                };
-                            } else {
-                                self.vault.put(repayment.take(current_debt));
-                                self.deposit_fee(repayment.take(debt_interest));
-                            }
-                        } 
+            let refill = if recovery.amount() > self.reserve_payouts { self.reserve_payouts } else { recovery.amount() };
 
-                        if extra_debt != Decimal::ZERO {
+            if refill != Decimal::ZERO {
+                self.reserve_payouts -= refill;
+                self.reserve_vault.put(recovery.take(refill));
+            }
 
-                            if extra_debt <= amount {
+            if let Some(dao) = self.dao {
+                let refund = if recovery.amount() > self.dao_payouts { self.dao_payouts } else { recovery.amount() };
+                if refund != Decimal::ZERO {
+                    self.dao_payouts -= refund;
+                    let dao: GroundBusinessDAO = dao.into();
+                    dao.deposit(recovery.take(refund));
+                    info!("{} stable coins of the recovery have been returned to the DAO.", refund);
+                }
+            }
 
-                                self.deposit_fee(repayment.take(extra_debt));
-                                (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, current_debt + debt_interest + extra_debt)
-    
-                            } else {
-    
-                                self.deposit_fee(repayment.take(amount));
-                                (Decimal::ZERO, Decimal::ZERO, extra_debt - amount, current_debt + debt_interest + amount)
-    
-                            }
-                            
-                        } else {
-                            (id_proof, credit_proof) = self.update_revolving_credit_repaid_accumulate(id_proof, credit_proof, self.controller_badge.create_proof(), current_debt + debt_interest);
-                            (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, current_debt + debt_interest)
-                        }
-    
-                    } else {
-        
-                        self.vault.put(repayment.take(amount));
+            let amount = recovery.amount();
 
-                        if extra_debt == Decimal::ZERO {
-                            (id_proof, credit_proof) = self.update_revolving_credit_repaid_accumulate(id_proof, credit_proof, self.controller_badge.create_proof(), amount);
-                        }
+            if amount == Decimal::ZERO {
+                self.vault.put(recovery);
+                self.fill_withdrawal_queue();
+                return
+            }
 
-                        if amount <= current_debt {
-                            (current_debt - amount, debt_interest, extra_debt, amount)
-                        } else {
-                            let remain = amount - current_debt;
-                            (Decimal::ZERO, debt_interest - remain, extra_debt, amount)
-                        }
-        
-                    };
+            let (senior, junior) = self.tranche_returns_of();
 
-                    credit_proof = self.update_debt(credit_proof, self.controller_badge.create_proof(), new_debt, new_debt_interest, new_extra_debt);
+            if senior + junior == Decimal::ZERO {
+                self.deposit_fee(recovery);
+                return
+            }
 
-                    if new_debt + new_debt_interest + new_extra_debt == Decimal::ZERO {
-                        
-                        credit_proof = self.update_debt_time(credit_proof, self.controller_badge.create_proof(), 0, 0);
-                        info!("You have repaid all your current debt.")
+            let senior_recovery = if senior == Decimal::ZERO {
+                Decimal::ZERO
+            } else if amount > self.senior_shortfall {
+                self.senior_shortfall
+            } else { amount };
 
-                    } else {
+            if senior_recovery != Decimal::ZERO {
+                self.senior_shortfall -= senior_recovery;
+                self.apply_rates(current, Decimal::ONE + senior_recovery / senior, Decimal::ONE, None);
+            }
 
-                        info!("You have repaid {} stable coins for the protocol.", total_repaid)
+            self.distribute_interest(current, amount - senior_recovery);
 
-                    };
+            self.total_return += amount;
 
-                    credit_proof
-                }
-            };
+            self.vault.put(recovery);
 
-            id_proof.drop(); credit_proof.drop();
+            self.fill_withdrawal_queue();
 
-            return repayment
+        }
 
+        /// Lock the interest to be released to the lenders over the interest release period.
+        fn distribute_interest(&mut self, current: u64, interest: Decimal) {
+            self.release_interest(current);
+            self.locked_interest += interest;
         }
 
-        /// This method is for the permissioned borrower to get their total debt.
-        /// ### Input: 
-        /// - credit_sbt: the Credit SBT proof.
-        /// ### Output: 
-        /// The borrower's initial debt, debt interest, and extra debt from late repayment.
+        /// Accumulate the lenders' capital×time and release the locked interest to the lenders, this must be called before any lender's return amount change.
         /// 
-        /// From this method, if the protocol see the borrower are late on repayment for the first time, 
-        /// the protocol will automatically degrade the borrower's credit score, 
-        /// calculate the extra debt from late repayment and update borrower's debt data.
-        pub fn get_total_debt(&self, mut credit_proof: Proof) -> (Decimal, Decimal, Decimal, Proof) {
-
-            let data = credit_proof.non_fungible::<Credit>().data().data;
-
-            let neuracle: NeuRacle = self.oracle.0.into();
-            let data_proof = self.oracle.1.create_proof();
-            let current = neuracle.get_data(data_proof);
-            let current: u64 = current.parse().expect("Wrong data!");
+        /// The released share of the locked interest is the time passed since the last release over the interest release period. 
+        /// Each tranche takes the released interest by its return amount weighted by the junior interest premium and its lenders' term deposit bonus, 
+        /// the pool token holders' share goes directly into the pool token's value. 
+        /// The pool token holders' locked share of the loans' interest is released into the pool token's value at the same pace.
+        /// 
+        /// The interest stays locked while the protocol doesn't have any lender.
+        fn release_interest(&mut self, current: u64) {
 
-            let due_time = data.due_time;
+            self.accumulate_capital_time(current);
 
-            let current_debt = data.current_debt;
+            if current <= self.interest_release_time { return }
 
-            let mut extra_debt = data.extra_debt;
-            
-            let debt_interest = data.debt_interest;
+            let elapsed = current - self.interest_release_time;
 
-            assert!(current_debt + debt_interest + extra_debt != Decimal::ZERO, "You currently don't have any debt!");
+            self.interest_release_time = current;
 
-            if due_time <= current && extra_debt == Decimal::ZERO {
+            let release_share = if elapsed >= INTEREST_RELEASE_PERIOD {
+                Decimal::ONE
+            } else {
+                Decimal::from(elapsed) / Decimal::from(INTEREST_RELEASE_PERIOD)
+            };
 
-                credit_proof = self.degrade_credit(credit_proof, self.controller_badge.create_proof());
+            if let Some(pool) = &mut self.token_pool {
+                if pool.value != Decimal::ZERO {
+                    let released = self.token_locked_interest * release_share;
+                    self.token_locked_interest -= released;
+                    pool.value += released
+                }
+            }
 
-                extra_debt = match data.credit_type {
+            let senior_weight = self.senior.weight();
 
-                    CreditType::Revolving(types) => {
+            let junior_weight = self.junior.weight() * self.junior_premium();
 
-                        match types {
+            let token_weight = match &self.token_pool {
+                Some(pool) => pool.value,
+                None => Decimal::ZERO
+            };
 
-                            RevolvingTypes::Monthly => {
+            let total_weight = senior_weight + junior_weight + token_weight;
 
-                                let number = (Decimal::from(current - due_time)  / Decimal::from(MONTH)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
-                                let rate = self.interest_rates.monthly.interest_rate_late;
-                                let mutiply = expo(rate, number);
-                                current_debt * (mutiply - Decimal::ONE)
+            if total_weight == Decimal::ZERO || self.locked_interest == Decimal::ZERO { return }
 
-                            }
+            let released = self.locked_interest * release_share;
 
-                            RevolvingTypes::Yearly => {
-                                let number = (Decimal::from(current - due_time)  / Decimal::from(YEAR)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
-                                let rate = dec!("1") + self.interest_rates.yearly.interest_rate_late;
-                                let mutiply = expo(rate, number);
-                                current_debt * (mutiply - Decimal::ONE)
-                            }
+            self.locked_interest -= released;
 
-                        }
-                    }
+            self.senior.release(released * senior_weight / total_weight);
 
-                    CreditType::Installment(data) => {
+            self.junior.release(released * junior_weight / total_weight);
 
-                        let number = (Decimal::from(current - due_time) / Decimal::from(data.period_length)).ceiling().to_string().parse().expect("Cannot parse Decimal to u8");
-                            let rate = data.interest_rate_late;
-                            let mutiply = expo(rate, number);
-                            current_debt * (mutiply - Decimal::ONE)
+            if let Some(pool) = &mut self.token_pool {
+                pool.value += released * token_weight / total_weight
+            }
 
-                    }
-                };
+        }
 
-                credit_proof = self.update_debt(credit_proof, self.controller_badge.create_proof(), current_debt, debt_interest, extra_debt);
+        /// The junior tranche's interest weight multiplier, increased by the junior interest premium.
+        fn junior_premium(&self) -> Decimal {
+            match &self.tranche_policy {
+                Some(policy) => Decimal::ONE + policy.junior_interest_premium,
+                None => Decimal::ONE
+            }
+        }
 
-            } else {
-                info!("Your current debt is {}.", current_debt + debt_interest + extra_debt);
+        /// Get the tranche's pool.
+        fn tranche_mut(&mut self, tranche: Tranche) -> &mut TranchePool {
+            match tranche {
+                Tranche::Senior => &mut self.senior,
+                Tranche::Junior => &mut self.junior
             }
-            
-            (current_debt, debt_interest, extra_debt, credit_proof)
         }
 
-        /// This method is to check if the protocol is listed or not.
+        /// The senior and junior tranches' return amount.
         /// 
-        /// Input: The protocol controller badge proof.
-        pub fn check_protocol(&self, protocol_proof: Proof) {
-            assert!(protocol_proof.resource_address() == self.controller_badge.resource_address(), "This protocol is not allowed to use on-chain credit service.");
-            protocol_proof.drop();
+        /// The pool token holders are in the senior tranche.
+        fn tranche_returns_of(&self) -> (Decimal, Decimal) {
+            self.eligible_returns_of(None)
         }
 
-        /// This method is for protocols to check and update user's installment credit data
-        /// before user take the installment loan.
-        /// ### Input: 
-        /// - id_sbt_proof: The Proof of the user's Credit SBT.
-        /// - credit_proof: the Proof of the user's Credit SBT.
-        /// - protocol_proof: The Proof of the protocol's controller badge.
-        /// - installment_credit_badge: The Bucket of user's Installment Credit NFT.
-        /// - current: Current time data fed in through the protocol. (unix)
-        /// ### Output: 
-        /// Return the installment credit amount.
-        pub fn use_installment_credit(&self, id_sbt_proof: Proof, credit_proof: Proof, protocol_proof: Proof, installment_credit_badge: Bucket, current: u64) -> Decimal {
-
-            self.check_protocol(protocol_proof);
-
-            let credit = credit_proof.non_fungible::<Credit>();
-
-            let data = credit.data().data;
+        /// The senior and junior tranches' return amount of the cohorts eligible for a loan (or all cohorts).
+        /// 
+        /// The pool token holders are always eligible.
+        fn eligible_returns_of(&self, debt_start: Option<u64>) -> (Decimal, Decimal) {
+            let senior = match &self.token_pool {
+                Some(pool) => pool.value,
+                None => Decimal::ZERO
+            };
+            (senior + self.senior.value(debt_start), self.junior.value(debt_start))
+        }
 
-            let id_sbt = id_sbt_proof.non_fungible::<Identity>().id();
+        /// Apply the interest (or loss) rates on the senior and junior tranches' cohorts eligible for a loan (or all cohorts).
+        /// 
+        /// The locked interest is released first, so the interest released until now is weighted by the tranches' return amount before the change.
+        fn apply_rates(&mut self, current: u64, senior_rate: Decimal, junior_rate: Decimal, debt_start: Option<u64>) {
+            self.release_interest(current);
+            if let Some(pool) = &mut self.token_pool {
+                pool.value = pool.value * senior_rate
+            }
+            self.senior.apply_rate(senior_rate, debt_start);
+            self.junior.apply_rate(junior_rate, debt_start);
+        }
 
-            id_sbt_proof.drop(); 
+        /// Distribute the interest of a loan to the lenders eligible for the loan, the insurance reserve takes its share of the interest first.
+        /// 
+        /// The interest is shared by the capital×time each eligible cohort (closed before the loan was taken) and the pool token holders accumulated since the loan's checkpoint, 
+        /// the junior cohorts' capital×time is increased by the junior interest premium. The loan's checkpoint is then moved to the repayment time. 
+        /// The pool token holders' share is released into the pool token's value over the interest release period. 
+        /// If no eligible lender has accumulated any capital×time, the interest is locked and released to all lenders.
+        fn distribute_loan_interest(&mut self, current: u64, credit_id: &NonFungibleId, interest: Decimal) {
 
-            let credit_service: GroundCredit = self.credit_service.into();
+            if interest == Decimal::ZERO { return }
 
-            credit_service.check_installment_credit(installment_credit_badge.resource_address());
+            let interest = self.take_reserve(interest);
 
-            let installment_data = installment_credit_badge.non_fungible::<InstallmentCredit>().data();
+            self.total_return += interest;
 
-            let installment_id = installment_data.sbt_id;
+            self.release_interest(current);
 
-            assert!(id_sbt == installment_id, "Wrong Installment Credit Badge provided.");
+            let mut checkpoint = match self.loan_checkpoints.get(credit_id) {
+                Some(checkpoint) => checkpoint,
+                None => return self.distribute_interest(current, interest)
+            };
 
-            assert!(data.due_time == 0, "You have to repay all your current debt first.");
+            let senior = self.senior.capital_times_since(&mut checkpoint.senior);
 
-            let mut installment_data = installment_data.data;
+            let premium = self.junior_premium();
 
-            installment_data.period_counter = 1;
-            
-            let current_debt = installment_data.total_loan / installment_data.period_max;
+            let junior: Vec<Decimal> = self.junior.capital_times_since(&mut checkpoint.junior).into_iter().map(|capital_time| capital_time * premium).collect();
 
-            let debt_interest = current_debt * installment_data.interest_rate;
+            let token = match &self.token_pool {
+                Some(pool) if pool.shares != Decimal::ZERO => self.token_capital_time - checkpoint.token,
+                _ => Decimal::ZERO
+            };
 
-            let due_time = current + installment_data.period_length;
+            checkpoint.token = self.token_capital_time;
 
-            self.controller_badge.authorize(|| { 
-                installment_credit_badge.burn();
-                credit.update_data(Credit {
-                    data: CreditData {
-                        credit_type: CreditType::Installment(installment_data),
-                        current_debt_start_time: current,
-                        current_debt,
-                        debt_interest,
-                        due_time,
-                        ..data
-                    }
-                })
-            });
+            self.loan_checkpoints.insert(credit_id.clone(), checkpoint);
 
-            info!("You have changed your credit type into Installment Credit. Your current debt is: {}. Your debt will over due in: {} (unix time)", current_debt + debt_interest, due_time);
+            let total = senior.iter().chain(junior.iter()).fold(token, |total, capital_time| total + *capital_time);
 
-            credit_proof.drop();
+            if total == Decimal::ZERO {
+                return self.distribute_interest(current, interest)
+            }
 
-            installment_data.total_loan
-                    
-        }
+            let rate = interest / total;
 
-        /// This method is for protocols to check and update user's installment credit data
-        /// after user repaid the period loan.
-        /// ### Input: 
-        /// - credit_proof: the Proof of the user's Credit SBT.
-        /// - protocol_proof: The Proof of the protocol's controller badge.
-        /// - late: input if the repayment is late or not.
-        /// ### Output:
-        /// - Update the installment credit data based on user's repayment.
-        /// - Return the next period debt amount. (include the origin debt and the interest)
-        pub fn update_installment_credit_data(&self, credit_proof: Proof, protocol_proof: Proof, late: bool) -> (Decimal, Decimal, Proof) {
+            self.senior.release_by(rate, &senior);
 
-            self.check_protocol(protocol_proof);
+            self.junior.release_by(rate, &junior);
 
-            let credit_service: GroundCredit = self.credit_service.into();
+            self.token_locked_interest += rate * token;
 
-            let credit_scoring_rates = credit_service.credit_scoring_rate();
+        }
 
-            let credit = credit_proof.non_fungible::<Credit>();
+        /// Accumulate the cohorts' and the pool token holders' capital×time until the current time.
+        fn accumulate_capital_time(&mut self, current: u64) {
 
-            let mut data = credit.data().data;
+            self.senior.accumulate(current);
 
-            let mut installment_data = match data.credit_type {
+            self.junior.accumulate(current);
 
-                CreditType::Installment(data) => {data}
-                _ => {panic!("Wrong credit type!")}
+            if current > self.token_capital_time_update {
+                if let Some(pool) = &self.token_pool {
+                    self.token_capital_time += pool.value * Decimal::from(current - self.token_capital_time_update) / Decimal::from(DAY)
+                }
+                self.token_capital_time_update = current;
+            }
 
-            };
+        }
 
-            if !late {
-                data.credit_score += credit_scoring_rates.monthly.restore_rate
-            };
+        /// Checkpoint the capital×time of the lenders eligible for a loan taken at the current time.
+        fn loan_checkpoint(&mut self, current: u64) -> LoanCheckpoint {
+            self.accumulate_capital_time(current);
+            LoanCheckpoint {
+                senior: self.senior.capital_times(current),
+                junior: self.junior.capital_times(current),
+                token: self.token_capital_time
+            }
+        }
 
-            if installment_data.period_counter < installment_data.period_max {
+        /// Move the reserve factor share of the interest from the protocol's vault into the insurance reserve.
+        /// 
+        /// Return the remaining interest for lenders.
+        fn take_reserve(&mut self, interest: Decimal) -> Decimal {
+            let reserve = interest * self.reserve_factor;
+            if reserve != Decimal::ZERO {
+                self.reserve_vault.put(self.vault.take(reserve));
+            }
+            interest - reserve
+        }
 
-                installment_data.period_counter += 1;
+        /// Cover the loss from the insurance reserve into the protocol's vault.
+        /// 
+        /// Return the covered amount.
+        fn cover_loss(&mut self, loss: Decimal) -> Decimal {
+            let covered = if loss > self.reserve_vault.amount() { self.reserve_vault.amount() } else { loss };
+            if covered != Decimal::ZERO {
+                self.reserve_payouts += covered;
+                self.vault.put(self.reserve_vault.take(covered));
+                self.fill_withdrawal_queue();
+                info!("The insurance reserve has covered {} stable coins of the loss.", covered);
+            }
+            covered
+        }
 
-                let current_debt = installment_data.total_loan / installment_data.period_max;
+        /// Get the current time from the protocol's time source, or directly from the protocol's oracle if the protocol doesn't use a time source.
+        fn current_time(&self) -> u64 {
+            match self.time_source {
+                Some(time_source) => {
+                    let time_source: GroundTimeSource = time_source.into();
+                    time_source.current(self.controller_badge.create_proof())
+                }
+                None => {
+                    let neuracle: NeuRacle = self.oracle.0.into();
+                    let data_proof = self.oracle.1.create_proof();
+                    let current = neuracle.get_data(data_proof);
+                    current.parse().expect("Wrong data!")
+                }
+            }
+        }
 
-                let debt_interest = current_debt * installment_data.interest_rate;
+        /// Read the time without any validation or panic, for read only methods, the circuit breakers check and resume.
+        /// 
+        /// Return the time source's last accepted time if the protocol is using a time source, None if the time can't be read.
+        fn read_time(&self) -> Option<u64> {
+            match self.time_source {
+                Some(time_source) => {
+                    let time_source: GroundTimeSource = time_source.into();
+                    match time_source.last_time() {
+                        0 => None,
+                        time => Some(time)
+                    }
+                }
+                None => {
+                    let neuracle: NeuRacle = self.oracle.0.into();
+                    let data_proof = self.oracle.1.create_proof();
+                    neuracle.get_data(data_proof).parse().ok()
+                }
+            }
+        }
 
-                let period_length = installment_data.period_length;
+        /// Check the junior tranche's ratio against the protocol's tranche policy.
+        fn check_tranche_ratio(&self) {
+            if let Some(policy) = &self.tranche_policy {
+                let (senior, junior) = self.tranche_returns_of();
+                if senior != Decimal::ZERO {
+                    assert!(junior >= (senior + junior) * policy.min_junior_ratio, "The junior tranche is below the protocol's minimum junior ratio!");
+                }
+            }
+        }
 
-                info!("You have repaid all the current period debt from your installment credit, your installment period will be advanced by 1. Current period: {}", installment_data.period_counter);
+        /// Burn the pool-share tokens and return their return amount.
+        fn burn_pool_token(&mut self, pool_token: Bucket, current: u64) -> Decimal {
 
-                data = CreditData {
-                        credit_type: CreditType::Installment(installment_data),
-                        current_debt_start_time: data.due_time,
-                        current_debt,
-                        debt_interest,
-                        due_time: data.due_time + period_length,
-                        ..data
-                    };
+            self.release_interest(current);
 
-            } else if installment_data.period_counter == installment_data.period_max {
+            let pool = self.token_pool.as_mut().unwrap();
 
-                installment_data.period_counter += 1;
+            let amount = pool.to_amount(pool_token.amount());
 
-                data = CreditData {
-                        credit_type: CreditType::Revolving(RevolvingTypes::Monthly),
-                        current_debt_start_time: 0,
-                        current_debt: Decimal::ZERO,
-                        debt_interest: Decimal::ZERO,
-                        due_time: 0,
-                        ..data
-                    };
+            pool.shares -= pool_token.amount();
 
-                info!("You have repaid all your installment loan, your credit will change into the Monthly Revolving Credit");
+            pool.value -= amount;
 
-            } else {panic!("You have already repaid all the installment credit debt. Please consider changing your credit type into revolving credit.")};
+            self.total_return -= amount;
 
-            self.controller_badge.authorize(|| { 
-                credit.update_data(Credit {
-                    data: CreditData {..data}
-                })
+            self.controller_badge.authorize(|| {
+                pool_token.burn()
             });
 
-            return (data.current_debt, data.debt_interest, credit_proof)
-
+            amount
         }
 
-        /// This method is for lending protocol to update the debt amount of an user.
-        /// ### Input: 
-        /// - credit_proof: the user's credit proof.
-        /// - protocol_proof: the protocol controller's proof.
-        /// - current_debt: new debt amount.
-        /// - extra_debt: new extra debt amount.
-        /// ### Output: 
-        /// update the credit debt amount.
-        pub fn update_debt(&self, credit_proof: Proof, protocol_proof: Proof, current_debt: Decimal, debt_interest: Decimal, extra_debt: Decimal) -> Proof {
+        /// Mint the pool-share tokens for a return amount.
+        fn mint_pool_token(&mut self, amount: Decimal, current: u64) -> Bucket {
 
-                self.check_protocol(protocol_proof);
-                let credit = credit_proof.non_fungible::<Credit>();
-                let data = credit.data().data;
-    
-                self.controller_badge.authorize(|| {
-                    credit.update_data(
-                        Credit {
-                            data: CreditData {
-                                current_debt,
-                                debt_interest,
-                                extra_debt,
-                                ..data
-                            }
-                        }
-                    )
-                });
+            self.release_interest(current);
 
-                info!("Your current debt is {}", current_debt + debt_interest + extra_debt);
+            let shares = self.token_pool.as_mut().unwrap().mint(amount);
 
-                credit_proof
-    
+            let pool_token = self.pool_token.unwrap();
+
+            self.controller_badge.authorize(|| {
+                borrow_resource_manager!(pool_token).mint(shares)
+            })
         }
 
-        /// This method is for lending protocol to update credit due time of an user.
-        /// ### Input: 
-        /// - credit_proof: the user's credit proof.
-        /// - protocol_proof: the protocol controller's proof.
-        /// - due_time: new credit loan due time
-        /// - current_debt_start_time: new credit loan start time.
-        /// ### Output: 
-        /// update the user's credit due time
-        pub fn update_debt_time(&self, credit_proof: Proof, protocol_proof: Proof, due_time: u64, current_debt_start_time: u64) -> Proof {
+        /// Remove a lender's shares from the lender's tranche and return the lender's return amount, include the released interest.
+        fn remove_shares(&mut self, lender: &mut Lender, current: u64) -> Decimal {
+            self.release_interest(current);
+            self.tranche_mut(lender.tranche).remove(lender)
+        }
 
-                self.check_protocol(protocol_proof);
-                let credit = credit_proof.non_fungible::<Credit>();
-                let data = credit.data().data;
-                self.controller_badge.authorize(|| {
-                    credit.update_data(
-                        Credit {
-                            data: CreditData {
-                                due_time,
-                                current_debt_start_time,
-                                ..data
-                            }
-                        }
-                    )
-                });
+        /// This method is for the protocol operator to allow a checkout component to take the revolving loan on behalf of borrowers.
+        /// 
+        /// Input: The checkout component's controller badge address.
+        pub fn list_checkout(&mut self, checkout_controller_address: ResourceAddress) {
+            self.checkouts.push(checkout_controller_address);
+            info!("listed the checkout component with controller badge address {}", checkout_controller_address);
+        }
 
-                if due_time != 0 {info!("Your debt will be over due in {} (unix time)", due_time);}
+        /// This method is for the protocol operator to deny a checkout component.
+        /// 
+        /// Input: The checkout component's controller badge address.
+        pub fn delist_checkout(&mut self, checkout_controller_address: ResourceAddress) {
+            match self.checkouts.iter().position(|x| *x == checkout_controller_address) {
+                None => {info!("Doesn't have this checkout component on the list.")}
+                Some(x) => {
+                    self.checkouts.remove(x);
+                    info!("delisted the checkout component with controller badge address {}", checkout_controller_address);
+                }
+            }
+        }
 
-                credit_proof
-    
+        /// This method is to check if the checkout component is listed or not.
+        /// 
+        /// Input: The checkout component's controller badge proof.
+        pub fn check_checkout(&self, checkout_proof: Proof) {
+            assert!(self.checkouts.contains(&checkout_proof.resource_address()), "This checkout component is not allowed to use the protocol.");
+            checkout_proof.drop();
         }
 
-        /// This method is for lending protocol to degrade credit score of an user.
+        /// This method is for the protocol's guardian to pause an action on the protocol.
+        /// 
+        /// Repayments stay open while borrowing is paused, unless the guardian pause the repay action separately.
         /// ### Input: 
-        /// - credit_proof: the user's credit proof.
-        /// - protocol_proof: the protocol controller's proof.
-        /// ### Output: 
-        /// degrade the credit score
-        pub fn degrade_credit(&self, credit_proof: Proof, protocol_proof: Proof) -> Proof {
-
-            self.check_protocol(protocol_proof);
-            let credit = credit_proof.non_fungible::<Credit>();
-            let old_data = credit.data().data;
-
-            let credit_service: GroundCredit = self.credit_service.into();
-
-            let credit_scoring_rates = credit_service.credit_scoring_rate();
-
-            let score = old_data.credit_score;
-
-            let degrade_rate = match old_data.credit_type {
+        /// - guardian_proof: the guardian badge proof.
+        /// - action: the action to pause.
+        pub fn pause(&mut self, guardian_proof: Proof, action: LendingAction) {
+            self.check_guardian(guardian_proof);
+            if !self.paused.contains(&action) {
+                self.paused.push(action);
+            }
+            info!("The {:?} action has been paused on the protocol.", action);
+        }
 
-                CreditType::Revolving(ref types) => {
+        /// This method is for the protocol's guardian to resume a paused action on the protocol.
+        /// 
+        /// The circuit breakers' checkpoint is also reset to the current oracle time and vault amount, 
+        /// the checkpoint keeps its last time if the oracle time can't be read so the guardian can always resume the protocol.
+        /// ### Input: 
+        /// - guardian_proof: the guardian badge proof.
+        /// - action: the action to resume.
+        pub fn resume(&mut self, guardian_proof: Proof, action: LendingAction) {
+            self.check_guardian(guardian_proof);
+            self.paused.retain(|paused| *paused != action);
+            let time = self.read_time().unwrap_or(self.breaker_checkpoint.1);
+            self.breaker_checkpoint = (Runtime::current_epoch(), time, self.breaker_vault());
+            info!("The {:?} action has been resumed on the protocol.", action);
+        }
 
-                    match types {
+        /// This method is to check the guardian badge.
+        /// 
+        /// Input: The guardian badge proof.
+        fn check_guardian(&self, guardian_proof: Proof) {
+            assert!(Some(guardian_proof.resource_address()) == self.guardian, "Only the protocol's guardian can use this method.");
+            guardian_proof.drop();
+        }
 
-                        RevolvingTypes::Monthly => {credit_scoring_rates.monthly.degrade_rate}
-                        RevolvingTypes::Yearly => {credit_scoring_rates.yearly.degrade_rate}
+        /// This method is for anyone (a keeper) to check the protocol's circuit breakers.
+        /// 
+        /// When an anomaly is found, the breakers trip and pause borrowing and withdrawal on the protocol until the guardian resume them.
+        /// 
+        /// The method never panic on a bad oracle time so the trip is persisted, an unreadable oracle time also trip the breakers.
+        /// The vault drop is checked against the vault amount at the start of the ledger epoch, 
+        /// this catch the outflows that aren't rate limited (withdrawal queue fills,...).
+        /// 
+        /// Output: the breakers tripped or not.
+        pub fn check_circuit_breakers(&mut self) -> bool {
 
-                    }
-                    
-                }
+            if self.circuit_breaker.is_none() {
+                return false
+            }
 
-                _ => {credit_scoring_rates.monthly.degrade_rate}
+            let current = self.read_time();
 
+            let anomaly = match current {
+                None => Some(String::from("The oracle time can't be read.")),
+                Some(current) => self.breaker_anomaly(current, Decimal::ZERO)
             };
 
-            let new_score = if score >= degrade_rate {
-                score - degrade_rate
-            } else { Decimal::zero() };
-
-            self.controller_badge
-            .authorize(|| { 
-                credit.update_data(
-                    Credit {
-                        data: CreditData {
-                            credit_score: new_score,
-                            ..old_data
+            match anomaly {
+                Some(anomaly) => {
+                    for action in [LendingAction::Borrow, LendingAction::Withdraw].iter() {
+                        if !self.paused.contains(action) {
+                            self.paused.push(*action);
                         }
                     }
-                )
-            });
+                    info!("The circuit breakers have tripped: {} Borrowing and withdrawal have been paused.", anomaly);
+                    true
+                }
+                None => {
+                    self.update_breaker_checkpoint(current.unwrap());
+                    false
+                }
+            }
 
-            info!("Your credit score has been degraded to {} because of late repayment", new_score);
+        }
 
-            credit_proof
+        /// Read only method to get the protocol's paused actions.
+        pub fn get_paused_actions(&self) -> Vec<LendingAction> {
+            self.paused.clone()
+        }
 
+        /// Check the action isn't paused on the protocol.
+        fn assert_active(&self, action: LendingAction) {
+            assert!(!self.paused.contains(&action), "The {:?} action is currently paused on the protocol, please come back later.", action);
         }
-    
-        /// This method is for lending protocol to update repaid data and restore credit score of an user (if passed the maximum credit amount).
-        /// ### Input: 
-        /// - id_proof: the user's ID proof.
-        /// - credit_proof: the user's credit proof.
-        /// - protocol_proof: the protocol controller's proof.
-        /// - amount: token amount the user has repaid.
-        /// ### Output: 
-        /// update repaid data and restore the credit score (if passed the maximum credit amount).
+
+        /// Check the action isn't paused and the circuit breakers before taking an amount out of the protocol's vault.
         /// 
-        /// Notice: This will not work with restrictive proof
-        pub fn update_revolving_credit_repaid_accumulate(&self, id_proof: Proof, credit_proof: Proof, protocol_proof: Proof, amount: Decimal) -> (Proof, Proof) {
+        /// The outflow is rejected on an anomaly, this doesn't pause the protocol since the rejection is reverted, 
+        /// keepers persist the trip through the "check_circuit_breakers" method.
+        fn check_outflow(&mut self, action: LendingAction, amount: Decimal) {
 
-            self.check_protocol(protocol_proof);
+            self.assert_active(action);
 
-            let credit_service: GroundCredit = self.credit_service.into();
+            if self.circuit_breaker.is_none() {
+                return
+            }
 
-            let credit_scoring_rates = credit_service.credit_scoring_rate();
+            let current = self.current_time();
 
-            let id_data = id_proof.non_fungible::<Identity>().data().data;
+            if let Some(anomaly) = self.breaker_anomaly(current, amount) {
+                panic!("{} Please come back later.", anomaly)
+            }
 
-            let credit = credit_proof.non_fungible::<Credit>();
+            self.update_breaker_checkpoint(current);
 
-            let old_data = credit.data().data;
+        }
 
-            let credit_service: GroundCredit = self.credit_service.into();
+        /// Find the anomaly that would trip the circuit breakers if an amount is taken out of the protocol's vault.
+        fn breaker_anomaly(&self, current: u64, amount: Decimal) -> Option<String> {
 
-            let (maximum, _) = credit_service.get_revolving_credit_amount_by_data(id_data, old_data);
+            let breaker = self.circuit_breaker.as_ref()?;
 
-            let new_accumulated = old_data.repaid_amount_accumulated + amount;
+            let (epoch, time, vault) = self.breaker_checkpoint;
 
-            if new_accumulated >= maximum {
+            if time != 0 {
 
-                let restore_rate = match old_data.credit_type {
-                    CreditType::Revolving(ref types) => {
+                if current < time {
+                    return Some(format!("The oracle time moved backward from {} to {}.", time, current))
+                }
 
-                        match types {
-    
-                            RevolvingTypes::Monthly => {credit_scoring_rates.monthly.restore_rate}
-                            RevolvingTypes::Yearly => {credit_scoring_rates.yearly.restore_rate}
-    
-                        }
-                        
-                    }
+                let epochs = Runtime::current_epoch() - epoch + 1;
 
-                    _ => {panic!("Wrong credit type!")}
-    
-                };
+                if current - time > breaker.max_time_jump * epochs {
+                    return Some(format!("The oracle time jumped {} seconds in {} ledger epochs.", current - time, epochs))
+                }
 
-                let score = old_data.credit_score;
+            }
 
-                let new_score = if score <= (dec!("100") - restore_rate) {
-                    score + restore_rate
-                } else { dec!("100") };
+            let current_vault = self.breaker_vault();
 
-                self.controller_badge
-                .authorize(|| { 
-                    credit.update_data(
-                        Credit {
-                            data: CreditData {
-                                credit_score: new_score,
-                                repaid_amount_accumulated: Decimal::zero(),
-                                ..old_data
-                            }
-                        }
-                    )
-                });
+            let vault = if epoch == Runtime::current_epoch() { vault } else { current_vault };
 
-                info!("Your credit score has been restored to {} because of your on-time repayment frequency", new_score);
+            if vault != Decimal::ZERO && current_vault - amount < vault * (Decimal::ONE - breaker.max_vault_drop) {
+                return Some(format!("The protocol's vault would drop more than {}% in this ledger epoch.", breaker.max_vault_drop * dec!("100")))
+            }
 
-            } else {
-                self.controller_badge
-                .authorize(|| { 
-                    credit.update_data(
-                        Credit {
-                            data: CreditData {
-                                repaid_amount_accumulated: new_accumulated,
-                                ..old_data
-                            }
-                        }
-                    )
-                });
+            None
+
+        }
+
+        /// Roll the circuit breakers' checkpoint into the current ledger epoch.
+        fn update_breaker_checkpoint(&mut self, current: u64) {
+            let epoch = Runtime::current_epoch();
+            if self.breaker_checkpoint.1 == 0 || epoch != self.breaker_checkpoint.0 {
+                self.breaker_checkpoint = (epoch, current, self.breaker_vault())
             }
+        }
 
-            (id_proof, credit_proof)
-            
+        /// The protocol's vault amount counted by the circuit breakers.
+        /// 
+        /// The outstanding flash loans are counted back since they must be repaid in the same transaction, 
+        /// so a flash loan can't be used to move the breakers' checkpoint or to hide an outflow from them.
+        fn breaker_vault(&self) -> Decimal {
+            self.vault.amount() + self.outstanding_flash_loans
         }
 
         /// This method is for the protocol operator to make this protocol run by a DAO
@@ -1560,27 +5397,71 @@ blueprint! {
 
         /// This method is for any volunteer or the DAO to deposit a bucket into the protocol's vault to support the protocol in case of loan default.
         pub fn deposit(&mut self, bucket: Bucket) {
-            self.vault.put(bucket)
+            self.vault.put(bucket);
+            self.fill_withdrawal_queue()
+        }
+
+        /// This method is for any volunteer or the DAO to deposit a bucket into the protocol's insurance reserve.
+        pub fn deposit_reserve(&mut self, bucket: Bucket) {
+            self.reserve_vault.put(bucket)
+        }
+
+        /// Read only method to get the insurance reserve's coverage.
+        /// 
+        /// Output: (reserve amount, outstanding loans, coverage ratio (%))
+        /// 
+        /// ```coverage_ratio = reserve / outstanding_loans```
+        pub fn reserve_coverage(&self) -> (Decimal, Decimal, Decimal) {
+            let reserve = self.reserve_vault.amount();
+            let outstanding = if self.total_return > self.vault.amount() { self.total_return - self.vault.amount() } else { Decimal::ZERO };
+            let coverage = if outstanding == Decimal::ZERO { Decimal::ZERO } else { reserve / outstanding * dec!("100") };
+            (reserve, outstanding, coverage)
+        }
+
+        /// Read only method to get the DAO's compensations which haven't been repaid by recoveries from defaulted loans.
+        pub fn dao_payouts(&self) -> Decimal {
+            self.dao_payouts
         }
 
         /// This method is for lenders to take their compensation from the DAO treasury in the worst case of cooperated loan default.
         pub fn compensate(&mut self, lender_bucket: Bucket) -> Bucket {
 
+            self.assert_active(LendingAction::Compensate);
+
             match self.dao {
                 None => {panic!("The protocol isn't run by a DAO, please consider contact the protocol operator!")}
                 Some(dao) => {
 
                     let dao: GroundBusinessDAO = dao.into();
 
+                    let mut compensate = Decimal::ZERO;
+
+                    let current = self.current_time();
+
+                    if Some(lender_bucket.resource_address()) == self.pool_token {
+
+                        let amount = self.burn_pool_token(lender_bucket, current);
+
+                        compensate = amount * self.compensate_rate;
+
+                        let bucket = dao.compensate(self.controller_badge.create_proof(), compensate);
+
+                        info!("You have been compensated {} stable coins from the DAO operated the protocol.", compensate);
+
+                        return bucket
+                    }
+
                     assert!(lender_bucket.resource_address() == self.account_nft, "Wrong resource");
 
                     let lender_accounts = lender_bucket.non_fungibles::<Account>();
 
-                    let mut compensate = Decimal::ZERO;
-
                     for account in lender_accounts {
 
-                        let amount = self.lenders.remove(&account.id()).unwrap().lending_amount;
+                        let mut lender = self.lenders.remove(&account.id()).unwrap();
+
+                        assert!(lender.delegated == Decimal::ZERO, "You have to revoke all your credit delegations first.");
+
+                        let amount = self.remove_shares(&mut lender, current);
 
                         compensate += amount * self.compensate_rate;
                         
@@ -1601,24 +5482,147 @@ blueprint! {
             }
         }
 
-        pub fn change_interest_rates(&mut self, interest_rates: RevolvingCreditInterestRates) {
+        /// Read only method to get the protocol's current interest rate curve and quotes.
+        /// ### Input: 
+        /// - amount: the quoted borrow amount (zero to get the current rates).
+        /// ### Output: 
+        /// The interest rate quote after the borrow amount is taken from the protocol's vault.
+        pub fn quote_interest_rates(&self, amount: Decimal) -> InterestRateQuote {
+            InterestRateQuote {
+                utilization: self.utilization(amount) * dec!("100"),
+                model: self.interest_rate_model.as_ref().map(|model| InterestRateModel {
+                    optimal_utilization: model.optimal_utilization * dec!("100"),
+                    slope: model.slope * dec!("100"),
+                    jump_slope: model.jump_slope * dec!("100")
+                }),
+                monthly: self.borrow_rate(self.interest_rates.monthly.interest_rate, amount) * dec!("100"),
+                yearly: self.borrow_rate(self.interest_rates.yearly.interest_rate, amount) * dec!("100")
+            }
+        }
+
+        /// Get the pool utilization after the borrow amount is taken from the protocol's vault.
+        fn utilization(&self, amount: Decimal) -> Decimal {
+
+            if self.total_return == Decimal::ZERO { return Decimal::ZERO }
+
+            let remain = self.vault.amount() - amount;
+
+            if remain >= self.total_return {
+                Decimal::ZERO
+            } else if remain <= Decimal::ZERO {
+                Decimal::ONE
+            } else {
+                (self.total_return - remain) / self.total_return
+            }
+        }
+
+        /// Get the borrow rate of a product from its base rate, the static base rate is used if the protocol doesn't have an interest rate model.
+        fn borrow_rate(&self, base_rate: Decimal, amount: Decimal) -> Decimal {
+            match &self.interest_rate_model {
+                None => base_rate,
+                Some(model) => model.rate(base_rate, self.utilization(amount))
+            }
+        }
+
+        pub fn change_interest_rate_model(&mut self, interest_rate_model: Option<InterestRateModel>) {
+            self.interest_rate_model = interest_rate_model.map(|mut model| {
+                model.check_model();
+                model.model_aggregrate();
+                model
+            })
+        }
+
+        pub fn change_interest_rates(&mut self, mut interest_rates: RevolvingCreditInterestRates) {
+            interest_rates.check_rates();
+            interest_rates.rates_aggregrate();
             self.interest_rates = interest_rates
         }
 
         pub fn change_fee(&mut self, fee: Decimal) {
-            self.fee = fee
+            assert_rate(fee);
+            self.fee = fee / dec!("100")
         }
 
         pub fn change_tolerance_threshold(&mut self, tolerance_threshold: Decimal) {
-            self.tolerance_threshold = tolerance_threshold
+            assert_rate(tolerance_threshold);
+            self.tolerance_threshold = tolerance_threshold / dec!("100")
         }
 
         pub fn change_compensate_rate(&mut self, compensate_rate: Decimal) {
-            self.compensate_rate = compensate_rate
+            assert_rate(compensate_rate);
+            self.compensate_rate = compensate_rate / dec!("100")
+        }
+
+        pub fn change_default_policy(&mut self, default_policy: DefaultPolicy) {
+            default_policy.check_policy();
+            self.default_policy = default_policy
+        }
+
+        pub fn change_reserve_factor(&mut self, reserve_factor: Decimal) {
+            assert_rate(reserve_factor);
+            self.reserve_factor = reserve_factor / dec!("100")
+        }
+
+        /// The new bonuses apply to the lending accounts created after the change.
+        pub fn change_term_bonuses(&mut self, mut term_bonuses: TermBonuses) {
+            term_bonuses.check_bonuses();
+            term_bonuses.bonuses_aggregrate();
+            self.term_bonuses = term_bonuses
+        }
+
+        pub fn change_flash_loan_fee(&mut self, flash_loan_fee: Decimal) {
+            assert_rate(flash_loan_fee);
+            self.flash_loan_fee = flash_loan_fee / dec!("100")
+        }
+
+        pub fn change_guardian(&mut self, guardian: ResourceAddress) {
+            self.guardian = Some(guardian)
+        }
+
+        pub fn change_circuit_breaker(&mut self, circuit_breaker: Option<CircuitBreaker>) {
+            self.circuit_breaker = circuit_breaker.map(|mut breaker| {
+                breaker.check_breaker();
+                breaker.breaker_aggregrate();
+                breaker
+            });
+            self.breaker_checkpoint = (0, 0, Decimal::ZERO)
+        }
+
+        pub fn change_exposure_limits(&mut self, exposure_limits: Option<ExposureLimits>) {
+            self.exposure_limits = exposure_limits.map(|mut limits| {
+                limits.check_limits();
+                limits.limits_aggregrate();
+                limits
+            })
+        }
+
+        pub fn change_tranche_policy(&mut self, tranche_policy: Option<TranchePolicy>) {
+            let current = self.current_time();
+            self.release_interest(current);
+            self.tranche_policy = tranche_policy.map(|mut policy| {
+                policy.check_policy();
+                policy.policy_aggregrate();
+                policy
+            })
         }
 
         pub fn withdraw_fee(&mut self) -> Bucket {
             self.fee_vault.take_all()
         }
+
+        pub fn change_asset_interest_rates(&mut self, asset: ResourceAddress, mut interest_rates: RevolvingCreditInterestRates) {
+            interest_rates.check_rates();
+            interest_rates.rates_aggregrate();
+            self.asset_markets.get_mut(&asset).expect("The asset is not listed on the protocol.").interest_rates = interest_rates
+        }
+
+        pub fn change_peg_policy(&mut self, asset: ResourceAddress, peg_policy: PegPolicy) {
+            peg_policy.check_policy();
+            self.asset_markets.get_mut(&asset).expect("The asset is not listed on the protocol.").peg_policy = peg_policy
+        }
+
+        pub fn withdraw_asset_fee(&mut self, asset: ResourceAddress) -> Bucket {
+            self.asset_markets.get_mut(&asset).expect("The asset is not listed on the protocol.").fee_vault.take_all()
+        }
     }
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 //! # Ground Finance: Make a Finance Ground for your journey into Web 3
 //! 
-//! Ground Finance is a blueprint package with 2 main usecases: on-chain credit service; on-chain lending protocol.
+//! Ground Finance is a blueprint package with 3 main usecases: on-chain credit service; on-chain lending protocol; on-chain "buy now, pay later" checkout.
 //! 
 //! ## Current on-chain problem of uncollateral lending protocols:
 //! 
@@ -16,4 +16,5 @@
 
 mod utils;
 pub mod ground_credit;
-pub mod ground_lending;
\ No newline at end of file
+pub mod ground_lending;
+pub mod ground_checkout;
\ No newline at end of file
@@ -19,4 +19,28 @@ pub fn expo(rate: Decimal, number: u8) -> Decimal {
 
     result
 
-}
\ No newline at end of file
+}
+
+/// Same as [expo] but stop compounding once the result reach the cap.
+pub fn expo_capped(rate: Decimal, number: u8, cap: Decimal) -> Decimal {
+
+    let mut counter = 0;
+
+    let mut result = Decimal::ONE;
+
+    while counter != number && result < cap {
+
+        counter += 1;
+        result *= Decimal::ONE + rate;
+
+    }
+
+    if result > cap { cap } else { result }
+
+}
+
+/// Get the number of started periods in the elapsed time, capped at 255 periods.
+pub fn started_periods(elapsed: u64, period_length: u64) -> u8 {
+    let periods = elapsed / period_length + if elapsed % period_length == 0 { 0 } else { 1 };
+    if periods > u8::MAX as u64 { u8::MAX } else { periods as u8 }
+}
@@ -386,5 +386,10 @@ blueprint! {
         pub fn check_resource(&self, id: ResourceAddress) {
             assert!(id == self.identity_sbt, "Wrong resource!")
         }
+
+        /// Read only method to get the Identity SBT resource address.
+        pub fn identity_sbt_address(&self) -> ResourceAddress {
+            self.identity_sbt
+        }
     }
 }
\ No newline at end of file
@@ -1,8 +1,11 @@
 use scrypto::prelude::*;
 use neuracle::{neuracle::*, validator::*};
 use ground_id::*;
-use ground_business::{ground_business_dao::*, ground_cv::*};
-use ground_finance::{ground_credit::*, ground_lending::*};
+use ground_business::{ground_business_dao::*, ground_cv::*, ground_clock::*, ground_time_source::*};
+use ground_finance::{ground_credit::*, ground_lending::*, ground_checkout::*};
+
+/// The NeuRacle api of the test stablecoin market's peg feed, the peg is always 1 primary stable coin.
+const PEG_API: &str = "https://test.ground.finance/peg";
 
 blueprint! {
     struct GroundTestEngine {
@@ -10,6 +13,7 @@ blueprint! {
         dao_badge: Vault,
         dao_share_token: Vault,
         stable_coin: Vault,
+        asset_coin: Vault,
         admin_badge: Vault,
         neura: Vault,
         neuracle: ComponentAddress,
@@ -17,7 +21,10 @@ blueprint! {
         ground_credit: ComponentAddress,
         ground_cv: ComponentAddress,
         ground_lending: Option<ComponentAddress>,
+        ground_lending_controller: Option<ResourceAddress>,
+        time_source: Option<(ComponentAddress, ComponentAddress)>,
         dao: Option<ComponentAddress>,
+        dao_member: Option<(ResourceAddress, ResourceAddress)>,
         validators: Vec<(ComponentAddress, Vault)>,
         stakers: Vec<Vault>,
         etc_vault: LazyMap<ResourceAddress, Vault>,
@@ -153,12 +160,18 @@ blueprint! {
                 admin_badge.resource_address()
                 
             );
+
+            let asset_coin: Bucket = ResourceBuilder::new_fungible()
+                .metadata("name", "Test Asset Stable Coin")
+                .metadata("symbol", "TAS")
+                .initial_supply(120000000isize);
                 
             Self {
 
                 dao_badge: Vault::with_bucket(dao_badge),
                 dao_share_token: Vault::with_bucket(dao_share_token),
                 stable_coin: Vault::with_bucket(stable_coin),
+                asset_coin: Vault::with_bucket(asset_coin),
                 admin_badge: Vault::with_bucket(admin_badge),
                 neura: Vault::with_bucket(token_bucket),
                 neuracle: neuracle,
@@ -166,7 +179,10 @@ blueprint! {
                 ground_credit: ground_credit,
                 ground_cv: ground_cv,
                 ground_lending: None,
+                ground_lending_controller: None,
+                time_source: None,
                 dao: None,
+                dao_member: None,
                 validators: Vec::new(),
                 stakers: Vec::new(),
                 etc_vault: LazyMap::new()
@@ -182,7 +198,7 @@ blueprint! {
         /// ### Create new validator node to validate data into the NeuRacle. 
         /// In practice, this should done by many users but for a simple test, we just get one validator node on this test component.
         /// 
-        /// ### Call the "become_new_user" method on the NeuRacle and get 2 unix time badges
+        /// ### Call the "become_new_user" method on the NeuRacle and get 3 unix time badges
         /// 
         /// ### Create new Ground Lending test component with the follow params:
         /// - name: "Ground Lending Test Component"
@@ -195,8 +211,13 @@ blueprint! {
         /// - oracle: The oracle component address and the unix time oracle badge.
         /// - dao: None > will update through the lending protocol's method later.
         /// - compensate_rate: 50%
+        /// - default_policy: a loan can be declared defaulted after 3 missed repayment periods or 90 days overdue, the protocol will automatically take the compensation from the DAO.
+        /// 
+        /// ### List the test asset stable coin market on the lending protocol with the same interest rates, the peg feed always return 1 primary stable coin.
+        /// 
+        /// ### Appoint the test component admin badge as the lending protocol's guardian.
         /// 
-        /// ### List the lending protocol on the ground credit component.
+        /// ### List the lending protocol on the ground credit component and let the ground credit component use the unix time oracle.
         /// 
         /// ### Create new DAO test component with the follow params:
         /// - name: "Test DAO component"
@@ -261,20 +282,67 @@ blueprint! {
                 self.ground_credit,
                 (self.neuracle, unix_time_badge),
                 None,
-                dec!("50")
+                dec!("50"),
+                DefaultPolicy {
+                    missed_periods: 3,
+                    overdue_days: 90,
+                    auto_compensate: true
+                }
             );
 
             info!("Ground Lending test component address: {}", ground_lending);
 
             self.ground_lending = Some(ground_lending);
 
+            self.ground_lending_controller = Some(ground_lending_controller);
+
+            let (peg_badge, repayment) = neuracle.become_new_user(self.neura.take(dec!("1000000")), String::from(PEG_API));
+
+            self.neura.put(repayment);
+
+            let asset_interest_rates = RevolvingCreditInterestRates {
+                yearly: Interest {
+                    interest_rate: dec!("10"),
+                    interest_rate_late: dec!("15")
+                },
+                monthly: Interest {
+                    interest_rate: dec!("0.5"),
+                    interest_rate_late: dec!("2")
+                },
+            };
+
+            ComponentAuthZone::push(self.dao_badge.create_proof());
+
+            let lending: GroundLending = ground_lending.into();
+
+            lending.list_asset(self.asset_coin.resource_address(), asset_interest_rates, peg_badge, PegPolicy {
+                min_peg: dec!("0.9"),
+                max_peg: dec!("1.1"),
+                max_age: 86400
+            });
+
+            lending.change_guardian(self.admin_badge.resource_address());
+
+            ComponentAuthZone::pop().drop();
+
             let (unix_time_badge2, repayment) = neuracle.become_new_user(self.neura.take(dec!("1000000")), String::from("https://showcase.api.linx.twenty57.net/UnixTime/tounix?date=now"));
 
             self.neura.put(repayment);
 
+            let (unix_time_badge3, repayment) = neuracle.become_new_user(self.neura.take(dec!("1000000")), String::from("https://showcase.api.linx.twenty57.net/UnixTime/tounix?date=now"));
+
+            self.neura.put(repayment);
+
             let ground_credit: GroundCredit = self.ground_credit.into();
 
-            ground_credit.list_protocol(ground_lending_controller);
+            ground_credit.list_protocol(ground_lending_controller, ProtocolCapabilities {
+                revolving: true,
+                installment: true,
+                may_degrade: true,
+                exposure_cap: dec!("10000000")
+            });
+
+            ground_credit.use_oracle(self.neuracle, unix_time_badge3);
 
             let mut entry_requirement = HashMap::new();
             entry_requirement.insert(3011, 5);
@@ -326,6 +394,13 @@ blueprint! {
         /// 
         /// ***This is just for test purpose and totally not the practial use of these packages!***
         pub fn update_neuracle_data(&mut self, data: String) {
+            self.update_neuracle_data_with_peg_time(data.clone(), data)
+        }
+
+        /// Same as update_neuracle_data but the peg feed quotes its peg at the given unix time, to test stale peg quotes.
+        /// 
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn update_neuracle_data_with_peg_time(&mut self, data: String, peg_time: String) {
 
             let unix_time: u64 = data.parse().expect("Wrong data!");
 
@@ -335,7 +410,8 @@ blueprint! {
             let mut datas = BTreeMap::new();
 
             for api in apis {
-                datas.insert(api, data.clone());
+                let api_data = if api == PEG_API { format!("1,{}", peg_time) } else { data.clone() };
+                datas.insert(api, api_data);
             }
 
             for (address, badge) in &self.validators {
@@ -372,27 +448,260 @@ blueprint! {
         }
 
 
-        /// The method will get all resource of a new DAO member and go through a Ground Business Dao's 
-        /// concept consensus to let the lending protocol use the dao treasury.
+        /// The method will go through a Ground Business Dao's concept consensus to let the lending protocol use the dao treasury.
         ///
         /// ***This is just for test purpose and totally not the practial use of these packages!***
         pub fn lending_use_dao(&mut self) {
 
-            let ([id_sbt, credit_sbt, stable_coin, dao_share_token, cv, member_sbt], move_proof) = self.init_dao_member(8u8);
+            let dao_address = self.dao.unwrap();
 
-            self.stable_coin.put(stable_coin); self.dao_share_token.put(dao_share_token);
+            self.dao_concept(self.ground_lending.unwrap(), String::from("use_dao"), vec![scrypto_encode(&dao_address)]);
 
-            self.deposit(id_sbt); self.deposit(credit_sbt);
+        }
 
-            let dao_address = self.dao.unwrap();
+        /// The method will go through a Ground Business Dao's concept consensus to change the lending protocol's circuit breakers.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn change_circuit_breaker(&mut self, circuit_breaker: Option<CircuitBreaker>) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("change_circuit_breaker"), vec![scrypto_encode(&circuit_breaker)]);
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to change the lending protocol's revolving credit interest rates.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn change_interest_rates(&mut self, interest_rates: RevolvingCreditInterestRates) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("change_interest_rates"), vec![scrypto_encode(&interest_rates)]);
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to change the lending protocol's interest rate model.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn change_interest_rate_model(&mut self, interest_rate_model: Option<InterestRateModel>) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("change_interest_rate_model"), vec![scrypto_encode(&interest_rate_model)]);
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to change the lending protocol's insurance reserve factor.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn change_reserve_factor(&mut self, reserve_factor: Decimal) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("change_reserve_factor"), vec![scrypto_encode(&reserve_factor)]);
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to package a defaulted position on the lending protocol as a debt claim and auction it.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn auction_debt_claim(&mut self, id: NonFungibleId, start_price: Decimal, floor_price: Decimal, duration: u64) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("auction_debt_claim"), vec![scrypto_encode(&id), scrypto_encode(&start_price), scrypto_encode(&floor_price), scrypto_encode(&duration)]);
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to change the lending protocol's exposure limits.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn change_exposure_limits(&mut self, exposure_limits: Option<ExposureLimits>) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("change_exposure_limits"), vec![scrypto_encode(&exposure_limits)]);
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to change the lending protocol's term deposit bonuses.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn change_term_bonuses(&mut self, term_bonuses: TermBonuses) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("change_term_bonuses"), vec![scrypto_encode(&term_bonuses)]);
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to let the lending protocol use the pool token mode.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn lending_use_pool_token(&mut self) {
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("use_pool_token"), vec![]);
+
+        }
+
+        /// The method will create a new GroundClock component and a GroundTimeSource component reading from the clock 
+        /// with the test component admin badge as the operator badge, 
+        /// then go through a Ground Business Dao's concept consensus to let the lending protocol use the time source.
+        /// 
+        /// The time source policy: the time can stay unchanged for 1000 ledger epochs and move forward 1 day per ledger epoch.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn init_time_source(&mut self, time: u64) -> ComponentAddress {
+
+            let clock = GroundClock::new(self.admin_badge.resource_address(), time);
+
+            let time_source_address = GroundTimeSource::new_clock(self.admin_badge.resource_address(), clock, TimePolicy {
+                max_stale_epochs: 1000,
+                max_forward_per_epoch: 86400
+            }, time);
+
+            info!("Ground Time Source test component address: {}", time_source_address);
+
+            ComponentAuthZone::push(self.admin_badge.create_proof());
+
+            let time_source: GroundTimeSource = time_source_address.into();
+            time_source.add_protocol(self.ground_lending_controller.unwrap());
+
+            ComponentAuthZone::pop().drop();
+
+            self.time_source = Some((clock, time_source_address));
+
+            self.dao_concept(self.ground_lending.unwrap(), String::from("use_time_source"), vec![scrypto_encode(&Some(time_source_address))]);
+
+            time_source_address
+
+        }
+
+        /// The method will set the test clock's time.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn set_clock_time(&self, time: u64) {
+
+            ComponentAuthZone::push(self.admin_badge.create_proof());
+
+            let clock: GroundClock = self.time_source.unwrap().0.into();
+            clock.set_time(time);
+
+            ComponentAuthZone::pop().drop();
+
+        }
+
+        /// The method will reset the test time source's last accepted time.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn reset_time_source(&self, time: u64) {
+
+            ComponentAuthZone::push(self.admin_badge.create_proof());
+
+            let time_source: GroundTimeSource = self.time_source.unwrap().1.into();
+            time_source.reset(time);
+
+            ComponentAuthZone::pop().drop();
+
+        }
+
+        /// The method will pause or resume an action on the lending protocol with the test component admin badge as the guardian badge.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn guard(&self, action: LendingAction, pause: bool) {
+
+            let lending: GroundLending = self.ground_lending.unwrap().into();
+
+            if pause {
+                lending.pause(self.admin_badge.create_proof(), action)
+            } else {
+                lending.resume(self.admin_badge.create_proof(), action)
+            }
+
+        }
+
+        /// The method will create a new GroundCheckout component with the test component admin badge as the checkout operator badge 
+        /// and go through a Ground Business Dao's concept consensus to list the checkout on the lending protocol.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn init_checkout(&mut self) -> ComponentAddress {
 
             let lending_address = self.ground_lending.unwrap();
 
-            let dao: GroundBusinessDAO = dao_address.into();
+            let (checkout, checkout_controller) = GroundCheckout::new(
+                String::from("Test Organization"),
+                self.admin_badge.resource_address(),
+                lending_address,
+                self.stable_coin.resource_address()
+            );
+
+            info!("Ground Checkout test component address: {}", checkout);
+
+            self.dao_concept(lending_address, String::from("list_checkout"), vec![scrypto_encode(&checkout_controller)]);
+
+            checkout
+
+        }
+
+        /// The method will register a new merchant on the checkout component.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn register_merchant(&self, checkout: ComponentAddress, name: String) -> Bucket {
+
+            let proof = self.admin_badge.create_proof();
+
+            ComponentAuthZone::push(proof);
+
+            let checkout: GroundCheckout = checkout.into();
+            let merchant_badge = checkout.register_merchant(name);
+
+            ComponentAuthZone::pop().drop();
+
+            merchant_badge
+
+        }
+
+        /// The method will mint a forged Identity SBT with the same ID and data of an issued one but from another resource.
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn forge_identity(&self, id: NonFungibleId) -> Bucket {
+
+            ResourceBuilder::new_non_fungible()
+                .metadata("name", "Forged Identity SBT")
+                .initial_supply([(id, Identity {
+                    data: IdentityData {
+                        identity: IdentityType::Person,
+                        income: dec!("100000"),
+                        trust_factor: dec!("100")
+                    }
+                })])
+
+        }
+
+        /// The method will go through a Ground Business Dao's concept consensus to call a method with the DAO badge.
+        /// 
+        /// The first call will get all resource of a new DAO member, the member is kept on the test component and reused for later concepts 
+        /// so the member alone always hold enough voting power (if there's no other DAO member or delegator yet).
+        ///
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn dao_concept(&mut self, component: ComponentAddress, method: String, args: Vec<Vec<u8>>) {
+
+            if self.dao_member.is_none() {
+
+                let ([id_sbt, credit_sbt, stable_coin, dao_share_token, cv, member_sbt], move_proof) = self.init_dao_member(8u8);
+
+                self.stable_coin.put(stable_coin); self.dao_share_token.put(dao_share_token);
+
+                self.deposit(id_sbt); self.deposit(credit_sbt);
+
+                self.dao_member = Some((member_sbt.resource_address(), cv.resource_address()));
+
+                ComponentAuthZone::push(move_proof);
+
+                self.deposit(member_sbt); self.deposit(cv); 
+
+                ComponentAuthZone::pop().drop();
+
+            }
+
+            let (member_sbt, cv) = self.dao_member.unwrap();
+
+            let member_sbt = self.etc_vault.get(&member_sbt).unwrap();
+
+            let cv = self.etc_vault.get(&cv).unwrap();
+
+            let dao: GroundBusinessDAO = self.dao.unwrap().into();
 
             ComponentAuthZone::push(member_sbt.create_proof());
 
-            let propose_badge = dao.propose_concept(cv.create_proof(), Methods { methods: vec![Method {component: lending_address, method: String::from("use_dao"), args: vec![scrypto_encode(&dao_address)] }]}, dec!("100"));
+            let propose_badge = dao.propose_concept(cv.create_proof(), Methods { methods: vec![Method {component, method, args}]}, dec!("100"));
 
             ComponentAuthZone::pop().drop();
 
@@ -404,12 +713,6 @@ blueprint! {
 
             self.deposit(return_reward.unwrap());
 
-            ComponentAuthZone::push(move_proof);
-
-            self.deposit(member_sbt); self.deposit(cv); 
-
-            ComponentAuthZone::pop().drop();
-
         }
 
         /// The method will get all the needed resources needed to become a DAO member and test all the Ground Packages. The resources included:
@@ -464,9 +767,10 @@ blueprint! {
         /// - 100 dao share tokens
         /// - CV component SBTs: COMMUNICATION (skill id 14) at level 3 and SCRYPTO_PROGRAMMING (skill id 2) at level 2.
         /// - The DAO's delegator badge.
+        /// - 10000 test asset stable coins
         /// 
         /// ***This is just for test purpose and totally not the practial use of these packages!***
-        pub fn init_credit_user(&mut self, maximum_monthly_allowance: Decimal) -> [Bucket; 6] {
+        pub fn init_credit_user(&mut self, maximum_monthly_allowance: Decimal) -> [Bucket; 7] {
 
             let proof = self.admin_badge.create_proof();
 
@@ -494,7 +798,83 @@ blueprint! {
 
             ComponentAuthZone::pop().drop();
 
-            [id_sbt, credit_sbt, self.stable_coin.take(dec!("10000")), self.dao_share_token.take(dec!("100")), cv1, delegator_badge]
+            [id_sbt, credit_sbt, self.stable_coin.take(dec!("10000")), self.dao_share_token.take(dec!("100")), cv1, delegator_badge, self.asset_coin.take(dec!("10000"))]
+
+        }
+
+        /// The method will get the resources needed to take a business credit, included:
+        /// - A unique Business ID SBT with the yearly revenue make the monthly allowance (without business credit terms) equal the input, trust factor score: 60
+        /// - A credit SBT with the credit score same as the ID SBT.
+        /// - 10000 stable coins
+        /// - 10000 asset stable coins
+        /// 
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn init_business_user(&mut self, maximum_monthly_allowance: Decimal) -> [Bucket; 4] {
+
+            let proof = self.admin_badge.create_proof();
+
+            ComponentAuthZone::push(proof);
+
+            let income = (maximum_monthly_allowance * dec!("12") / dec!("0.6") / dec!("0.6")).ceiling();
+
+            let ground_id: GroundID = self.ground_id.into();
+            let id_sbt = ground_id.issue_new_id_sbt(IdentityType::Business, income, dec!("60"));
+
+            let ground_credit: GroundCredit = self.ground_credit.into();
+            let credit_sbt = ground_credit.get_new_credit_sbt(id_sbt.create_proof());
+
+            ComponentAuthZone::pop().drop();
+
+            [id_sbt, credit_sbt, self.stable_coin.take(dec!("10000")), self.asset_coin.take(dec!("10000"))]
+
+        }
+
+        /// The method will change the business credit terms on the ground credit component.
+        pub fn change_business_credit_terms(&self, business_credit_terms: Option<BusinessCreditTerms>) {
+
+            let proof = self.admin_badge.create_proof();
+
+            ComponentAuthZone::push(proof);
+
+            let ground_credit: GroundCredit = self.ground_credit.into();
+            ground_credit.change_business_credit_terms(business_credit_terms);
+
+            ComponentAuthZone::pop().drop();
+
+        }
+
+        /// The method will wind the ground lending protocol down on the ground credit component, the protocol's borrowers can only repay their debt.
+        pub fn wind_down_lending(&self) {
+
+            let proof = self.admin_badge.create_proof();
+
+            ComponentAuthZone::push(proof);
+
+            let ground_credit: GroundCredit = self.ground_credit.into();
+            ground_credit.delist_protocol(self.ground_lending_controller.unwrap(), true);
+
+            ComponentAuthZone::pop().drop();
+
+        }
+
+        /// Read only method to get the ground lending protocol's permission on the ground credit component.
+        pub fn lending_permission(&self) -> Option<ProtocolPermission> {
+            let ground_credit: GroundCredit = self.ground_credit.into();
+            ground_credit.get_protocol_permission(self.ground_lending_controller.unwrap())
+        }
+
+        /// The method will link the Person owners to a Business identity on the ground credit component.
+        pub fn link_business_owners(&self, business: NonFungibleId, owners: Vec<NonFungibleId>) {
+
+            let proof = self.admin_badge.create_proof();
+
+            ComponentAuthZone::push(proof);
+
+            let ground_id: GroundID = self.ground_id.into();
+            let ground_credit: GroundCredit = self.ground_credit.into();
+            ground_credit.link_business_owners(ground_id.identity_sbt_address(), business, owners);
+
+            ComponentAuthZone::pop().drop();
 
         }
 
@@ -538,15 +918,67 @@ blueprint! {
 
         }
 
+        /// The method will revoke an installment credit on the ground credit component.
+        /// 
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn revoke_installment_credit(&self, id: u64) {
+
+            let proof = self.admin_badge.create_proof();
+
+            ComponentAuthZone::push(proof);
+
+            let ground_credit: GroundCredit = self.ground_credit.into();
+            ground_credit.revoke_installment_credit(id);
+
+            ComponentAuthZone::pop().drop();
+
+        }
+
+        /// The method will create a second Ground Credit component and let both credit services trust each other for Credit SBT migration.
+        /// 
+        /// ***This is just for test purpose and totally not the practial use of these packages!***
+        pub fn new_credit_service(&self) -> ComponentAddress {
+
+            let proof = self.admin_badge.create_proof();
+
+            ComponentAuthZone::push(proof);
+
+            let ground_credit: GroundCredit = self.ground_credit.into();
+
+            let other_credit = GroundCredit::new(
+
+                String::from("GroundCredit Test Component 2"),
+                self.admin_badge.resource_address(),
+                ground_credit.credit_scoring_rate(),
+                self.ground_id
+
+            );
+
+            let other: GroundCredit = other_credit.into();
+
+            ground_credit.trust_credit_service(other_credit, other.controller_badge_address());
+
+            other.trust_credit_service(self.ground_credit, ground_credit.controller_badge_address());
+
+            ComponentAuthZone::pop().drop();
+
+            info!("Ground Credit test component 2 address: {}", other_credit);
+
+            other_credit
+
+        }
+
         /// The method will blacklist and prevent an unique Identity SBT ID from using the Ground Finance components.
-        pub fn blacklist(&self, id: NonFungibleId) {
+        /// 
+        /// The restriction will lapse after the expiry time (unix) if provided.
+        pub fn blacklist(&self, id: NonFungibleId, reason: BlacklistReason, expiry: Option<u64>) {
 
             let proof = self.admin_badge.create_proof();
 
             ComponentAuthZone::push(proof);
 
             let ground_credit: GroundCredit = self.ground_credit.into();
-            ground_credit.blacklist(id);
+            ground_credit.blacklist(id, reason, expiry);
 
             ComponentAuthZone::pop().drop();
 
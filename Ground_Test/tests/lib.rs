@@ -6,6 +6,8 @@
 use radix_engine::{ledger::*, transaction::*, model::Receipt};
 use scrypto_unit::*;
 use scrypto::prelude::*;
+use ground_finance::ground_credit::{BlacklistReason, BusinessCreditTerms, ProtocolPermission, ProtocolStatus};
+use ground_finance::ground_lending::{Tranche, DepositTerm, TermBonuses, CreditHealth, ExposureLimits, ExposureHeadroom, QueuedWithdrawal, LendingAction, CircuitBreaker, Interest, RevolvingCreditInterestRates, InterestRateModel, InterestRateQuote, Delegation};
 mod neuracle_time_gateway;
 
 const PACKAGE: &str = "ground_test";
@@ -15,12 +17,17 @@ struct GroundTestEnv<'a, L: SubstateStore> {
     env: TestEnv<'a, L>,
     // dao_share_token: ResourceAddress,
     stable_coin: ResourceAddress,
+    asset_coin: ResourceAddress,
     id_sbt: ResourceAddress,
     credit_sbt: ResourceAddress,
     installment_credit_request_badge: ResourceAddress,
     installment_credit_badge: ResourceAddress,
+    guarantee_badge: ResourceAddress,
     // cv_sbt: ResourceAddress,
     lending_account: ResourceAddress,
+    withdrawal_ticket: ResourceAddress,
+    flash_loan_receipt: ResourceAddress,
+    debt_claim: ResourceAddress,
     dao_member_sbt: ResourceAddress,
     // dao_delegator_nft: ResourceAddress,
     // dao_proposal_badge: ResourceAddress,
@@ -70,6 +77,17 @@ impl<'a, L: SubstateStore> GroundTestEnv<'a, L> {
 
     }
 
+    /// Create new BUSINESS CREDIT USER, the business has no owner linked yet.
+    fn new_business_user(&mut self, name: &str, maximum_monthly_allowance: Decimal) {
+
+        self.env.create_user(name);
+        self.env.acting_as(name);
+        let receipt = self.env.call_method(self.test_component, "init_business_user", vec![scrypto_encode(&maximum_monthly_allowance)]);
+        println!("INIT RESOURCES FOR NEW BUSINESS, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+    }
+
     /// ADVANCE EPOCH BY 1 AND UPDATE TIME DATA.
     /// 
     /// THIS CAN BE MANIPULATED IF INPUT SOME(TIME (String))
@@ -98,6 +116,22 @@ impl<'a, L: SubstateStore> GroundTestEnv<'a, L> {
 
     }
 
+    fn update_neuracle_time_with_peg_time(&mut self, time: String, peg_time: String) {
+
+        let substate_store = self.env.executor.substate_store_mut();
+
+        let current = substate_store.get_epoch();
+
+        substate_store.set_epoch(current + 1);
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "update_neuracle_data_with_peg_time", vec![scrypto_encode(&time), scrypto_encode(&peg_time)]);
+        println!("UPDATE NEURACLE TIME DATA WITH THE PEG QUOTED AT UNIX TIME {}, RECEPIT: {:?}", peg_time, receipt);
+        assert!(receipt.result.is_ok());
+
+    }
+
     fn get_revolving_credit_amount(&mut self, name: &str) -> Receipt {
 
         self.env.acting_as(name);
@@ -162,6 +196,17 @@ impl<'a, L: SubstateStore> GroundTestEnv<'a, L> {
     /// - period_max: tester's input.
     fn get_installment_credit(&mut self, name: &str, total_loan: Decimal, period_max: u8) { 
 
+        self.request_installment_credit(name, total_loan, period_max);
+
+        let receipt = self.claim_installment_credit_badge(name);
+        println!("USER GOT THE INSTALLMENT CREDIT WITH {} STABLE COINS AMOUNT AND HAVE TO REPAID IN {} PERIODS", total_loan, period_max);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    /// Request an installment credit and let the credit service operator accept the request.
+    fn request_installment_credit(&mut self, name: &str, total_loan: Decimal, period_max: u8) -> u64 { 
+
         self.env.acting_as(name);
 
         let (user, private_key) = self.env.get_current_user();
@@ -197,6 +242,13 @@ impl<'a, L: SubstateStore> GroundTestEnv<'a, L> {
         println!("USER REQUEST INSTALLMENT CREDIT ACCEPTED, RECEPIT: {:?}", receipt);
         assert!(receipt.result.is_ok()); 
 
+        request_id
+
+    }
+
+    /// Use the installment credit request badge to get the Installment Credit Badge.
+    fn claim_installment_credit_badge(&mut self, name: &str) -> Receipt { 
+
         self.env.acting_as(name);
 
         let (user, private_key) = self.env.get_current_user();
@@ -216,13 +268,38 @@ impl<'a, L: SubstateStore> GroundTestEnv<'a, L> {
         .sign([private_key]);
             
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("USER GOT THE INSTALLMENT CREDIT WITH {} STABLE COINS AMOUNT AND HAVE TO REPAID IN {} PERIODS, RECEPIT: {:?}", total_loan, period_max, receipt);
+        println!("USER CLAIM THE INSTALLMENT CREDIT BADGE, RECEPIT: {:?}", receipt);
+
+        receipt
+
+    }
+
+    /// Let the credit service operator revoke an installment credit.
+    fn revoke_installment_credit(&mut self, request_id: u64) { 
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "revoke_installment_credit", vec![scrypto_encode(&request_id)]);
+        println!("REVOKE THE INSTALLMENT CREDIT NO.{}, RECEPIT: {:?}", request_id, receipt);
         assert!(receipt.result.is_ok()); 
 
+    }
+
+    /// Let the user mint a forged Identity SBT with the ID of another user's Identity SBT.
+    fn forge_id(&mut self, name: &str, victim: &str) -> ResourceAddress {
+
+        let id = self.get_id(victim);
+
+        self.env.acting_as(name);
+        let receipt = self.env.call_method(self.test_component, "forge_identity", vec![scrypto_encode(&id)]);
+        println!("FORGE AN IDENTITY SBT, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+        receipt.new_resource_addresses[0]
 
     }
 
-    fn black_list(&mut self, name: &str) {
+    fn get_id(&mut self, name: &str) -> NonFungibleId {
 
         self.env.acting_as(name);
 
@@ -243,173 +320,235 @@ impl<'a, L: SubstateStore> GroundTestEnv<'a, L> {
         println!("GET IDENTITY SBT ID, RECEPIT: {:?}", receipt);
         assert!(receipt.result.is_ok()); 
 
-        let id: NonFungibleId = return_of_call_method(&mut receipt, "get_id");
+        return_of_call_method(&mut receipt, "get_id")
+
+    }
+
+    fn black_list(&mut self, name: &str, expiry: Option<u64>) {
+
+        let id = self.get_id(name);
 
         self.env.acting_as("tester");
 
-        let receipt = self.env.call_method(self.test_component, "blacklist", vec![scrypto_encode(&id)]);
+        let receipt = self.env.call_method(self.test_component, "blacklist", vec![scrypto_encode(&id), scrypto_encode(&BlacklistReason::LateRepayment), scrypto_encode(&expiry)]);
         println!("BLACK LIST THE IDENTITY ID {}, RECEPIT: {:?}", id, receipt);
         assert!(receipt.result.is_ok()); 
 
     }
 
-    fn new_lender(&mut self, name: &str, amount: Decimal) {
+    fn pledge_guarantee(&mut self, guarantor: &str, borrower: &str, amount: Decimal) -> Receipt {
 
-        self.new_credit_user(name, dec!("30"));
-        
-        self.env.acting_as(name);
+        let borrower = self.get_id(borrower);
+
+        self.env.acting_as(guarantor);
 
         let (user, private_key) = self.env.get_current_user();
 
         let transaction = TransactionBuilder::new()
-        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
-        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
             continue_transaction
-            .call_method(self.ground_lending, "new_lending_account", vec![scrypto_encode(&Bucket(bucket_id))])
-
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(self.ground_credit, "pledge_guarantee", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&borrower), scrypto_encode(&amount)])
+            })
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("GET NEW LENDING ACCOUNT WITH {} STABLE COIN, RECEPIT: {:?}", amount, receipt);
-        assert!(receipt.result.is_ok()); 
+        println!("PLEDGE {} STABLE COINS GUARANTEE FOR THE IDENTITY ID {}, RECEPIT: {:?}", amount, borrower, receipt);
+
+        receipt
 
     }
 
-    fn withdraw(&mut self, name: &str, amount: Decimal) { 
+    fn release_guarantee(&mut self, guarantor: &str) -> Receipt {
 
-        self.env.acting_as(name);
+        self.env.acting_as(guarantor);
 
         let (user, private_key) = self.env.get_current_user();
 
         let transaction = TransactionBuilder::new()
-        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.lending_account)])
-        .pop_from_auth_zone( |continue_transaction, proof_id| {
+        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.guarantee_badge)])
+        .take_from_worktop(self.guarantee_badge, |continue_transaction, bucket_id| {
             continue_transaction
-            .call_method(self.ground_lending, "withdraw", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&amount)])
-
+            .call_method(self.ground_credit, "release_guarantee", vec![scrypto_encode(&Bucket(bucket_id))])
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("WITHDRAW {} STABLE COIN FROM THE USER'S LENDING ACCOUNT, RECEPIT: {:?}", amount, receipt);
-        assert!(receipt.result.is_ok()); 
+        println!("RELEASE THE GUARANTEE, RECEPIT: {:?}", receipt);
+
+        receipt
 
     }
 
-    fn withdraw_all(&mut self, name: &str) { 
+    fn call_guarantee(&mut self, name: &str) -> Receipt {
+
+        let id = self.get_id(name);
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.ground_lending, "call_guarantee", vec![scrypto_encode(&id)]);
+        println!("CALL THE GUARANTEES OF THE IDENTITY ID {}, RECEPIT: {:?}", id, receipt);
+
+        receipt
+
+    }
+
+    fn declare_default(&mut self, name: &str) -> Receipt {
+
+        let id = self.get_id(name);
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.ground_lending, "declare_default", vec![scrypto_encode(&id)]);
+        println!("DECLARE DEFAULT ON THE LOAN OF THE IDENTITY ID {}, RECEPIT: {:?}", id, receipt);
+
+        receipt
+
+    }
+
+    fn auction_debt_claim(&mut self, name: &str, start_price: Decimal, floor_price: Decimal, duration: u64) {
+
+        let id = self.get_id(name);
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "auction_debt_claim", vec![scrypto_encode(&id), scrypto_encode(&start_price), scrypto_encode(&floor_price), scrypto_encode(&duration)]);
+        println!("PACKAGE THE DEFAULTED POSITION OF THE IDENTITY ID {} AS A DEBT CLAIM AND AUCTION IT, RECEPIT: {:?}", id, receipt);
+        assert!(receipt.result.is_ok());
+
+    }
+
+    fn buy_debt_claim(&mut self, name: &str, claim_id: u64, amount: Decimal) -> Receipt {
 
         self.env.acting_as(name);
 
         let (user, private_key) = self.env.get_current_user();
 
         let transaction = TransactionBuilder::new()
-        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.lending_account)])
-        .take_from_worktop( self.lending_account,|continue_transaction, bucket_id| {
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
             continue_transaction
-            .call_method(self.ground_lending, "withdraw_all", vec![scrypto_encode(&Bucket(bucket_id))])
-
+            .call_method(self.ground_lending, "buy_debt_claim", vec![scrypto_encode(&claim_id), scrypto_encode(&Bucket(bucket_id))])
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("WITHDRAW ALL STABLE COIN FROM THE USER'S LENDING ACCOUNT, RECEPIT: {:?}", receipt);
-        assert!(receipt.result.is_ok()); 
+        println!("BUY THE DEBT CLAIM NO.{} WITH {} STABLE COINS, RECEPIT: {:?}", claim_id, amount, receipt);
+
+        receipt
 
     }
 
-    fn withdraw_fail(&mut self, name: &str) { 
+    fn claim_debt_repayments(&mut self, name: &str) -> Receipt {
 
         self.env.acting_as(name);
 
         let (user, private_key) = self.env.get_current_user();
 
         let transaction = TransactionBuilder::new()
-        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.lending_account)])
-        .take_from_worktop( self.lending_account,|continue_transaction, bucket_id| {
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.debt_claim)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
             continue_transaction
-            .call_method(self.ground_lending, "withdraw_all", vec![scrypto_encode(&Bucket(bucket_id))])
-
+            .call_method(self.ground_lending, "claim_debt_repayments", vec![scrypto_encode(&Proof(proof_id))])
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("WITHDRAW ALL STABLE COIN FROM THE USER'S LENDING ACCOUNT WHEN THE PROTOCOL'S VAULT IS NOT ENOUGH, THIS SHOULD FAIL, RECEPIT: {:?}", receipt);
-        assert!(receipt.result.is_err()); 
+        println!("TAKE THE REPAYMENTS ROUTED TO THE DEBT CLAIM, RECEPIT: {:?}", receipt);
+
+        receipt
 
     }
 
-    fn revolving_credit(&mut self, name: &str, amount: Decimal) -> Receipt {
+    fn delegate_credit(&mut self, lender: &str, borrower: &str, allowance: Decimal) -> u64 {
 
-        self.env.acting_as(name);
+        let borrower = self.get_id(borrower);
+
+        self.env.acting_as(lender);
 
         let (user, private_key) = self.env.get_current_user();
-    
+
         let transaction = TransactionBuilder::new()
-        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.lending_account)])
         .pop_from_auth_zone(|continue_transaction, proof_id| {
             continue_transaction
-            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
-            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
-                continue_transaction2
-                .call_method(self.ground_lending, "revolving_credit", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&amount)])
-            })
+            .call_method(self.ground_lending, "delegate_credit", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&borrower), scrypto_encode(&allowance), scrypto_encode(&dec!("5")), scrypto_encode(&2592000u64)])
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
-        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("TAKE {} STABLE COIN LOAN FROM USER'S REVOLVING CREDIT, RECEPIT: {:?}", amount, receipt);
+
+        let mut receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("DELEGATE {} STABLE COINS CREDIT TO THE IDENTITY ID {}, RECEPIT: {:?}", allowance, borrower, receipt);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "delegate_credit")
+
+    }
+
+    fn write_off_delegation(&mut self, delegation_id: u64) -> Receipt {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.ground_lending, "write_off_delegation", vec![scrypto_encode(&delegation_id)]);
+        println!("WRITE OFF THE DELEGATED LOAN NO.{}, RECEPIT: {:?}", delegation_id, receipt);
 
         receipt
 
     }
 
-    fn repay_part(&mut self, name: &str, amount: Decimal) {
+    fn get_delegation(&mut self, delegation_id: u64) -> Delegation {
 
-        self.env.acting_as(name);
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "get_delegation", vec![scrypto_encode(&delegation_id)]);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "get_delegation")
+
+    }
+
+    fn revoke_delegation(&mut self, lender: &str, delegation_id: u64) -> Receipt {
+
+        self.env.acting_as(lender);
 
         let (user, private_key) = self.env.get_current_user();
-    
+
         let transaction = TransactionBuilder::new()
-        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.lending_account)])
         .pop_from_auth_zone(|continue_transaction, proof_id| {
             continue_transaction
-            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
-            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
-                continue_transaction2
-                .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
-                .take_from_worktop(self.stable_coin, |continue_transaction3, bucket_id| {
-                    continue_transaction3.call_method(self.ground_lending, "repay", vec![scrypto_encode(&mut Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&mut Bucket(bucket_id))])
-                })
-            })
+            .call_method(self.ground_lending, "revoke_delegation", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&delegation_id)])
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("REPAY {} STABLE COIN TO THE PROTOCOL, RECEPIT: {:?}", amount, receipt);
+        println!("REVOKE THE DELEGATION NO.{}, RECEPIT: {:?}", delegation_id, receipt);
 
-        assert!(receipt.result.is_ok())
+        receipt
 
     }
 
-    fn repay_full(&mut self, name: &str) {
+    fn delegated_credit(&mut self, name: &str, delegation_id: u64, amount: Decimal) -> Receipt {
 
         self.env.acting_as(name);
 
         let (user, private_key) = self.env.get_current_user();
-    
+
         let transaction = TransactionBuilder::new()
         .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
         .pop_from_auth_zone(|continue_transaction, proof_id| {
@@ -417,464 +556,2844 @@ impl<'a, L: SubstateStore> GroundTestEnv<'a, L> {
             .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
             .pop_from_auth_zone(|continue_transaction2, proof_id2| {
                 continue_transaction2
-                .call_method(user.account, "withdraw", vec![scrypto_encode(&self.stable_coin)])
-                .take_from_worktop(self.stable_coin, |continue_transaction3, bucket_id| {
-                    continue_transaction3.call_method(self.ground_lending, "repay", vec![scrypto_encode(&mut Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&mut Bucket(bucket_id))])
-                })
+                .call_method(self.ground_lending, "delegated_credit", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&delegation_id), scrypto_encode(&amount)])
             })
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("REPAY ALL THE USER'S LOAN FROM THE PROTOCOL, RECEPIT: {:?}", receipt);
+        println!("TAKE {} STABLE COIN LOAN FROM THE DELEGATION NO.{}, RECEPIT: {:?}", amount, delegation_id, receipt);
 
-        assert!(receipt.result.is_ok())
+        receipt
 
     }
 
-    fn installment_credit(&mut self, name: &str) -> Receipt {
+    fn repay_delegated_credit(&mut self, name: &str, delegation_id: u64, amount: Decimal) {
 
         self.env.acting_as(name);
 
         let (user, private_key) = self.env.get_current_user();
-    
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "repay_delegated_credit", vec![scrypto_encode(&delegation_id), scrypto_encode(&Bucket(bucket_id))])
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("REPAY {} STABLE COIN TO THE DELEGATION NO.{}, RECEPIT: {:?}", amount, delegation_id, receipt);
+        assert!(receipt.result.is_ok());
+
+    }
+
+    fn import_credit(&mut self, name: &str, destination: ComponentAddress, source: ComponentAddress) -> Receipt {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
         let transaction = TransactionBuilder::new()
         .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
         .pop_from_auth_zone(|continue_transaction, proof_id| {
             continue_transaction
-            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
-            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
-                continue_transaction2
-                .call_method(user.account, "withdraw", vec![scrypto_encode(&self.installment_credit_badge)])
-                .take_from_worktop(self.installment_credit_badge, |continue_transaction3, bucket_id| {
-                    continue_transaction3.call_method(self.ground_lending, "installment_credit", vec![scrypto_encode(&mut Proof(proof_id)), scrypto_encode(&mut Proof(proof_id2)), scrypto_encode(&mut Bucket(bucket_id))])
-                })
-            })
+            .call_method(destination, "import_credit", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&source)])
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("TAKE ALL THE LOAN FROM THE INSTALLMENT CREDIT, RECEPIT: {:?}", receipt);
+        println!("IMPORT THE CREDIT FROM THE CREDIT SERVICE {} INTO THE CREDIT SERVICE {}, RECEPIT: {:?}", source, destination, receipt);
 
         receipt
 
     }
 
-    // /// The function will go through a simple consensus round of the Ground Business DAO.
-    // fn proof_of_concept_consensus(&mut self, methods: Methods) {
+    /// CREATE A NEW CHECKOUT COMPONENT AND LIST IT ON THE LENDING PROTOCOL THROUGH A DAO CONCEPT.
+    /// 
+    /// THIS HAS TO BE CALLED BEFORE ANY CREDIT USER IS CREATED SO THE TEST DAO MEMBER STILL HOLD ENOUGH VOTING POWER.
+    fn init_checkout(&mut self) -> Checkout {
 
-    //     self.env.acting_as("DAO_member");
+        self.env.acting_as("tester");
 
-    //     let (user, private_key) = self.env.get_current_user();
-    
-    //     let transaction = TransactionBuilder::new()
-    //     .call_method(user.account, "create_proof", vec![scrypto_encode(&self.dao_member_sbt)])
-    //     .call_method(user.account, "create_proof", vec![scrypto_encode(&self.cv_sbt)])
-    //     .pop_from_auth_zone(|continue_transaction, proof_id| {
-    //         continue_transaction
-    //         .call_method(self.dao, "propose_concept", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&methods), scrypto_encode(&dec!("100"))])
-    //     })
-    //     .call_method_with_all_resources(user.account, "deposit_batch")
-    //     .build(self.env.executor.get_nonce([user.key]))
-    //     .sign([private_key]);
-            
-    //     let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-    //     println!("LET THE DAO MEMBER PROPOSE THE CONCEPT, RECEPIT: {:?}", receipt);
-    //     assert!(receipt.result.is_ok());
+        let mut receipt = self.env.call_method(self.test_component, "init_checkout", vec![]);
+        println!("NEW CHECKOUT COMPONENT, RECEIPT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
 
-    //     let (user, private_key) = self.env.get_current_user();
+        let merchant_badge = resource_by_name(&self.env, &receipt, "Test Organization's Merchant Badge");
+        let settlement_receipt = resource_by_name(&self.env, &receipt, "Test Organization's Settlement Receipt");
 
-    //     let id = NonFungibleId::from_u64(0u64);
-    
-    //     let transaction = TransactionBuilder::new()
-    //     .call_method(user.account, "create_proof", vec![scrypto_encode(&self.dao_member_sbt)])
-    //     .pop_from_auth_zone(|continue_transaction, proof_id| {
-    //         continue_transaction
-    //         .call_method(self.dao, "vote", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&id), scrypto_encode(&true)])
-    //     })
-    //     .call_method_with_all_resources(user.account, "deposit_batch")
-    //     .build(self.env.executor.get_nonce([user.key]))
-    //     .sign([private_key]);
-            
-    //     let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-    //     println!("LET THE DAO MEMBER VOTE ON THE CONCEPT, RECEPIT: {:?}", receipt);
-    //     assert!(receipt.result.is_ok());
+        Checkout {
+            component: return_of_call_method(&mut receipt, "init_checkout"),
+            merchant_badge,
+            settlement_receipt
+        }
 
-    //     let (user, private_key) = self.env.get_current_user();
+    }
+
+    fn new_merchant(&mut self, name: &str, checkout: &Checkout) {
+
+        self.env.create_user(name);
+        self.env.acting_as(name);
+        let receipt = self.env.call_method(self.test_component, "register_merchant", vec![scrypto_encode(&checkout.component), scrypto_encode(&name.to_string())]);
+        println!("REGISTER NEW MERCHANT, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+    }
+
+    fn create_invoice(&mut self, merchant: &str, checkout: &Checkout, amount: Decimal, buyer: &str) -> u64 {
+
+        let buyer = self.get_id(buyer);
+
+        self.env.acting_as(merchant);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&checkout.merchant_badge)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(checkout.component, "create_invoice", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&amount), scrypto_encode(&Some(buyer.clone()))])
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+
+        let mut receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("CREATE AN INVOICE OF {} STABLE COINS FOR THE IDENTITY ID {}, RECEPIT: {:?}", amount, buyer, receipt);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "create_invoice")
+
+    }
+
+    fn pay_invoice(&mut self, buyer: &str, checkout: &Checkout, invoice_id: u64) -> Receipt {
+
+        self.env.acting_as(buyer);
+
+        let (user, private_key) = self.env.get_current_user();
     
-    //     let transaction = TransactionBuilder::new()
-    //     .call_method(user.account, "withdraw", vec![scrypto_encode(&self.dao_proposal_badge)])
-    //     .take_from_worktop(self.dao_proposal_badge, |continue_transaction, bucket_id| {
-    //         continue_transaction
-    //         .call_method(self.dao, "execute_concept", vec![scrypto_encode(&Bucket(bucket_id))])
-    //     })
-    //     .call_method_with_all_resources(user.account, "deposit_batch")
-    //     .build(self.env.executor.get_nonce([user.key]))
-    //     .sign([private_key]);
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(checkout.component, "pay_invoice", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&invoice_id)])
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
             
-    //     let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-    //     println!("LET THE DAO MEMBER EXECUTE THE CONCEPT, RECEPIT: {:?}", receipt);
-    //     assert!(receipt.result.is_ok())
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("PAY THE INVOICE ID {} WITH THE USER'S REVOLVING CREDIT, RECEPIT: {:?}", invoice_id, receipt);
 
-    // }
+        receipt
 
-    fn lending_use_dao(&mut self) {
+    }
 
-        self.env.acting_as("tester");
+    fn settle_invoice(&mut self, merchant: &str, checkout: &Checkout, invoice_id: u64) {
 
-        let receipt = self.env.call_method(self.test_component, "lending_use_dao", vec![]);
+        self.env.acting_as(merchant);
 
-        println!("LET THE LENDING PROTOCOL USE DAO TREASURY, RECEPIT: {:?}", receipt);
+        let (user, private_key) = self.env.get_current_user();
 
-        assert!(receipt.result.is_ok()); 
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&checkout.merchant_badge)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(checkout.component, "settle_invoice", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&invoice_id)])
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("SETTLE THE INVOICE ID {}, RECEPIT: {:?}", invoice_id, receipt);
+        assert!(receipt.result.is_ok());
 
     }
 
-    fn compensation(&mut self, name: &str) {
+    /// REFUND THE MERCHANT'S ONLY SETTLED PURCHASE.
+    fn refund(&mut self, merchant: &str, checkout: &Checkout, amount: Decimal) {
 
-        self.env.acting_as(name);
+        self.env.acting_as(merchant);
 
         let (user, private_key) = self.env.get_current_user();
 
         let transaction = TransactionBuilder::new()
-        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.lending_account)])
-        .take_from_worktop( self.lending_account,|continue_transaction, bucket_id| {
+        .call_method(user.account, "withdraw", vec![scrypto_encode(&checkout.settlement_receipt)])
+        .take_from_worktop(checkout.settlement_receipt, |continue_transaction, bucket_id| {
             continue_transaction
-            .call_method(self.ground_lending, "compensate", vec![scrypto_encode(&Bucket(bucket_id))])
+            .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+            .take_from_worktop(self.stable_coin, |continue_transaction2, bucket_id2| {
+                continue_transaction2
+                .call_method(checkout.component, "refund", vec![scrypto_encode(&Bucket(bucket_id)), scrypto_encode(&Bucket(bucket_id2))])
+            })
         })
         .call_method_with_all_resources(user.account, "deposit_batch")
         .build(self.env.executor.get_nonce([user.key]))
         .sign([private_key]);
-            
+
         let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
-        println!("COMPENSATE USER, RECEPIT: {:?}", receipt);
-        assert!(receipt.result.is_ok()); 
-        
+        println!("REFUND THE PURCHASE WITH {} STABLE COINS, RECEPIT: {:?}", amount, receipt);
+        assert!(receipt.result.is_ok());
+
     }
-}
 
-/// CREATE NEW TEST ENV.
-fn new_test_env<L: SubstateStore>(mut env: TestEnv<L>) -> GroundTestEnv<L> {
+    fn claim_refund(&mut self, buyer: &str, checkout: &Checkout) -> Receipt {
 
-    env.create_user("tester");
-    env.acting_as("tester");
-    env.publish_package(PACKAGE, include_package!("ground_test"));
-    env.using_package(PACKAGE);
-    let mut receipt = env.call_function(BLUEPRINT, "new", vec![]);
-    println!("NEW TEST COMPONENT, RECEIPT: {:?}", receipt);
-    assert!(receipt.result.is_ok());  
+        let id_sbt = self.id_sbt;
 
-    // let dao_share_token = receipt.new_resource_addresses[1];
-    let stable_coin = receipt.new_resource_addresses[2];
-    let id_sbt = receipt.new_resource_addresses[10];
-    let credit_sbt = receipt.new_resource_addresses[14];
-    let installment_credit_request_badge = receipt.new_resource_addresses[15];
-    let installment_credit_badge = receipt.new_resource_addresses[16];
-    let ground_id = receipt.new_component_addresses[1];
-    let ground_credit = receipt.new_component_addresses[2];
-    // let cv_sbt = receipt.new_resource_addresses[18];
-    let test_component: ComponentAddress = return_of_call_function(&mut receipt, BLUEPRINT);
+        self.claim_refund_with(buyer, checkout, id_sbt)
 
-    let mut receipt = env.call_method(test_component, "init", vec![]);
-    println!("NEW LENDING PROTOCOL AND DAO COMPONENT, RECEIPT: {:?}", receipt);
-    assert!(receipt.result.is_ok());  
+    }
 
-    let lending_account = receipt.new_resource_addresses[2];
-    let dao_member_sbt = receipt.new_resource_addresses[7];
-    // let dao_delegator_nft = receipt.new_resource_addresses[8];
-    // let dao_proposal_badge = receipt.new_resource_addresses[9];
-    // let dao_unstake_badge = receipt.new_resource_addresses[10];
+    fn claim_refund_with(&mut self, buyer: &str, checkout: &Checkout, id_resource: ResourceAddress) -> Receipt {
 
-    let (ground_lending, _dao): (ComponentAddress, ComponentAddress) = return_of_call_method(&mut receipt, "init");
+        self.env.acting_as(buyer);
 
-    let receipt = env.call_method(test_component, "init_stake_for_validator_node", vec![]);
-    println!("INIT STAKE FOR VALIDATOR NODES, RECEIPT: {:?}", receipt);
-    assert!(receipt.result.is_ok()); 
+        let (user, private_key) = self.env.get_current_user();
 
-    GroundTestEnv {
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&id_resource)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(checkout.component, "claim_refund", vec![scrypto_encode(&Proof(proof_id))])
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
 
-        env,
-        // dao_share_token,
-        stable_coin,
-        id_sbt,
-        credit_sbt,
-        installment_credit_request_badge,
-        installment_credit_badge,
-        // cv_sbt,
-        lending_account,
-        dao_member_sbt,
-        // dao_delegator_nft,
-        // dao_proposal_badge,
-        ground_id,
-        ground_credit,
-        ground_lending,
-        // dao,
-        test_component
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("CLAIM THE REFUND REMAINDER, RECEPIT: {:?}", receipt);
+
+        receipt
 
     }
 
-}
+    fn credit_health(&mut self, id: NonFungibleId, id_resource: ResourceAddress) -> Option<CreditHealth> {
 
-/// ## Ground Credit blueprint test:
-/// The test will do the following:
-/// 
-/// - Go through all the blueprint's functions.
-/// - Test the Ground Credit blueprint's methods.
-/// 
-/// Testers can edit the params in the test.
-#[test]
-fn test_ground_credit() {
+        self.env.acting_as("tester");
 
-    let mut ledger = InMemorySubstateStore::with_bootstrap();
-    let env = TestEnv::new(&mut ledger);
-    let mut test_env = new_test_env(env);
+        let mut receipt = self.env.call_method(self.ground_lending, "credit_health", vec![scrypto_encode(&id), scrypto_encode(&id_resource)]);
+        println!("GET THE CREDIT HEALTH OF THE IDENTITY ID {}, RECEPIT: {:?}", id, receipt);
+        assert!(receipt.result.is_ok());
 
-    println!("GET REAL CURRENT TIME BY UNIX");
-    test_env.update_neuracle_time(None);
+        return_of_call_method(&mut receipt, "credit_health")
 
-    println!("CREATE NEW REVOLVING CREDIT USER WITH MAXIMUM MONTHLY ALLOWANCE IS 30 STABLECOINS. THE USER HAS INCOME 1000 AND TRUST SCORE AT 60.");
-    test_env.new_credit_user("credit_user", dec!("30"));
+    }
 
-    println!("RETEST CREDIT USER SBT PATTERN");
-    test_env.env.acting_as("credit_user");
+    fn change_business_credit_terms(&mut self, business_credit_terms: Option<BusinessCreditTerms>) {
 
-    let (user, private_key) = test_env.env.get_current_user();
-    
-    let transaction = TransactionBuilder::new()
-    .call_method(user.account, "create_proof", vec![scrypto_encode(&test_env.id_sbt)])
-    .pop_from_auth_zone(|continue_transaction, proof_id| {
-        continue_transaction.call_method(test_env.ground_credit, "get_new_credit_sbt", vec![scrypto_encode(&Proof(proof_id))])
-    })
-    .call_method_with_all_resources(user.account, "deposit_batch")
-    .build(test_env.env.executor.get_nonce([user.key]))
-    .sign([private_key]);
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "change_business_credit_terms", vec![scrypto_encode(&business_credit_terms)]);
+        println!("CHANGE THE BUSINESS CREDIT TERMS, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+    }
+
+    fn wind_down_lending(&mut self) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "wind_down_lending", vec![]);
+        println!("WIND THE LENDING PROTOCOL DOWN ON THE CREDIT SERVICE, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+    }
+
+    fn lending_permission(&mut self) -> ProtocolPermission {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.test_component, "lending_permission", vec![]);
+        assert!(receipt.result.is_ok());
+
+        let permission: Option<ProtocolPermission> = return_of_call_method(&mut receipt, "lending_permission");
+
+        permission.unwrap()
+
+    }
+
+    fn link_business_owners(&mut self, business: &str, owners: Vec<&str>) -> Vec<NonFungibleId> {
+
+        let business = self.get_id(business);
+
+        let owners: Vec<NonFungibleId> = owners.into_iter().map(|owner| self.get_id(owner)).collect();
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "link_business_owners", vec![scrypto_encode(&business), scrypto_encode(&owners)]);
+        println!("LINK THE OWNERS TO THE BUSINESS ID {}, RECEPIT: {:?}", business, receipt);
+        assert!(receipt.result.is_ok());
+
+        let mut receipt = self.env.call_method(self.ground_credit, "get_business_owners", vec![scrypto_encode(&business)]);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "get_business_owners")
+
+    }
+
+    fn dao_payouts(&mut self) -> Decimal {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "dao_payouts", vec![]);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "dao_payouts")
+
+    }
+
+    fn new_lender(&mut self, name: &str, amount: Decimal) {
+
+        self.new_term_lender(name, amount, DepositTerm::Flexible)
+
+    }
+
+    fn new_term_lender(&mut self, name: &str, amount: Decimal, term: DepositTerm) {
+
+        self.new_credit_user(name, dec!("30"));
         
-    let receipt = test_env.env.executor.validate_and_execute(&transaction).unwrap();
-    println!("GET THE CREDIT SBT, THIS SHOULD FAIL, RECEPIT: {:?}", receipt);
-    assert!(receipt.result.is_err()); 
+        self.env.acting_as(name);
 
-    println!("GET USER'S REVOLVING CREDIT AMOUNT");
-    let mut receipt = test_env.get_revolving_credit_amount("credit_user");
+        let (user, private_key) = self.env.get_current_user();
 
-    assert!(receipt.result.is_ok());
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "new_lending_account", vec![scrypto_encode(&Bucket(bucket_id)), scrypto_encode(&term)])
 
-    let (maximum_credit, current_allowance): (Decimal, Decimal) = return_of_call_method(&mut receipt, "get_revolving_credit_amount");
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("GET NEW LENDING ACCOUNT WITH {} STABLE COIN, RECEPIT: {:?}", amount, receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn new_junior_lender(&mut self, name: &str, amount: Decimal) {
+
+        self.new_credit_user(name, dec!("30"));
+        
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "new_tranche_lending_account", vec![scrypto_encode(&Bucket(bucket_id)), scrypto_encode(&Tranche::Junior), scrypto_encode(&DepositTerm::Flexible)])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("GET NEW JUNIOR TRANCHE LENDING ACCOUNT WITH {} STABLE COIN, RECEPIT: {:?}", amount, receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn withdraw(&mut self, name: &str, amount: Decimal) { 
+
+        let receipt = self.try_withdraw(name, amount);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn try_withdraw(&mut self, name: &str, amount: Decimal) -> Receipt { 
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.lending_account)])
+        .pop_from_auth_zone( |continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "withdraw", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&amount)])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("WITHDRAW {} STABLE COIN FROM THE USER'S LENDING ACCOUNT, RECEPIT: {:?}", amount, receipt);
+
+        receipt
+
+    }
+
+    fn withdraw_all(&mut self, name: &str) { 
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.lending_account)])
+        .take_from_worktop( self.lending_account,|continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "withdraw_all", vec![scrypto_encode(&Bucket(bucket_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("WITHDRAW ALL STABLE COIN FROM THE USER'S LENDING ACCOUNT, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn withdraw_fail(&mut self, name: &str) { 
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.lending_account)])
+        .take_from_worktop( self.lending_account,|continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "withdraw_all", vec![scrypto_encode(&Bucket(bucket_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("WITHDRAW ALL STABLE COIN FROM THE USER'S LENDING ACCOUNT WHEN THE PROTOCOL'S VAULT IS NOT ENOUGH, THIS SHOULD FAIL, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_err()); 
+
+    }
+
+    fn account_return(&mut self, name: &str) -> (Decimal, Decimal) { 
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.lending_account)])
+        .pop_from_auth_zone( |continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "get_account_return", vec![scrypto_encode(&Proof(proof_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let mut receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("GET THE USER'S LENDING ACCOUNT RETURN, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok()); 
+
+        return_of_call_method(&mut receipt, "get_account_return")
+
+    }
+
+    fn enqueue_withdrawal(&mut self, name: &str, amount: Decimal) { 
+
+        let receipt = self.try_enqueue_withdrawal(name, amount);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn try_enqueue_withdrawal(&mut self, name: &str, amount: Decimal) -> Receipt { 
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.lending_account)])
+        .pop_from_auth_zone( |continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "enqueue_withdrawal", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&amount)])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("QUEUE A {} STABLE COIN WITHDRAWAL FROM THE USER'S LENDING ACCOUNT, RECEPIT: {:?}", amount, receipt);
+
+        receipt
+
+    }
+
+    /// Claim (or cancel) the queued withdrawal of the user's withdrawal ticket.
+    fn claim_withdrawal(&mut self, name: &str, method: &str) { 
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.withdrawal_ticket)])
+        .take_from_worktop( self.withdrawal_ticket,|continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, method, vec![scrypto_encode(&Bucket(bucket_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("{} THE USER'S QUEUED WITHDRAWAL, RECEPIT: {:?}", method.to_uppercase(), receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn queued_withdrawal(&mut self, ticket_id: u64) -> Option<QueuedWithdrawal> {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "get_queued_withdrawal", vec![scrypto_encode(&ticket_id)]);
+        println!("GET THE QUEUED WITHDRAWAL {}, RECEPIT: {:?}", ticket_id, receipt);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "get_queued_withdrawal")
+
+    }
+
+    fn revolving_credit(&mut self, name: &str, amount: Decimal) -> Receipt {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+    
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(self.ground_lending, "revolving_credit", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&amount)])
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("TAKE {} STABLE COIN LOAN FROM USER'S REVOLVING CREDIT, RECEPIT: {:?}", amount, receipt);
+
+        receipt
+
+    }
+
+    /// TAKE A FLASH LOAN, CHECK THE CIRCUIT BREAKERS WHILE THE LOAN IS OPEN AND REPAY THE LOAN WITH THE FEE.
+    fn flash_loan(&mut self, name: &str, amount: Decimal) {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let fee = amount * dec!("0.0009");
+
+        let transaction = TransactionBuilder::new()
+        .call_method(self.ground_lending, "flash_loan", vec![scrypto_encode(&amount)])
+        .call_method(self.ground_lending, "check_circuit_breakers", vec![])
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&fee), scrypto_encode(&self.stable_coin)])
+        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
+            continue_transaction
+            .take_from_worktop(self.flash_loan_receipt, |continue_transaction2, receipt_id| {
+                continue_transaction2
+                .call_method(self.ground_lending, "repay_flash_loan", vec![scrypto_encode(&Bucket(bucket_id)), scrypto_encode(&Bucket(receipt_id))])
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("TAKE AND REPAY A {} STABLE COIN FLASH LOAN, RECEPIT: {:?}", amount, receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn flash_loan_keep_receipt(&mut self, name: &str, amount: Decimal) -> Receipt {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(self.ground_lending, "flash_loan", vec![scrypto_encode(&amount)])
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("TAKE A {} STABLE COIN FLASH LOAN AND KEEP THE RECEIPT, RECEPIT: {:?}", amount, receipt);
+
+        receipt
+
+    }
+
+    /// LET THE LENDING PROTOCOL USE THE POOL TOKEN MODE THROUGH A DAO CONCEPT AND GET THE POOL TOKEN ADDRESS.
+    /// 
+    /// THIS HAS TO BE CALLED BEFORE ANY CREDIT USER IS CREATED SO THE TEST DAO MEMBER STILL HOLD ENOUGH VOTING POWER.
+    fn use_pool_token(&mut self) -> ResourceAddress {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "lending_use_pool_token", vec![]);
+        println!("LET THE LENDING PROTOCOL USE THE POOL TOKEN MODE, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+        resource_by_name(&self.env, &receipt, "Ground Lending Pool Share")
+
+    }
+
+    fn lend_for_pool_token(&mut self, name: &str, amount: Decimal) {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "lend_for_pool_token", vec![scrypto_encode(&Bucket(bucket_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("LEND {} STABLE COIN FOR THE POOL TOKEN, RECEPIT: {:?}", amount, receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn redeem_pool_token(&mut self, name: &str, pool_token: ResourceAddress) -> Receipt {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw", vec![scrypto_encode(&pool_token)])
+        .take_from_worktop(pool_token, |continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "redeem_pool_token", vec![scrypto_encode(&Bucket(bucket_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("REDEEM ALL THE USER'S POOL TOKEN, RECEPIT: {:?}", receipt);
+
+        receipt
+
+    }
+
+    fn pool_token_value(&mut self) -> Decimal {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "pool_token_value", vec![]);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "pool_token_value")
+
+    }
+
+    fn lend_asset(&mut self, name: &str, amount: Decimal) {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.asset_coin)])
+        .take_from_worktop(self.asset_coin, |continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "lend_asset", vec![scrypto_encode(&Bucket(bucket_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("LEND {} ASSET STABLE COIN INTO THE MARKET, RECEPIT: {:?}", amount, receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn asset_credit(&mut self, name: &str, amount: Decimal) -> Receipt {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+    
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(self.ground_lending, "asset_credit", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&self.asset_coin), scrypto_encode(&amount)])
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("TAKE {} ASSET STABLE COIN LOAN FROM USER'S REVOLVING CREDIT, RECEPIT: {:?}", amount, receipt);
+
+        receipt
+
+    }
+
+    fn repay_asset(&mut self, name: &str, amount: Decimal) -> Receipt {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+    
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.asset_coin)])
+                .take_from_worktop(self.asset_coin, |continue_transaction3, bucket_id| {
+                    continue_transaction3.call_method(self.ground_lending, "repay_asset", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&Bucket(bucket_id))])
+                })
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("REPAY {} ASSET STABLE COIN OF THE USER'S LOAN IN THE MARKET, RECEPIT: {:?}", amount, receipt);
+
+        receipt
+
+    }
+
+    fn asset_market(&mut self) -> (Decimal, Decimal, ResourceAddress, Decimal) {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "get_asset_market", vec![scrypto_encode(&self.asset_coin)]);
+        println!("GET THE ASSET STABLE COIN MARKET, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "get_asset_market")
+
+    }
+
+    fn repay_part(&mut self, name: &str, amount: Decimal) {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+    
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+                .take_from_worktop(self.stable_coin, |continue_transaction3, bucket_id| {
+                    continue_transaction3.call_method(self.ground_lending, "repay", vec![scrypto_encode(&mut Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&mut Bucket(bucket_id))])
+                })
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("REPAY {} STABLE COIN TO THE PROTOCOL, RECEPIT: {:?}", amount, receipt);
+
+        assert!(receipt.result.is_ok())
+
+    }
+
+    fn repay_full(&mut self, name: &str) {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+    
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(user.account, "withdraw", vec![scrypto_encode(&self.stable_coin)])
+                .take_from_worktop(self.stable_coin, |continue_transaction3, bucket_id| {
+                    continue_transaction3.call_method(self.ground_lending, "repay", vec![scrypto_encode(&mut Proof(proof_id)), scrypto_encode(&Proof(proof_id2)), scrypto_encode(&mut Bucket(bucket_id))])
+                })
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("REPAY ALL THE USER'S LOAN FROM THE PROTOCOL, RECEPIT: {:?}", receipt);
+
+        assert!(receipt.result.is_ok())
+
+    }
+
+    fn installment_credit(&mut self, name: &str) -> Receipt {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+    
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "create_proof", vec![scrypto_encode(&self.id_sbt)])
+        .pop_from_auth_zone(|continue_transaction, proof_id| {
+            continue_transaction
+            .call_method(user.account, "create_proof", vec![scrypto_encode(&self.credit_sbt)])
+            .pop_from_auth_zone(|continue_transaction2, proof_id2| {
+                continue_transaction2
+                .call_method(user.account, "withdraw", vec![scrypto_encode(&self.installment_credit_badge)])
+                .take_from_worktop(self.installment_credit_badge, |continue_transaction3, bucket_id| {
+                    continue_transaction3.call_method(self.ground_lending, "installment_credit", vec![scrypto_encode(&mut Proof(proof_id)), scrypto_encode(&mut Proof(proof_id2)), scrypto_encode(&mut Bucket(bucket_id))])
+                })
+            })
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("TAKE ALL THE LOAN FROM THE INSTALLMENT CREDIT, RECEPIT: {:?}", receipt);
+
+        receipt
+
+    }
+
+    // /// The function will go through a simple consensus round of the Ground Business DAO.
+    // fn proof_of_concept_consensus(&mut self, methods: Methods) {
+
+    //     self.env.acting_as("DAO_member");
+
+    //     let (user, private_key) = self.env.get_current_user();
+    
+    //     let transaction = TransactionBuilder::new()
+    //     .call_method(user.account, "create_proof", vec![scrypto_encode(&self.dao_member_sbt)])
+    //     .call_method(user.account, "create_proof", vec![scrypto_encode(&self.cv_sbt)])
+    //     .pop_from_auth_zone(|continue_transaction, proof_id| {
+    //         continue_transaction
+    //         .call_method(self.dao, "propose_concept", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&methods), scrypto_encode(&dec!("100"))])
+    //     })
+    //     .call_method_with_all_resources(user.account, "deposit_batch")
+    //     .build(self.env.executor.get_nonce([user.key]))
+    //     .sign([private_key]);
+            
+    //     let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+    //     println!("LET THE DAO MEMBER PROPOSE THE CONCEPT, RECEPIT: {:?}", receipt);
+    //     assert!(receipt.result.is_ok());
+
+    //     let (user, private_key) = self.env.get_current_user();
+
+    //     let id = NonFungibleId::from_u64(0u64);
+    
+    //     let transaction = TransactionBuilder::new()
+    //     .call_method(user.account, "create_proof", vec![scrypto_encode(&self.dao_member_sbt)])
+    //     .pop_from_auth_zone(|continue_transaction, proof_id| {
+    //         continue_transaction
+    //         .call_method(self.dao, "vote", vec![scrypto_encode(&Proof(proof_id)), scrypto_encode(&id), scrypto_encode(&true)])
+    //     })
+    //     .call_method_with_all_resources(user.account, "deposit_batch")
+    //     .build(self.env.executor.get_nonce([user.key]))
+    //     .sign([private_key]);
+            
+    //     let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+    //     println!("LET THE DAO MEMBER VOTE ON THE CONCEPT, RECEPIT: {:?}", receipt);
+    //     assert!(receipt.result.is_ok());
+
+    //     let (user, private_key) = self.env.get_current_user();
+    
+    //     let transaction = TransactionBuilder::new()
+    //     .call_method(user.account, "withdraw", vec![scrypto_encode(&self.dao_proposal_badge)])
+    //     .take_from_worktop(self.dao_proposal_badge, |continue_transaction, bucket_id| {
+    //         continue_transaction
+    //         .call_method(self.dao, "execute_concept", vec![scrypto_encode(&Bucket(bucket_id))])
+    //     })
+    //     .call_method_with_all_resources(user.account, "deposit_batch")
+    //     .build(self.env.executor.get_nonce([user.key]))
+    //     .sign([private_key]);
+            
+    //     let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+    //     println!("LET THE DAO MEMBER EXECUTE THE CONCEPT, RECEPIT: {:?}", receipt);
+    //     assert!(receipt.result.is_ok())
+
+    // }
+
+    fn lending_use_dao(&mut self) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "lending_use_dao", vec![]);
+
+        println!("LET THE LENDING PROTOCOL USE DAO TREASURY, RECEPIT: {:?}", receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    /// CHANGE THE LENDING PROTOCOL'S INSURANCE RESERVE FACTOR THROUGH A DAO CONCEPT.
+    /// 
+    /// THIS HAS TO BE CALLED BEFORE ANY CREDIT USER IS CREATED SO THE TEST DAO MEMBER STILL HOLD ENOUGH VOTING POWER.
+    fn try_change_interest_rates(&mut self, interest_rates: RevolvingCreditInterestRates) -> Receipt {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "change_interest_rates", vec![scrypto_encode(&interest_rates)]);
+
+        println!("CHANGE THE LENDING PROTOCOL'S REVOLVING CREDIT INTEREST RATES, RECEPIT: {:?}", receipt);
+
+        receipt
+
+    }
+
+    fn try_change_interest_rate_model(&mut self, interest_rate_model: Option<InterestRateModel>) -> Receipt {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "change_interest_rate_model", vec![scrypto_encode(&interest_rate_model)]);
+
+        println!("CHANGE THE LENDING PROTOCOL'S INTEREST RATE MODEL, RECEPIT: {:?}", receipt);
+
+        receipt
+
+    }
+
+    fn quote_interest_rates(&mut self, amount: Decimal) -> InterestRateQuote {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "quote_interest_rates", vec![scrypto_encode(&amount)]);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "quote_interest_rates")
+
+    }
+
+    fn change_reserve_factor(&mut self, reserve_factor: Decimal) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "change_reserve_factor", vec![scrypto_encode(&reserve_factor)]);
+
+        println!("CHANGE THE LENDING PROTOCOL'S INSURANCE RESERVE FACTOR, RECEPIT: {:?}", receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn deposit_reserve(&mut self, name: &str, amount: Decimal) {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw_by_amount", vec![scrypto_encode(&amount), scrypto_encode(&self.stable_coin)])
+        .take_from_worktop(self.stable_coin, |continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "deposit_reserve", vec![scrypto_encode(&Bucket(bucket_id))])
+
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("DEPOSIT {} STABLE COIN INTO THE INSURANCE RESERVE, RECEPIT: {:?}", amount, receipt);
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn reserve_coverage(&mut self) -> (Decimal, Decimal, Decimal) {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "reserve_coverage", vec![]);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "reserve_coverage")
+
+    }
+
+    /// CHANGE THE LENDING PROTOCOL'S EXPOSURE LIMITS THROUGH A DAO CONCEPT.
+    /// 
+    /// THIS HAS TO BE CALLED BEFORE ANY CREDIT USER IS CREATED SO THE TEST DAO MEMBER STILL HOLD ENOUGH VOTING POWER.
+    fn change_exposure_limits(&mut self, exposure_limits: Option<ExposureLimits>) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "change_exposure_limits", vec![scrypto_encode(&exposure_limits)]);
+
+        println!("CHANGE THE LENDING PROTOCOL'S EXPOSURE LIMITS, RECEPIT: {:?}", receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn exposure_headroom(&mut self, id: NonFungibleId, id_resource: ResourceAddress) -> Option<ExposureHeadroom> {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "exposure_headroom", vec![scrypto_encode(&id), scrypto_encode(&id_resource)]);
+        println!("GET THE EXPOSURE HEADROOM OF THE IDENTITY ID {}, RECEPIT: {:?}", id, receipt);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "exposure_headroom")
+
+    }
+
+    /// CHANGE THE LENDING PROTOCOL'S TERM DEPOSIT BONUSES THROUGH A DAO CONCEPT.
+    /// 
+    /// THIS HAS TO BE CALLED BEFORE ANY CREDIT USER IS CREATED SO THE TEST DAO MEMBER STILL HOLD ENOUGH VOTING POWER.
+    fn change_term_bonuses(&mut self, term_bonuses: TermBonuses) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "change_term_bonuses", vec![scrypto_encode(&term_bonuses)]);
+
+        println!("CHANGE THE LENDING PROTOCOL'S TERM DEPOSIT BONUSES, RECEPIT: {:?}", receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    /// CHANGE THE LENDING PROTOCOL'S CIRCUIT BREAKERS THROUGH A DAO CONCEPT.
+    /// 
+    /// THIS HAS TO BE CALLED BEFORE ANY CREDIT USER IS CREATED SO THE TEST DAO MEMBER STILL HOLD ENOUGH VOTING POWER.
+    fn change_circuit_breaker(&mut self, circuit_breaker: Option<CircuitBreaker>) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "change_circuit_breaker", vec![scrypto_encode(&circuit_breaker)]);
+
+        println!("CHANGE THE LENDING PROTOCOL'S CIRCUIT BREAKERS, RECEPIT: {:?}", receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    /// CREATE A NEW CLOCK AND TIME SOURCE AND LET THE LENDING PROTOCOL USE THE TIME SOURCE THROUGH A DAO CONCEPT.
+    /// 
+    /// THIS HAS TO BE CALLED BEFORE ANY CREDIT USER IS CREATED SO THE TEST DAO MEMBER STILL HOLD ENOUGH VOTING POWER.
+    fn init_time_source(&mut self, time: u64) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "init_time_source", vec![scrypto_encode(&time)]);
+
+        println!("NEW CLOCK AND TIME SOURCE COMPONENT, RECEPIT: {:?}", receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn set_clock_time(&mut self, time: u64) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "set_clock_time", vec![scrypto_encode(&time)]);
+
+        println!("SET THE CLOCK'S TIME INTO UNIX TIME: {}, RECEPIT: {:?}", time, receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn reset_time_source(&mut self, time: u64) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "reset_time_source", vec![scrypto_encode(&time)]);
+
+        println!("RESET THE TIME SOURCE INTO UNIX TIME: {}, RECEPIT: {:?}", time, receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    /// PAUSE OR RESUME AN ACTION ON THE LENDING PROTOCOL WITH THE GUARDIAN BADGE.
+    fn guard(&mut self, action: LendingAction, pause: bool) {
+
+        self.env.acting_as("tester");
+
+        let receipt = self.env.call_method(self.test_component, "guard", vec![scrypto_encode(&action), scrypto_encode(&pause)]);
+
+        println!("PAUSE: {}, THE {:?} ACTION ON THE LENDING PROTOCOL, RECEPIT: {:?}", pause, action, receipt);
+
+        assert!(receipt.result.is_ok()); 
+
+    }
+
+    fn check_circuit_breakers(&mut self) -> bool {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "check_circuit_breakers", vec![]);
+        println!("CHECK THE LENDING PROTOCOL'S CIRCUIT BREAKERS, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "check_circuit_breakers")
+
+    }
+
+    fn paused_actions(&mut self) -> Vec<LendingAction> {
+
+        self.env.acting_as("tester");
+
+        let mut receipt = self.env.call_method(self.ground_lending, "get_paused_actions", vec![]);
+        println!("GET THE LENDING PROTOCOL'S PAUSED ACTIONS, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok());
+
+        return_of_call_method(&mut receipt, "get_paused_actions")
+
+    }
+
+    fn compensation(&mut self, name: &str) {
+
+        self.env.acting_as(name);
+
+        let (user, private_key) = self.env.get_current_user();
+
+        let transaction = TransactionBuilder::new()
+        .call_method(user.account, "withdraw", vec![scrypto_encode(&self.lending_account)])
+        .take_from_worktop( self.lending_account,|continue_transaction, bucket_id| {
+            continue_transaction
+            .call_method(self.ground_lending, "compensate", vec![scrypto_encode(&Bucket(bucket_id))])
+        })
+        .call_method_with_all_resources(user.account, "deposit_batch")
+        .build(self.env.executor.get_nonce([user.key]))
+        .sign([private_key]);
+            
+        let receipt = self.env.executor.validate_and_execute(&transaction).unwrap();
+        println!("COMPENSATE USER, RECEPIT: {:?}", receipt);
+        assert!(receipt.result.is_ok()); 
+        
+    }
+}
+
+/// The listed checkout component and its resources.
+struct Checkout {
+    component: ComponentAddress,
+    merchant_badge: ResourceAddress,
+    settlement_receipt: ResourceAddress
+}
+
+/// FIND THE RESOURCE CREATED ON THE RECEIPT BY ITS NAME METADATA.
+fn resource_by_name<L: SubstateStore>(env: &TestEnv<L>, receipt: &Receipt, name: &str) -> ResourceAddress {
+
+    *receipt.new_resource_addresses.iter().find(|address| {
+        env.executor.substate_store().get_resource_manager(address)
+            .map_or(false, |resource_manager| resource_manager.metadata().get("name").map(|x| x.as_str()) == Some(name))
+    }).expect("The resource is not created on the receipt.")
+
+}
+
+/// CREATE NEW TEST ENV.
+fn new_test_env<L: SubstateStore>(mut env: TestEnv<L>) -> GroundTestEnv<L> {
+
+    env.create_user("tester");
+    env.acting_as("tester");
+    env.publish_package(PACKAGE, include_package!("ground_test"));
+    env.using_package(PACKAGE);
+    let mut receipt = env.call_function(BLUEPRINT, "new", vec![]);
+    println!("NEW TEST COMPONENT, RECEIPT: {:?}", receipt);
+    assert!(receipt.result.is_ok());  
+
+    // let dao_share_token = receipt.new_resource_addresses[1];
+    let stable_coin = receipt.new_resource_addresses[2];
+    let asset_coin = resource_by_name(&env, &receipt, "Test Asset Stable Coin");
+    let id_sbt = receipt.new_resource_addresses[10];
+    let credit_sbt = receipt.new_resource_addresses[14];
+    let installment_credit_request_badge = receipt.new_resource_addresses[15];
+    let installment_credit_badge = receipt.new_resource_addresses[16];
+    let guarantee_badge = resource_by_name(&env, &receipt, "GroundCredit Test Component's Credit Guarantee Badge");
+    let ground_id = receipt.new_component_addresses[1];
+    let ground_credit = receipt.new_component_addresses[2];
+    // let cv_sbt = receipt.new_resource_addresses[18];
+    let test_component: ComponentAddress = return_of_call_function(&mut receipt, BLUEPRINT);
+
+    let mut receipt = env.call_method(test_component, "init", vec![]);
+    println!("NEW LENDING PROTOCOL AND DAO COMPONENT, RECEIPT: {:?}", receipt);
+    assert!(receipt.result.is_ok());  
+
+    let lending_account = resource_by_name(&env, &receipt, "Ground Lending Test Component's Lending NFT");
+    let withdrawal_ticket = resource_by_name(&env, &receipt, "Ground Lending Test Component's Withdrawal Ticket");
+    let flash_loan_receipt = resource_by_name(&env, &receipt, "Ground Lending Test Component's Flash Loan Receipt");
+    let debt_claim = resource_by_name(&env, &receipt, "Ground Lending Test Component's Debt Claim");
+    let dao_member_sbt = resource_by_name(&env, &receipt, "Test DAO component DAO Member SBT");
+    // let dao_delegator_nft = receipt.new_resource_addresses[11];
+    // let dao_proposal_badge = receipt.new_resource_addresses[12];
+    // let dao_unstake_badge = receipt.new_resource_addresses[13];
+
+    let (ground_lending, _dao): (ComponentAddress, ComponentAddress) = return_of_call_method(&mut receipt, "init");
+
+    let receipt = env.call_method(test_component, "init_stake_for_validator_node", vec![]);
+    println!("INIT STAKE FOR VALIDATOR NODES, RECEIPT: {:?}", receipt);
+    assert!(receipt.result.is_ok()); 
+
+    GroundTestEnv {
+
+        env,
+        // dao_share_token,
+        stable_coin,
+        asset_coin,
+        id_sbt,
+        credit_sbt,
+        installment_credit_request_badge,
+        installment_credit_badge,
+        guarantee_badge,
+        // cv_sbt,
+        lending_account,
+        withdrawal_ticket,
+        flash_loan_receipt,
+        debt_claim,
+        dao_member_sbt,
+        // dao_delegator_nft,
+        // dao_proposal_badge,
+        ground_id,
+        ground_credit,
+        ground_lending,
+        // dao,
+        test_component
+
+    }
+
+}
+
+/// ## Ground Credit blueprint test:
+/// The test will do the following:
+/// 
+/// - Go through all the blueprint's functions.
+/// - Test the Ground Credit blueprint's methods.
+/// 
+/// Testers can edit the params in the test.
+#[test]
+fn test_ground_credit() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    println!("GET REAL CURRENT TIME BY UNIX");
+    test_env.update_neuracle_time(None);
+
+    println!("CREATE NEW REVOLVING CREDIT USER WITH MAXIMUM MONTHLY ALLOWANCE IS 30 STABLECOINS. THE USER HAS INCOME 1000 AND TRUST SCORE AT 60.");
+    test_env.new_credit_user("credit_user", dec!("30"));
+
+    println!("RETEST CREDIT USER SBT PATTERN");
+    test_env.env.acting_as("credit_user");
+
+    let (user, private_key) = test_env.env.get_current_user();
+    
+    let transaction = TransactionBuilder::new()
+    .call_method(user.account, "create_proof", vec![scrypto_encode(&test_env.id_sbt)])
+    .pop_from_auth_zone(|continue_transaction, proof_id| {
+        continue_transaction.call_method(test_env.ground_credit, "get_new_credit_sbt", vec![scrypto_encode(&Proof(proof_id))])
+    })
+    .call_method_with_all_resources(user.account, "deposit_batch")
+    .build(test_env.env.executor.get_nonce([user.key]))
+    .sign([private_key]);
+        
+    let receipt = test_env.env.executor.validate_and_execute(&transaction).unwrap();
+    println!("GET THE CREDIT SBT, THIS SHOULD FAIL, RECEPIT: {:?}", receipt);
+    assert!(receipt.result.is_err()); 
+
+    println!("GET USER'S REVOLVING CREDIT AMOUNT");
+    let mut receipt = test_env.get_revolving_credit_amount("credit_user");
+
+    assert!(receipt.result.is_ok());
+
+    let (maximum_credit, current_allowance): (Decimal, Decimal) = return_of_call_method(&mut receipt, "get_revolving_credit_amount");
+
+    println!("USER'S MAXIMUM CREDIT: {}, CURRENT ALLOWANCE: {}", maximum_credit, current_allowance);
+
+    println!("TEST CHANGE CREDIT TYPE METHOD");
+    let receipt = test_env.change_credit_type("credit_user");
+
+    assert!(receipt.result.is_ok()); 
+
+    println!("GET USER'S REVOLVING CREDIT AMOUNT");
+
+    let mut receipt = test_env.get_revolving_credit_amount("credit_user");
+
+    assert!(receipt.result.is_ok());
+
+    let (maximum_credit, current_allowance): (Decimal, Decimal) = return_of_call_method(&mut receipt, "get_revolving_credit_amount");
+
+    println!("USER'S MAXIMUM CREDIT: {}, CURRENT ALLOWANCE: {}", maximum_credit, current_allowance);
+
+    println!("CREATE OTHER CREDIT USER WITH MAXIMUM MONTHLY ALLOWANCE IS 160 STABLECOINS. THE USER HAS INCOME 3000 AND TRUST SCORE AT 80.");
+    test_env.new_dao_member("credit_user2", 8u8);
+
+    println!("GET USER'S REVOLVING CREDIT AMOUNT");
+
+    let mut receipt = test_env.get_revolving_credit_amount("credit_user2");
+
+    assert!(receipt.result.is_ok());
+
+    let (maximum_credit, current_allowance): (Decimal, Decimal) = return_of_call_method(&mut receipt, "get_revolving_credit_amount");
+
+    println!("USER'S MAXIMUM CREDIT: {}, CURRENT ALLOWANCE: {}", maximum_credit, current_allowance);
+
+    println!("GO THROUGH INSTALLMENT CREDIT PROCESS");
+    test_env.get_installment_credit("credit_user2", dec!("1000000"), 7u8);
+
+    println!("CHECK BLACKLIST FUNCTION");
+    test_env.black_list("credit_user2", None);
+
+    let receipt = test_env.get_revolving_credit_amount("credit_user2");
+
+    assert!(receipt.result.is_err());
+
+}
+
+/// ## Ground Credit blueprint's temporary blacklist test:
+/// The test will do the following:
+/// 
+/// - Blacklist a credit user with an expiry time.
+/// - Check the restriction is lifted automatically after the expiry time.
+#[test]
+fn test_blacklist_expiry() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_credit_user("credit_user", dec!("30"));
+
+    println!("BLACKLIST THE USER FOR 1 MONTH");
+    test_env.black_list("credit_user", Some(10000000 + 2592000));
+
+    let receipt = test_env.get_revolving_credit_amount("credit_user");
+    assert!(receipt.result.is_err());
+
+    println!("ADVANCE 1 MONTH, THE RESTRICTION SHOULD LAPSE");
+    test_env.update_neuracle_time(Some("12592000".to_string()));
+
+    let receipt = test_env.get_revolving_credit_amount("credit_user");
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Credit blueprint's Credit SBT migration test:
+/// The test will do the following:
+/// 
+/// - Migrate a Credit SBT from the credit service A to the credit service B.
+/// - Check the migrated credit cannot be used on the credit service A anymore.
+/// - Migrate the credit back to A and then to B again.
+#[test]
+fn test_credit_migration() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    let mut receipt = test_env.env.call_method(test_env.test_component, "new_credit_service", vec![]);
+    println!("NEW CREDIT SERVICE, RECEPIT: {:?}", receipt);
+    assert!(receipt.result.is_ok());
+
+    let other_credit: ComponentAddress = return_of_call_method(&mut receipt, "new_credit_service");
+
+    test_env.new_credit_user("credit_user", dec!("30"));
+
+    let ground_credit = test_env.ground_credit;
+
+    let receipt = test_env.import_credit("credit_user", other_credit, ground_credit);
+    assert!(receipt.result.is_ok());
+
+    println!("THE CREDIT HAS BEEN MIGRATED, THIS SHOULD FAIL!");
+    let receipt = test_env.get_revolving_credit_amount("credit_user");
+    assert!(receipt.result.is_err());
+
+    println!("THE CREDIT HAS ALREADY BEEN MIGRATED, THIS SHOULD FAIL!");
+    let receipt = test_env.import_credit("credit_user", other_credit, ground_credit);
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.import_credit("credit_user", ground_credit, other_credit);
+    assert!(receipt.result.is_ok());
+
+    let receipt = test_env.import_credit("credit_user", other_credit, ground_credit);
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Lending blueprint's lender badge pattern test:
+/// The test will do the following:
+/// 
+/// - Go through all the blueprint's functions.
+/// - Test the Ground Lending blueprint's methods for lenders. 
+/// 
+/// Testers can edit the params in the test.
+#[test]
+fn test_lender_badge_pattern() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(None);
+
+    println!("TEST LENDING PROTOCOL'S LENDER BADGE PATTERN");
+    test_env.new_lender("lender1", dec!("264"));
+
+    test_env.withdraw("lender1", dec!("34"));
+
+    test_env.withdraw_all("lender1");
+
+}
+
+/// ## Ground Lending blueprint's revolving credit and interest for lenders test:
+/// The test will do the following:
+/// 
+/// - Go through all the blueprint's functions.
+/// - Create lenders and fund some stable coin into the protocol.
+/// - Test "Consumer-level" credit for borrowers: All the Ground Lending blueprint's methods for revolving credit borrowers, included late-repayment test.
+/// - Test the Automatic Credit Scoring Mechanism.
+/// - Test "Bank level" earning tracker for lenders: Compare the return of same lending account in amount but different in start - end time.
+/// 
+/// Testers can edit the params in the test.
+#[test]
+fn test_revolving_credit_and_interest() {
+
+    println!("THIS TEST WILL BE REALLY COMPLEX AND TAKE A LONG TIME, PLEASE BE PATIENT.");
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+    
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("9564"));
+
+    test_env.update_neuracle_time(Some("10052635".to_string()));
+
+    println!("NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 30");
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    println!("LET BORROWER 1 TAKE LOAN");
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("21"));
+
+    assert!(receipt.result.is_ok());
+
+    println!("LET BORROWER 1 TAKE MORE THAN HIS CAPABILITY");
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("644"));
+
+    assert!(receipt.result.is_err());
+    
+    test_env.update_neuracle_time(Some("10152356".to_string()));
+
+    test_env.new_lender("lender2", dec!("9564"));
+
+    test_env.update_neuracle_time(Some("10356952".to_string()));
+
+    println!("NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 112");
+    test_env.new_credit_user("borrower2", dec!("112"));
+
+    println!("LET BORROWER 2 TAKE MORE THAN HIS CAPABILITY");
+
+    let receipt = test_env.revolving_credit("borrower2", dec!("113"));
+
+    assert!(receipt.result.is_err());
+
+    println!("LET BORROWER 2 TAKE LOAN");
+
+    let receipt = test_env.revolving_credit("borrower2", dec!("112"));
+
+    assert!(receipt.result.is_ok());
+
+    // NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 3521
+    test_env.new_credit_user("borrower3", dec!("3521"));
+
+    println!("LET BORROWER 3 TAKE LOAN");
+
+    let receipt = test_env.revolving_credit("borrower3", dec!("3453"));
+
+    assert!(receipt.result.is_ok());
+
+    println!("LET BORROWER 3 TAKE MORE THAN HIS CAPABILITY");
+
+    let receipt = test_env.revolving_credit("borrower3", dec!("2236"));
+
+    assert!(receipt.result.is_err());
+
+    println!("TEST CHANGE CREDIT TYPE WHEN USER HAS A DEBT. THIS SHOULD FAIL");
+
+    let receipt = test_env.change_credit_type("borrower3");
+
+    assert!(receipt.result.is_err()); 
+
+    println!("LET BORROWER 3 TAKE LOAN");
+
+    let receipt = test_env.revolving_credit("borrower3", dec!("6"));
+
+    assert!(receipt.result.is_ok());
+
+    println!("LET BORROWER 1 AND 2 REPAY PART OF THE LOAN");
+
+    test_env.repay_part("borrower1", dec!("9"));
+
+    test_env.repay_part("borrower2", dec!("65"));
+
+    println!("LET BORROWER 3 REPAY FULL OF THE LOAN");
+
+    test_env.repay_full("borrower3");
+
+    test_env.update_neuracle_time(Some("11321546".to_string()));
+
+    println!("LET BORROWER 1 REPAY FULL OF THE LOAN");
+
+    test_env.repay_full("borrower1");
+
+    println!("LET LENDER 2 WITHDRAW ALL HIS ACCOUNT");
+
+    test_env.update_neuracle_time(Some("13513562".to_string()));
+
+    test_env.withdraw_all("lender2");
+
+    test_env.update_neuracle_time(Some("13795623".to_string()));
+
+    println!("LET BORROWER 2 REPAY FULL OF THE LOAN");
+    test_env.repay_full("borrower2");
+
+    println!("LET BORROWER 1 TAKE LOAN");
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("30"));
+
+    assert!(receipt.result.is_ok());
+
+    println!("LET BORROWER 1 REPAY FULL OF THE LOAN");
+
+    test_env.repay_full("borrower1");
+
+    println!("LET LENDER 1 WITHDRAW ALL HIS ACCOUNT");
+
+    test_env.withdraw_all("lender1");
+
+}
+
+/// ## Ground Lending blueprint's lender cohorts test:
+/// The test will do the following:
+/// 
+/// - Let a lender lend, then let a borrower take a loan a day later while a second lender lend.
+/// - Let a third lender lend right before the borrower repay the loan with interest.
+/// - Check only the first lender, whose cohort was closed before the loan was made, earn the loan's interest.
+#[test]
+fn test_lender_cohorts() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    test_env.update_neuracle_time(Some("10100000".to_string()));
+
+    test_env.new_lender("lender2", dec!("10000"));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
+
+    test_env.update_neuracle_time(Some("11000000".to_string()));
+
+    test_env.new_lender("lender3", dec!("10000"));
+
+    test_env.repay_full("borrower1");
+
+    println!("ONLY THE FIRST LENDER'S COHORT IS ELIGIBLE FOR THE LOAN'S INTEREST");
+    let (return1, _) = test_env.account_return("lender1");
+    let (return2, _) = test_env.account_return("lender2");
+    let (return3, _) = test_env.account_return("lender3");
+    assert!(return1 > dec!("10000") && return2 == dec!("10000") && return3 == dec!("10000"));
+
+}
+
+/// ## Ground Lending blueprint's time-weighted interest test:
+/// The test will do the following:
+/// 
+/// - Let two lenders lend the same amount in different days, then let a borrower take a loan.
+/// - Let the second lender withdraw half of the account in the middle of the loan.
+/// - Check the loan's interest is shared by the lenders' capital×time over the loan's life, not by their capital at the repayment.
+#[test]
+fn test_time_weighted_interest() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    test_env.update_neuracle_time(Some("10100000".to_string()));
+
+    test_env.new_lender("lender2", dec!("10000"));
+
+    test_env.update_neuracle_time(Some("10200000".to_string()));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
+
+    test_env.update_neuracle_time(Some("10600000".to_string()));
+
+    let receipt = test_env.try_withdraw("lender2", dec!("5000"));
+    assert!(receipt.result.is_ok());
+
+    test_env.update_neuracle_time(Some("11000000".to_string()));
+
+    test_env.repay_full("borrower1");
+
+    println!("THE SECOND LENDER EARN FOR THE FULL CAPITAL HELD BEFORE THE WITHDRAWAL");
+    let (return1, _) = test_env.account_return("lender1");
+    let (return2, _) = test_env.account_return("lender2");
+    let (interest1, interest2) = (return1 - dec!("10000"), return2 - dec!("5000"));
+    assert!(interest2 > Decimal::ZERO && interest1 > interest2 && interest2 * dec!("2") > interest1);
+
+}
+
+/// ## Ground Lending blueprint's interest rate model test:
+/// The test will do the following:
+/// 
+/// - Change the revolving credit interest rates and the interest rate model with wrong data.
+/// - Change the revolving credit interest rates and quote the static rates.
+/// - Change the interest rate model and quote the rates below and above the optimal utilization.
+#[test]
+fn test_interest_rate_model() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    println!("THE INTEREST RATE IS MORE THAN 100%, THIS SHOULD FAIL!");
+    let receipt = test_env.try_change_interest_rates(RevolvingCreditInterestRates {
+        yearly: Interest { interest_rate: dec!("10"), interest_rate_late: dec!("15") },
+        monthly: Interest { interest_rate: dec!("101"), interest_rate_late: dec!("2") }
+    });
+    assert!(receipt.result.is_err());
+
+    println!("THE OPTIMAL UTILIZATION IS 100%, THIS SHOULD FAIL!");
+    let receipt = test_env.try_change_interest_rate_model(Some(InterestRateModel {
+        optimal_utilization: dec!("100"),
+        slope: dec!("4"),
+        jump_slope: dec!("60")
+    }));
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.try_change_interest_rates(RevolvingCreditInterestRates {
+        yearly: Interest { interest_rate: dec!("10"), interest_rate_late: dec!("15") },
+        monthly: Interest { interest_rate: dec!("1"), interest_rate_late: dec!("2") }
+    });
+    assert!(receipt.result.is_ok());
+
+    let quote = test_env.quote_interest_rates(Decimal::ZERO);
+    assert!(quote.model.is_none() && quote.monthly == dec!("1") && quote.yearly == dec!("10"));
+
+    let receipt = test_env.try_change_interest_rate_model(Some(InterestRateModel {
+        optimal_utilization: dec!("80"),
+        slope: dec!("4"),
+        jump_slope: dec!("60")
+    }));
+    assert!(receipt.result.is_ok());
+
+    test_env.new_lender("lender1", dec!("1000"));
+
+    println!("THE UTILIZATION IS 40%, THE MONTHLY RATE IS 1% + 4% * 40% / 80%");
+    let quote = test_env.quote_interest_rates(dec!("400"));
+    assert!(quote.utilization == dec!("40") && quote.monthly == dec!("3") && quote.yearly == dec!("12"));
+    assert!(quote.model.unwrap().optimal_utilization == dec!("80"));
+
+    println!("THE UTILIZATION IS 90%, THE MONTHLY RATE IS 1% + 4% + 60% * 10% / 20%");
+    let quote = test_env.quote_interest_rates(dec!("900"));
+    assert!(quote.utilization == dec!("90") && quote.monthly == dec!("35"));
+
+}
+
+/// ## Ground Lending blueprint's withdrawal queue test:
+/// The test will do the following:
+/// 
+/// - Queue a withdrawal when the protocol's vault isn't short or the withdrawal is paused.
+/// - Queue the withdrawals when the protocol's vault is short.
+/// - Claim a filled withdrawal and cancel a partly filled withdrawal.
+/// - Fill the queue from a borrower's repayment.
+#[test]
+fn test_withdrawal_queue() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("100"));
+
+    test_env.new_lender("lender2", dec!("100"));
+
+    test_env.new_lender("lender3", dec!("100"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE VAULT HAS 280 STABLE COINS, THIS SHOULD FAIL!");
+    let receipt = test_env.try_enqueue_withdrawal("lender1", dec!("100"));
+    assert!(receipt.result.is_err());
+
+    test_env.withdraw("lender3", dec!("100"));
+
+    test_env.guard(LendingAction::Withdraw, true);
+
+    println!("THE WITHDRAWAL IS PAUSED, THIS SHOULD FAIL!");
+    let receipt = test_env.try_enqueue_withdrawal("lender1", dec!("100"));
+    assert!(receipt.result.is_err());
+
+    test_env.guard(LendingAction::Withdraw, false);
+
+    test_env.withdraw("lender1", dec!("90"));
+
+    println!("THE VAULT HAS 90 STABLE COINS, LENDER 2'S WITHDRAWAL IS ONLY FILLED BY THE 90 STABLE COINS");
+    test_env.enqueue_withdrawal("lender2", dec!("100"));
+    let withdrawal = test_env.queued_withdrawal(0).unwrap();
+    assert!(withdrawal.remaining == dec!("10") && withdrawal.filled == dec!("90"));
+
+    println!("LENDER 1'S WITHDRAWAL ISN'T FILLED");
+    test_env.enqueue_withdrawal("lender1", dec!("10"));
+    let withdrawal = test_env.queued_withdrawal(1).unwrap();
+    assert!(withdrawal.remaining == dec!("10") && withdrawal.filled == Decimal::ZERO);
+
+    println!("LENDER 2 CANCEL THE WITHDRAWAL, THE UNFILLED AMOUNT IS LENT BACK");
+    test_env.claim_withdrawal("lender2", "cancel_withdrawal");
+    assert!(test_env.queued_withdrawal(0).is_none());
+    let (amount, _) = test_env.account_return("lender2");
+    assert!(amount == dec!("10"));
+
+    println!("THE BORROWER'S REPAYMENT FILL THE QUEUE");
+    test_env.repay_full("borrower1");
+    let withdrawal = test_env.queued_withdrawal(1).unwrap();
+    assert!(withdrawal.remaining == Decimal::ZERO && withdrawal.filled == dec!("10"));
+
+    test_env.claim_withdrawal("lender1", "claim_withdrawal");
+    assert!(test_env.queued_withdrawal(1).is_none());
+
+}
+
+/// ## Ground Lending blueprint's pool token test:
+/// The test will do the following:
+/// 
+/// - Let a lender lend for the fungible pool token.
+/// - Let a borrower repay a loan with interest, the pool token's value rises when the interest is released.
+/// - Let the lender redeem the pool token.
+#[test]
+fn test_pool_token() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    let pool_token = test_env.use_pool_token();
+
+    test_env.new_credit_user("lender1", dec!("30"));
+
+    test_env.lend_for_pool_token("lender1", dec!("10000"));
+    assert!(test_env.pool_token_value() == Decimal::ONE);
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
+
+    test_env.update_neuracle_time(Some("11000000".to_string()));
+
+    test_env.repay_full("borrower1");
+
+    println!("THE INTEREST IS LOCKED RIGHT AFTER THE REPAYMENT");
+    assert!(test_env.pool_token_value() == Decimal::ONE);
+
+    println!("LET THE WHOLE INTEREST BE RELEASED, A NEW LENDING ACCOUNT RELEASE THE INTEREST INTO THE POOL TOKEN'S VALUE");
+    test_env.update_neuracle_time(Some("11700000".to_string()));
+    test_env.new_lender("lender2", dec!("100"));
+    assert!(test_env.pool_token_value() > Decimal::ONE);
+
+    let receipt = test_env.redeem_pool_token("lender1", pool_token);
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Lending blueprint's term deposit test:
+/// The test will do the following:
+/// 
+/// - Lock a 3 months term deposit until its maturity.
+/// - Let the term deposit take a bigger share of a loan's interest than a flexible deposit with the same amount.
+#[test]
+fn test_term_deposit() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.change_term_bonuses(TermBonuses {
+        three_months: dec!("50"),
+        six_months: dec!("100"),
+        twelve_months: dec!("200")
+    });
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_term_lender("lender2", dec!("10000"), DepositTerm::ThreeMonths);
+
+    println!("THE TERM DEPOSIT HASN'T MATURED, THIS SHOULD FAIL!");
+    let receipt = test_env.try_withdraw("lender2", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    test_env.update_neuracle_time(Some("10100000".to_string()));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
+
+    test_env.update_neuracle_time(Some("11000000".to_string()));
+
+    test_env.repay_full("borrower1");
+
+    let (return1, _) = test_env.account_return("lender1");
+    let (return2, _) = test_env.account_return("lender2");
+    assert!(return1 > dec!("10000") && return2 - dec!("10000") > return1 - dec!("10000"));
+
+    println!("THE TERM DEPOSIT HAS MATURED");
+    test_env.update_neuracle_time(Some((10000000 + DepositTerm::ThreeMonths.length()).to_string()));
+    let receipt = test_env.try_withdraw("lender2", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Lending blueprint's stablecoin market test:
+/// The test will do the following:
+/// 
+/// - Let a borrower take loans in the primary stablecoin and in the asset stablecoin market.
+/// - Let the borrower repay the primary stablecoin loan while the market loan is still open.
+/// - Let the borrower be late and repay the market loan with the late penalty.
+/// - Reject a stale peg quote and accept a fresh quote of the same peg.
+#[test]
+fn test_asset_market() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    println!("THE PEG FEED QUOTED ITS PEG MORE THAN A DAY AGO");
+    test_env.update_neuracle_time_with_peg_time("9990000".to_string(), (9990000u64 - 86401).to_string());
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.lend_asset("lender1", dec!("1000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    let id = test_env.get_id("borrower1");
+
+    println!("THE PEG QUOTE IS STALE, THIS SHOULD FAIL!");
+    let receipt = test_env.asset_credit("borrower1", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    println!("THE PEG FEED QUOTED THE SAME PEG AGAIN, THE UNCHANGED PEG IS STILL ACCEPTED");
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    let receipt = test_env.asset_credit("borrower1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE BORROWER REPAY THE PRIMARY STABLE COIN LOAN, THE MARKET LOAN IS STILL COUNTED ON THE CREDIT");
+    test_env.repay_full("borrower1");
+    let health = test_env.credit_health(id.clone(), test_env.id_sbt).unwrap();
+    assert!(health.drawn == dec!("10"));
+
+    println!("LET THE BORROWER BE LATE");
+    test_env.update_neuracle_time(Some((10000000u64 + 2592000 + 86400).to_string()));
+    let health = test_env.credit_health(id.clone(), test_env.id_sbt).unwrap();
+    assert!(health.late && health.projected_late_penalty > Decimal::ZERO);
+
+    println!("THE BORROWER REPAY THE MARKET LOAN WITH THE LATE PENALTY");
+    let receipt = test_env.repay_asset("borrower1", dec!("100"));
+    assert!(receipt.result.is_ok());
+    let health = test_env.credit_health(id, test_env.id_sbt).unwrap();
+    assert!(!health.late && health.drawn == Decimal::ZERO && health.debt_interest == Decimal::ZERO && health.extra_debt == Decimal::ZERO);
+
+    let (value, _, _, _) = test_env.asset_market();
+    assert!(value > dec!("1000"));
+
+}
+
+/// ## Ground Lending blueprint's emergency pause and circuit breakers test:
+/// The test will do the following:
+/// 
+/// - Reject the outflows which would drop the protocol's vault more than the breaker's limit in one ledger epoch.
+/// - Let the guardian pause borrowing while repayments stay open.
+/// - Trip the breakers on an oracle time jump and let the guardian resume the protocol.
+#[test]
+fn test_pause_and_circuit_breakers() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.change_circuit_breaker(Some(CircuitBreaker {
+        max_time_jump: 1000000,
+        max_vault_drop: dec!("10")
+    }));
+
+    test_env.new_lender("lender1", dec!("1000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE VAULT CAN'T DROP MORE THAN 10% IN ONE LEDGER EPOCH, THIS SHOULD FAIL!");
+    let receipt = test_env.try_withdraw("lender1", dec!("100"));
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.try_withdraw("lender1", dec!("50"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE GUARDIAN PAUSE BORROWING, THE BORROWER CAN STILL REPAY");
+    test_env.guard(LendingAction::Borrow, true);
+    let receipt = test_env.revolving_credit("borrower1", dec!("1"));
+    assert!(receipt.result.is_err());
+    test_env.repay_full("borrower1");
+
+    test_env.guard(LendingAction::Borrow, false);
+    assert!(test_env.paused_actions().is_empty());
+
+    println!("THE ORACLE TIME JUMP TOO FAR, THE BREAKERS TRIP");
+    test_env.update_neuracle_time(Some("20000000".to_string()));
+    assert!(test_env.check_circuit_breakers());
+    let paused = test_env.paused_actions();
+    assert!(paused.contains(&LendingAction::Borrow) && paused.contains(&LendingAction::Withdraw));
+
+    let receipt = test_env.try_withdraw("lender1", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    test_env.guard(LendingAction::Borrow, false);
+    test_env.guard(LendingAction::Withdraw, false);
+    assert!(!test_env.check_circuit_breakers());
+
+    let receipt = test_env.try_withdraw("lender1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Time Source and Ground Clock blueprints test:
+/// The test will do the following:
+/// 
+/// - Let the lending protocol read the time from a local clock through a time source.
+/// - Reject the clock's time when it move backward or jump too far.
+/// - Let the time source operator reset the time source after a long downtime.
+#[test]
+fn test_time_source() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.init_time_source(10000000);
+
+    test_env.new_lender("lender1", dec!("1000"));
+
+    println!("THE CLOCK MOVE BACKWARD, THIS SHOULD FAIL!");
+    test_env.set_clock_time(9000000);
+    let receipt = test_env.try_withdraw("lender1", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    println!("THE CLOCK JUMP TOO FAR, THIS SHOULD FAIL!");
+    test_env.set_clock_time(20000000);
+    let receipt = test_env.try_withdraw("lender1", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    test_env.set_clock_time(10000100);
+    let receipt = test_env.try_withdraw("lender1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+    println!("AFTER A LONG DOWNTIME THE CLOCK IS REJECTED, THE OPERATOR RESET THE TIME SOURCE");
+    test_env.set_clock_time(20000000);
+    let receipt = test_env.try_withdraw("lender1", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    test_env.reset_time_source(20000000);
+    let receipt = test_env.try_withdraw("lender1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Lending blueprint's flash loan test:
+/// The test will do the following:
+/// 
+/// - Reject a flash loan when the integrator keep the flash loan receipt.
+/// - Let the integrator repay a flash loan with the fee in the same transaction, the fee is released to the lender as interest.
+/// - Check the circuit breakers while a flash loan bigger than the breaker's vault drop is open, the breakers shouldn't trip.
+#[test]
+fn test_flash_loan() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.change_circuit_breaker(Some(CircuitBreaker {
+        max_time_jump: 1000000,
+        max_vault_drop: dec!("10")
+    }));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_credit_user("integrator", dec!("30"));
+
+    println!("THE INTEGRATOR KEEP THE FLASH LOAN RECEIPT, THIS SHOULD FAIL!");
+    let receipt = test_env.flash_loan_keep_receipt("integrator", dec!("1000"));
+    assert!(receipt.result.is_err());
+
+    println!("THE FLASH LOAN IS MORE THAN THE BREAKER'S VAULT DROP BUT IS COUNTED BACK BY THE BREAKERS");
+    test_env.flash_loan("integrator", dec!("5000"));
+    assert!(test_env.paused_actions().is_empty());
+
+    println!("LET THE WHOLE FLASH LOAN FEE BE RELEASED");
+    test_env.update_neuracle_time(Some("10700000".to_string()));
+
+    let (amount, _) = test_env.account_return("lender1");
+    assert!(amount > dec!("10000"));
+
+}
+
+/// ## Ground Lending blueprint's exposure limits test:
+/// The test will do the following:
+/// 
+/// - Limit a borrower's loans to 1% of the protocol's pool.
+/// - Count the borrower's stablecoin market loan in the borrower's exposure.
+/// - Release the borrower's exposure on repayment.
+/// - Read the exposure headroom of a wrong Identity SBT resource.
+#[test]
+fn test_exposure_limits() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.change_exposure_limits(Some(ExposureLimits {
+        max_borrower_share: dec!("1"),
+        max_product_share: dec!("100"),
+        max_business_share: dec!("100")
+    }));
+
+    test_env.new_lender("lender1", dec!("1000"));
+
+    test_env.lend_asset("lender1", dec!("1000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    let id = test_env.get_id("borrower1");
+
+    println!("THE LOAN IS OVER THE BORROWER EXPOSURE LIMIT, THIS SHOULD FAIL!");
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("8"));
+    assert!(receipt.result.is_ok());
+    let headroom = test_env.exposure_headroom(id.clone(), test_env.id_sbt).unwrap();
+    assert!(headroom.borrower == dec!("2"));
+
+    println!("THE MARKET LOAN IS COUNTED IN THE BORROWER'S EXPOSURE, THIS SHOULD FAIL!");
+    let receipt = test_env.asset_credit("borrower1", dec!("5"));
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.asset_credit("borrower1", dec!("2"));
+    assert!(receipt.result.is_ok());
+    let headroom = test_env.exposure_headroom(id.clone(), test_env.id_sbt).unwrap();
+    assert!(headroom.borrower == Decimal::ZERO);
+
+    println!("THE BORROWER REPAY THE PRIMARY STABLE COIN LOAN, THE EXPOSURE IS RELEASED");
+    test_env.repay_full("borrower1");
+    let headroom = test_env.exposure_headroom(id.clone(), test_env.id_sbt).unwrap();
+    assert!(headroom.borrower >= dec!("8"));
+
+    assert!(test_env.exposure_headroom(id, test_env.credit_sbt).is_none());
+
+}
+
+/// ## Ground Credit blueprint's protocol wind down test:
+/// The test will do the following:
+/// 
+/// - Count the borrower's loan in the lending protocol's exposure on the credit service.
+/// - Wind the lending protocol down, the borrower cannot take a new loan.
+/// - The borrower is late on repayment, the winding down protocol can only lower the debt so the late repayment penalty is not applied.
+/// - Release the protocol's exposure when the borrower repay the loan in full.
+#[test]
+fn test_protocol_wind_down() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("1000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("8"));
+    assert!(receipt.result.is_ok());
+    assert!(test_env.lending_permission().exposure == dec!("8"));
+
+    test_env.wind_down_lending();
+    assert!(test_env.lending_permission().status == ProtocolStatus::WindDown);
+
+    println!("THE PROTOCOL IS WINDING DOWN, THIS SHOULD FAIL!");
+    let receipt = test_env.revolving_credit("borrower1", dec!("1"));
+    assert!(receipt.result.is_err());
+
+    test_env.update_neuracle_time(Some("12600000".to_string()));
+
+    println!("THE BORROWER IS LATE ON REPAYMENT, THE PENALTY IS NOT APPLIED BY THE WINDING DOWN PROTOCOL");
+    test_env.repay_part("borrower1", dec!("2"));
+    assert!(test_env.lending_permission().exposure == dec!("6"));
+
+    test_env.repay_full("borrower1");
+    assert!(test_env.lending_permission().exposure == Decimal::ZERO);
+
+}
+
+/// ## Ground Lending blueprint's installment credit test:
+/// The test will do the following:
+/// 
+/// - Go through all the blueprint's functions.
+/// - Create lenders and fund some stable coin into the protocol.
+/// - Test the Ground Lending blueprint's methods for installment credit borrowers, included late-repayment test. 
+/// - Test the "Tolerance threshold" of the protocol: 
+/// 
+/// Borrowers cannot take a loan after the protocol's vault contain < 60% of the total return amount. (this can be edited on TestEngine blueprint)
+/// 
+/// Testers can edit the params in the test.
+#[test]
+fn test_installment_credit() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.update_neuracle_time(Some("10056231".to_string()));
+
+    println!("NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 30");
+    test_env.new_credit_user("borrower1", dec!("30"));
+    
+    test_env.get_installment_credit("borrower1", dec!("4100"), 3u8);
+
+    println!("TEST THE PROTOCOL'S TOLERANCE THRESHOLD, THIS SHOULD FAIL!");
+    let receipt = test_env.installment_credit("borrower1");
+    assert!(receipt.result.is_err());
+
+    test_env.new_lender("lender2", dec!("300"));
+
+    test_env.update_neuracle_time(Some("10167622".to_string()));
+
+    let receipt = test_env.installment_credit("borrower1");
+    assert!(receipt.result.is_ok());
+
+    test_env.update_neuracle_time(Some("10372546".to_string()));
+
+    test_env.new_lender("lender3", dec!("10000"));
+
+    test_env.update_neuracle_time(Some("11653624".to_string()));
+
+    test_env.repay_part("borrower1", dec!("352"));
+
+    test_env.update_neuracle_time(Some("12722659".to_string()));
+
+    test_env.repay_part("borrower1", dec!("1351"));
+
+    test_env.update_neuracle_time(Some("13565695".to_string()));
+
+    test_env.repay_full("borrower1");
+
+    test_env.update_neuracle_time(Some("14536859".to_string()));
+
+    test_env.withdraw_all("lender1");
+
+    test_env.withdraw_all("lender2");
+
+    test_env.withdraw_all("lender3");
+
+}
+
+/// ## Ground Lending blueprint's installment credit badge expiry test:
+/// The test will do the following:
+/// 
+/// - Create 2 installment credit users.
+/// - The first user take the installment loan before the badge's activation deadline.
+/// - The second user try to take the installment loan after the badge's activation deadline, this should fail.
+#[test]
+fn test_installment_credit_expiry() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+    test_env.get_installment_credit("borrower1", dec!("1000"), 3u8);
+
+    test_env.new_credit_user("borrower2", dec!("30"));
+    test_env.get_installment_credit("borrower2", dec!("1000"), 3u8);
+
+    test_env.update_neuracle_time(Some("10056231".to_string()));
+
+    let receipt = test_env.installment_credit("borrower1");
+    assert!(receipt.result.is_ok());
+
+    println!("ADVANCE PASS THE ACTIVATION DEADLINE, THIS SHOULD FAIL!");
+    test_env.update_neuracle_time(Some("12600000".to_string()));
+
+    let receipt = test_env.installment_credit("borrower2");
+    assert!(receipt.result.is_err());
+
+}
+
+/// ## Ground Credit blueprint's installment credit revocation test:
+/// The test will do the following:
+/// 
+/// - Revoke an installment credit after the borrower got the badge, the lending protocol should reject the badge.
+/// - Revoke an installment credit before the borrower got the badge, the borrower should get no badge.
+#[test]
+fn test_installment_credit_revocation() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+    let request_id = test_env.request_installment_credit("borrower1", dec!("1000"), 3u8);
+    let receipt = test_env.claim_installment_credit_badge("borrower1");
+    assert!(receipt.result.is_ok());
+
+    test_env.revoke_installment_credit(request_id);
+
+    println!("THE INSTALLMENT CREDIT HAS BEEN REVOKED, THIS SHOULD FAIL!");
+    let receipt = test_env.installment_credit("borrower1");
+    assert!(receipt.result.is_err());
+
+    test_env.new_credit_user("borrower2", dec!("30"));
+    let request_id = test_env.request_installment_credit("borrower2", dec!("1000"), 3u8);
+
+    test_env.revoke_installment_credit(request_id);
+
+    println!("THE INSTALLMENT CREDIT HAS BEEN REVOKED, THE BORROWER GET NO BADGE");
+    let mut receipt = test_env.claim_installment_credit_badge("borrower2");
+    assert!(receipt.result.is_ok());
+    let badge: Option<Bucket> = return_of_call_method(&mut receipt, "get_installment_credit_badge");
+    assert!(badge.is_none());
+
+    println!("THE BORROWER DOESN'T HAVE THE BADGE, THIS SHOULD FAIL!");
+    let receipt = test_env.installment_credit("borrower2");
+    assert!(receipt.result.is_err());
+
+}
+
+/// ## Ground Credit blueprint's guarantee test:
+/// The test will do the following:
+/// 
+/// - Let guarantors pledge and release guarantees for the borrowers.
+/// - Call the guarantees of an overdue borrower, the borrower's exposure is moved to the guarantors.
+/// - Declare a default on a guarantor who didn't repay the charged debt.
+#[test]
+fn test_guarantee() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_credit_user("borrower1", dec!("30"));
+    test_env.new_credit_user("borrower2", dec!("30"));
+    test_env.new_credit_user("guarantor1", dec!("30"));
+    test_env.new_credit_user("guarantor2", dec!("30"));
+
+    let receipt = test_env.pledge_guarantee("guarantor1", "borrower1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE GUARANTOR CANNOT GUARANTEE ITS OWN CREDIT, THIS SHOULD FAIL!");
+    let receipt = test_env.pledge_guarantee("guarantor2", "guarantor2", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    println!("THE PLEDGE IS MORE THAN THE GUARANTOR'S ALLOWANCE, THIS SHOULD FAIL!");
+    let receipt = test_env.pledge_guarantee("guarantor2", "borrower1", dec!("31"));
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.pledge_guarantee("guarantor2", "borrower2", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE BORROWER HAS NO DEBT, THE GUARANTEE CAN BE RELEASED");
+    let receipt = test_env.release_guarantee("guarantor2");
+    assert!(receipt.result.is_ok());
+
+    let receipt = test_env.pledge_guarantee("guarantor2", "borrower1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE GUARANTEES RAISE THE BORROWER'S CREDIT BY 20 STABLE COINS");
+    let receipt = test_env.revolving_credit("borrower1", dec!("45"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE BORROWER STILL HAS DEBT, THIS SHOULD FAIL!");
+    let receipt = test_env.release_guarantee("guarantor1");
+    assert!(receipt.result.is_err());
+
+    println!("THE BORROWER IS NOT OVERDUE, THIS SHOULD FAIL!");
+    let receipt = test_env.call_guarantee("borrower1");
+    assert!(receipt.result.is_err());
+
+    let borrower = test_env.get_id("borrower1");
+    let guarantor1 = test_env.get_id("guarantor1");
+    let guarantor2 = test_env.get_id("guarantor2");
+
+    let borrower_headroom = test_env.exposure_headroom(borrower.clone(), test_env.id_sbt).unwrap().borrower;
+    let guarantor_headroom = test_env.exposure_headroom(guarantor1.clone(), test_env.id_sbt).unwrap().borrower;
+
+    test_env.update_neuracle_time(Some("12592001".to_string()));
+
+    let receipt = test_env.call_guarantee("borrower1");
+    assert!(receipt.result.is_ok());
+
+    println!("THE BORROWER'S EXPOSURE HAS BEEN MOVED TO THE GUARANTORS");
+    let new_borrower_headroom = test_env.exposure_headroom(borrower, test_env.id_sbt).unwrap().borrower;
+    let new_guarantor_headroom = test_env.exposure_headroom(guarantor1, test_env.id_sbt).unwrap().borrower;
+    assert!(new_borrower_headroom > borrower_headroom && new_guarantor_headroom < guarantor_headroom);
+    assert!(test_env.exposure_headroom(guarantor2.clone(), test_env.id_sbt).unwrap().borrower == new_guarantor_headroom);
+
+    println!("THE GUARANTEES HAVE BEEN CALLED, THIS SHOULD FAIL!");
+    let receipt = test_env.call_guarantee("borrower1");
+    assert!(receipt.result.is_err());
+
+    println!("THE CALLED GUARANTEE BADGE IS BURNT ON RELEASE");
+    let receipt = test_env.release_guarantee("guarantor1");
+    assert!(receipt.result.is_ok());
+
+    println!("THE GUARANTOR HAS REPAID THE CHARGED DEBT, THE EXPOSURE IS RELEASED");
+    test_env.repay_full("guarantor2");
+    let no_debt = test_env.get_id("borrower2");
+    let no_debt_headroom = test_env.exposure_headroom(no_debt, test_env.id_sbt).unwrap().borrower;
+    assert!(test_env.exposure_headroom(guarantor2, test_env.id_sbt).unwrap().borrower == no_debt_headroom);
+
+    println!("THE GUARANTOR DIDN'T REPAY THE CHARGED DEBT, THE LOAN CAN BE DECLARED DEFAULTED");
+    test_env.update_neuracle_time(Some((12592001u64 + 2592000 + 86400 * 91).to_string()));
+
+    let receipt = test_env.declare_default("guarantor1");
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Lending blueprint's delinquent loan and compensation test:
+/// The test will do the following:
+/// 
+/// - Go through all the blueprint's functions.
+/// - Go through a concept consensus round in the Ground Business DAO to let the protocol use the dao's treasury.
+/// - Create lenders and fund some stable coin into the protocol.
+/// - Let delinquent credit users take about 40% the fund.
+/// - Lenders try to withdraw 60% left.
+/// - Lenders try to get the compensation. (compensation rate is 50%, you can change this amount in the TestEngine.)
+/// 
+/// Testers can edit the params in the test.
+#[test]
+fn test_compensation() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    // test_env.new_dao_member("DAO_member", 8u8);
+
+    // let methods = Methods {methods: vec![Method {
+
+    //     component: test_env.ground_lending,
+
+    //     method: "use_dao".to_string(),
+  
+    //     args: vec![scrypto_encode(&test_env.dao)],
+
+    // }]};
+
+    // test_env.proof_of_concept_consensus(methods);
+
+    test_env.lending_use_dao();
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_lender("lender2", dec!("10000"));
+
+    test_env.new_credit_user("delinquent_borrower", dec!("10000"));
+
+    test_env.update_neuracle_time(Some("11300000".to_string()));
+
+    let receipt = test_env.revolving_credit("delinquent_borrower", dec!("7999"));
+
+    assert!(receipt.result.is_ok());
+
+    test_env.withdraw_all("lender1");
+
+    test_env.withdraw_fail("lender2");
+
+    test_env.withdraw("lender2", dec!("2001"));
+
+    test_env.compensation("lender2")
+
+}
+
+/// ## Ground Lending blueprint's loan default and recovery test:
+/// The test will do the following:
+/// 
+/// - Let a borrower take a loan and miss the repayment past the protocol's default policy.
+/// - Check a loan can only be declared defaulted when it was taken from the protocol.
+/// - Check the DAO's automatic compensation is repaid from the borrower's later repayment.
+/// - Check the borrower's credit can be used again after the defaulted debt is repaid in full.
+#[test]
+fn test_default_and_recovery() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.lending_use_dao();
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_credit_user("borrower1", dec!("1000"));
+
+    test_env.new_credit_user("borrower2", dec!("1000"));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("1000"));
+    assert!(receipt.result.is_ok());
+
+    println!("THE LOAN IS NOT OVERDUE YET, THIS SHOULD FAIL!");
+    let receipt = test_env.declare_default("borrower1");
+    assert!(receipt.result.is_err());
+
+    println!("ADVANCE 121 DAYS, THE BORROWER IS 91 DAYS OVERDUE");
+    test_env.update_neuracle_time(Some("20454400".to_string()));
+
+    println!("BORROWER 2 DOESN'T HAVE A LOAN FROM THE PROTOCOL, THIS SHOULD FAIL!");
+    let receipt = test_env.declare_default("borrower2");
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.declare_default("borrower1");
+    assert!(receipt.result.is_ok());
+
+    assert!(test_env.dao_payouts() > Decimal::ZERO);
+
+    println!("THE CREDIT IS DEFAULTED, THIS SHOULD FAIL!");
+    let receipt = test_env.revolving_credit("borrower1", dec!("10"));
+    assert!(receipt.result.is_err());
+
+    test_env.repay_full("borrower1");
+
+    assert!(test_env.dao_payouts() == Decimal::ZERO);
+
+    let receipt = test_env.declare_default("borrower1");
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("10"));
+    assert!(receipt.result.is_ok());
+
+}
+
+/// ## Ground Lending blueprint's debt claim test:
+/// The test will do the following:
+/// 
+/// - Let a borrower default on a primary stablecoin loan and a market loan after a late partial repayment left an extra debt.
+/// - Package the defaulted position as a debt claim, the borrower's repayments are recovered to the market's lenders until the claim is sold.
+/// - Sell the claim and route the borrower's later repayments, the extra debt included, to the claim holder.
+/// - Close the claim route when the defaulted debt is repaid in full.
+#[test]
+fn test_debt_claim() {
+
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
 
-    println!("USER'S MAXIMUM CREDIT: {}, CURRENT ALLOWANCE: {}", maximum_credit, current_allowance);
+    test_env.new_lender("lender1", dec!("5000"));
 
-    println!("TEST CHANGE CREDIT TYPE METHOD");
-    let receipt = test_env.change_credit_type("credit_user");
+    test_env.lend_asset("lender1", dec!("1000"));
 
-    assert!(receipt.result.is_ok()); 
+    test_env.new_business_user("business1", dec!("30"));
 
-    println!("GET USER'S REVOLVING CREDIT AMOUNT");
+    let id = test_env.get_id("business1");
 
-    let mut receipt = test_env.get_revolving_credit_amount("credit_user");
+    let receipt = test_env.asset_credit("business1", dec!("10"));
+    assert!(receipt.result.is_ok());
 
+    let receipt = test_env.revolving_credit("business1", dec!("10"));
     assert!(receipt.result.is_ok());
 
-    let (maximum_credit, current_allowance): (Decimal, Decimal) = return_of_call_method(&mut receipt, "get_revolving_credit_amount");
+    println!("THE BORROWER IS LATE AND ONLY REPAY A PART, THE LATE PENALTY IS LEFT AS EXTRA DEBT");
+    test_env.update_neuracle_time(Some((10000000u64 + 2592000 + 86400).to_string()));
+    test_env.repay_part("business1", dec!("1"));
 
-    println!("USER'S MAXIMUM CREDIT: {}, CURRENT ALLOWANCE: {}", maximum_credit, current_allowance);
+    println!("ADVANCE 121 DAYS, THE BORROWER IS 91 DAYS OVERDUE");
+    test_env.update_neuracle_time(Some("20454400".to_string()));
 
-    println!("CREATE OTHER CREDIT USER WITH MAXIMUM MONTHLY ALLOWANCE IS 160 STABLECOINS. THE USER HAS INCOME 3000 AND TRUST SCORE AT 80.");
-    test_env.new_dao_member("credit_user2", 8u8);
+    let receipt = test_env.declare_default("business1");
+    assert!(receipt.result.is_ok());
 
-    println!("GET USER'S REVOLVING CREDIT AMOUNT");
+    let health = test_env.credit_health(id.clone(), test_env.id_sbt).unwrap();
+    assert!(health.defaulted && health.extra_debt > Decimal::ZERO);
 
-    let mut receipt = test_env.get_revolving_credit_amount("credit_user2");
+    test_env.auction_debt_claim("business1", dec!("10"), dec!("5"), 86400);
 
+    println!("THE CLAIM HASN'T BEEN SOLD, THE REPAYMENT IS RECOVERED TO THE MARKET'S LENDERS");
+    let (value, _, _, _) = test_env.asset_market();
+    let receipt = test_env.repay_asset("business1", dec!("5"));
     assert!(receipt.result.is_ok());
+    let (value_after, _, _, _) = test_env.asset_market();
+    assert!(value_after == value + dec!("5"));
 
-    let (maximum_credit, current_allowance): (Decimal, Decimal) = return_of_call_method(&mut receipt, "get_revolving_credit_amount");
+    println!("THE PAYMENT IS BELOW THE AUCTION'S CURRENT PRICE, THIS SHOULD FAIL!");
+    let receipt = test_env.buy_debt_claim("lender1", 0, dec!("9"));
+    assert!(receipt.result.is_err());
 
-    println!("USER'S MAXIMUM CREDIT: {}, CURRENT ALLOWANCE: {}", maximum_credit, current_allowance);
+    let receipt = test_env.buy_debt_claim("lender1", 0, dec!("10"));
+    assert!(receipt.result.is_ok());
 
-    println!("GO THROUGH INSTALLMENT CREDIT PROCESS");
-    test_env.get_installment_credit("credit_user2", dec!("1000000"), 7u8);
+    println!("THE CLAIM HAS ALREADY BEEN SOLD, THIS SHOULD FAIL!");
+    let receipt = test_env.buy_debt_claim("lender1", 0, dec!("10"));
+    assert!(receipt.result.is_err());
 
-    println!("CHECK BLACKLIST FUNCTION");
-    test_env.black_list("credit_user2");
+    println!("THE REPAYMENT AND THE EXTRA DEBT PAID FROM ITS SURPLUS ARE ROUTED TO THE CLAIM HOLDER");
+    let receipt = test_env.repay_asset("business1", dec!("100"));
+    assert!(receipt.result.is_ok());
+    let (value, _, _, _) = test_env.asset_market();
+    assert!(value == value_after);
+    let health = test_env.credit_health(id.clone(), test_env.id_sbt).unwrap();
+    assert!(health.defaulted && health.extra_debt == Decimal::ZERO);
 
-    let receipt = test_env.get_revolving_credit_amount("credit_user2");
+    let receipt = test_env.claim_debt_repayments("lender1");
+    assert!(receipt.result.is_ok());
 
+    println!("THE BORROWER DOESN'T HOLD THE CLAIM, THIS SHOULD FAIL!");
+    let receipt = test_env.claim_debt_repayments("business1");
     assert!(receipt.result.is_err());
 
+    test_env.repay_full("business1");
+    let health = test_env.credit_health(id, test_env.id_sbt).unwrap();
+    assert!(!health.defaulted && health.drawn == Decimal::ZERO);
+
+    let receipt = test_env.claim_debt_repayments("lender1");
+    assert!(receipt.result.is_ok());
+
 }
 
-/// ## Ground Lending blueprint's lender badge pattern test:
+/// ## Ground Lending blueprint's insurance reserve test:
 /// The test will do the following:
 /// 
-/// - Go through all the blueprint's functions.
-/// - Test the Ground Lending blueprint's methods for lenders. 
-/// 
-/// Testers can edit the params in the test.
+/// - Feed the insurance reserve by the reserve factor of a borrower's repaid interest.
+/// - Cover a defaulted loan from the insurance reserve before writing off the lenders.
 #[test]
-fn test_lender_badge_pattern() {
+fn test_insurance_reserve() {
 
     let mut ledger = InMemorySubstateStore::with_bootstrap();
     let env = TestEnv::new(&mut ledger);
     let mut test_env = new_test_env(env);
 
-    test_env.update_neuracle_time(None);
+    test_env.update_neuracle_time(Some("10000000".to_string()));
 
-    println!("TEST LENDING PROTOCOL'S LENDER BADGE PATTERN");
-    test_env.new_lender("lender1", dec!("264"));
+    test_env.change_reserve_factor(dec!("20"));
 
-    test_env.withdraw("lender1", dec!("34"));
+    test_env.new_lender("lender1", dec!("5000"));
 
-    test_env.withdraw_all("lender1");
+    test_env.new_credit_user("borrower1", dec!("30"));
+
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
+
+    test_env.update_neuracle_time(Some("11000000".to_string()));
+
+    println!("THE RESERVE TAKE 20% OF THE REPAID INTEREST");
+    test_env.repay_full("borrower1");
+    let (reserve, _, _) = test_env.reserve_coverage();
+    assert!(reserve > Decimal::ZERO);
+
+    test_env.deposit_reserve("lender1", dec!("2000"));
+
+    test_env.new_credit_user("borrower2", dec!("1000"));
+
+    let receipt = test_env.revolving_credit("borrower2", dec!("1000"));
+    assert!(receipt.result.is_ok());
+
+    let (reserve, outstanding, coverage) = test_env.reserve_coverage();
+    assert!(outstanding > Decimal::ZERO && outstanding <= dec!("1000") && coverage > dec!("200"));
+
+    println!("ADVANCE 121 DAYS, THE BORROWER IS 91 DAYS OVERDUE");
+    test_env.update_neuracle_time(Some("21454400".to_string()));
+
+    let receipt = test_env.declare_default("borrower2");
+    assert!(receipt.result.is_ok());
+
+    println!("THE RESERVE COVER THE LOSS, THE LENDER ISN'T WRITTEN OFF");
+    let (reserve_after, _, _) = test_env.reserve_coverage();
+    assert!(reserve_after < reserve);
+    let (return1, _) = test_env.account_return("lender1");
+    assert!(return1 > dec!("5000"));
 
 }
 
-/// ## Ground Lending blueprint's revolving credit and interest for lenders test:
+/// ## Ground Lending blueprint's tranches test:
 /// The test will do the following:
 /// 
-/// - Go through all the blueprint's functions.
-/// - Create lenders and fund some stable coin into the protocol.
-/// - Test "Consumer-level" credit for borrowers: All the Ground Lending blueprint's methods for revolving credit borrowers, included late-repayment test.
-/// - Test the Automatic Credit Scoring Mechanism.
-/// - Test "Bank level" earning tracker for lenders: Compare the return of same lending account in amount but different in start - end time.
-/// 
-/// Testers can edit the params in the test.
+/// - Let a senior lender and a junior lender lend into the protocol.
+/// - Write off a defaulted loan from the junior tranche first, the senior lender isn't written off.
+/// - Return the borrower's late repayment to the written off junior lender.
 #[test]
-fn test_revolving_credit_and_interest() {
-
-    println!("THIS TEST WILL BE REALLY COMPLEX AND TAKE A LONG TIME, PLEASE BE PATIENT.");
+fn test_tranches() {
 
     let mut ledger = InMemorySubstateStore::with_bootstrap();
     let env = TestEnv::new(&mut ledger);
     let mut test_env = new_test_env(env);
-    
-    test_env.update_neuracle_time(Some("10000000".to_string()));
-
-    test_env.new_lender("lender1", dec!("9564"));
 
-    test_env.update_neuracle_time(Some("10052635".to_string()));
+    test_env.update_neuracle_time(Some("10000000".to_string()));
 
-    println!("NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 30");
-    test_env.new_credit_user("borrower1", dec!("30"));
+    test_env.new_lender("lender1", dec!("5000"));
 
-    println!("LET BORROWER 1 TAKE LOAN");
+    test_env.new_junior_lender("lender2", dec!("2000"));
 
-    let receipt = test_env.revolving_credit("borrower1", dec!("21"));
+    test_env.new_credit_user("borrower1", dec!("1000"));
 
+    let receipt = test_env.revolving_credit("borrower1", dec!("1000"));
     assert!(receipt.result.is_ok());
 
-    println!("LET BORROWER 1 TAKE MORE THAN HIS CAPABILITY");
+    println!("ADVANCE 121 DAYS, THE BORROWER IS 91 DAYS OVERDUE");
+    test_env.update_neuracle_time(Some("20454400".to_string()));
 
-    let receipt = test_env.revolving_credit("borrower1", dec!("644"));
+    let receipt = test_env.declare_default("borrower1");
+    assert!(receipt.result.is_ok());
 
-    assert!(receipt.result.is_err());
-    
-    test_env.update_neuracle_time(Some("10152356".to_string()));
+    println!("THE JUNIOR TRANCHE ABSORB THE LOSS FIRST");
+    let (return1, _) = test_env.account_return("lender1");
+    let (return2, _) = test_env.account_return("lender2");
+    assert!(return1 == dec!("5000") && return2 < dec!("2000"));
 
-    test_env.new_lender("lender2", dec!("9564"));
+    println!("THE BORROWER REPAY THE DEFAULTED LOAN, THE RECOVERY GO BACK TO THE JUNIOR LENDER");
+    test_env.repay_full("borrower1");
+    let (recovered, _) = test_env.account_return("lender2");
+    assert!(recovered > return2);
 
-    test_env.update_neuracle_time(Some("10356952".to_string()));
+}
 
-    println!("NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 112");
-    test_env.new_credit_user("borrower2", dec!("112"));
+/// ## Ground Lending blueprint's credit delegation test:
+/// The test will do the following:
+/// 
+/// - Let a lender delegate part of the lending amount to a borrower and the borrower draw from it.
+/// - Check the borrower cannot draw a revoked delegation.
+/// - Check the drawn part is released back to the lender's account when the borrower repay it.
+#[test]
+fn test_credit_delegation() {
 
-    println!("LET BORROWER 2 TAKE MORE THAN HIS CAPABILITY");
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
 
-    let receipt = test_env.revolving_credit("borrower2", dec!("113"));
+    test_env.update_neuracle_time(Some("10000000".to_string()));
 
-    assert!(receipt.result.is_err());
+    test_env.new_lender("lender1", dec!("10000"));
 
-    println!("LET BORROWER 2 TAKE LOAN");
+    test_env.new_credit_user("borrower1", dec!("30"));
 
-    let receipt = test_env.revolving_credit("borrower2", dec!("112"));
+    let delegation_id = test_env.delegate_credit("lender1", "borrower1", dec!("1000"));
 
+    let receipt = test_env.delegated_credit("borrower1", delegation_id, dec!("400"));
     assert!(receipt.result.is_ok());
 
-    // NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 3521
-    test_env.new_credit_user("borrower3", dec!("3521"));
-
-    println!("LET BORROWER 3 TAKE LOAN");
-
-    let receipt = test_env.revolving_credit("borrower3", dec!("3453"));
-
+    let receipt = test_env.revoke_delegation("lender1", delegation_id);
     assert!(receipt.result.is_ok());
 
-    println!("LET BORROWER 3 TAKE MORE THAN HIS CAPABILITY");
-
-    let receipt = test_env.revolving_credit("borrower3", dec!("2236"));
-
+    println!("THE DELEGATION HAS BEEN REVOKED, THIS SHOULD FAIL!");
+    let receipt = test_env.delegated_credit("borrower1", delegation_id, dec!("100"));
     assert!(receipt.result.is_err());
 
-    println!("TEST CHANGE CREDIT TYPE WHEN USER HAS A DEBT. THIS SHOULD FAIL");
-
-    let receipt = test_env.change_credit_type("borrower3");
-
-    assert!(receipt.result.is_err()); 
-
-    println!("LET BORROWER 3 TAKE LOAN");
+    println!("THE DRAWN PART IS STILL DELEGATED, THIS SHOULD FAIL!");
+    test_env.withdraw_fail("lender1");
 
-    let receipt = test_env.revolving_credit("borrower3", dec!("6"));
+    test_env.update_neuracle_time(Some("10086400".to_string()));
 
-    assert!(receipt.result.is_ok());
+    test_env.repay_delegated_credit("borrower1", delegation_id, dec!("1000"));
 
-    println!("LET BORROWER 1 AND 2 REPAY PART OF THE LOAN");
+    println!("THE DELEGATION HAS BEEN CLOSED, THIS SHOULD FAIL!");
+    let receipt = test_env.env.call_method(test_env.ground_lending, "get_delegation", vec![scrypto_encode(&delegation_id)]);
+    assert!(receipt.result.is_err());
 
-    test_env.repay_part("borrower1", dec!("9"));
+    test_env.withdraw_all("lender1");
 
-    test_env.repay_part("borrower2", dec!("65"));
+}
 
-    println!("LET BORROWER 3 REPAY FULL OF THE LOAN");
+/// ## Ground Lending blueprint's delegated loan write-off test:
+/// The test will do the following:
+/// 
+/// - Write off an overdue delegated loan, the loss is taken only from the delegating lender.
+/// - Keep the written-off debt on the delegation and recover the borrower's later repayment to the delegating lender.
+#[test]
+fn test_write_off_delegation() {
 
-    test_env.repay_full("borrower3");
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
 
-    test_env.update_neuracle_time(Some("11321546".to_string()));
+    test_env.update_neuracle_time(Some("10000000".to_string()));
 
-    println!("LET BORROWER 1 REPAY FULL OF THE LOAN");
+    test_env.new_lender("lender1", dec!("10000"));
 
-    test_env.repay_full("borrower1");
+    test_env.new_credit_user("borrower1", dec!("30"));
 
-    println!("LET LENDER 2 WITHDRAW ALL HIS ACCOUNT");
+    let delegation_id = test_env.delegate_credit("lender1", "borrower1", dec!("1000"));
 
-    test_env.update_neuracle_time(Some("13513562".to_string()));
+    let receipt = test_env.delegated_credit("borrower1", delegation_id, dec!("400"));
+    assert!(receipt.result.is_ok());
 
-    test_env.withdraw_all("lender2");
+    println!("THE DELEGATED LOAN IS NOT OVERDUE, THIS SHOULD FAIL!");
+    let receipt = test_env.write_off_delegation(delegation_id);
+    assert!(receipt.result.is_err());
 
-    test_env.update_neuracle_time(Some("13795623".to_string()));
+    test_env.update_neuracle_time(Some((10000000u64 + 2592000 + 86400 * 90).to_string()));
 
-    println!("LET BORROWER 2 REPAY FULL OF THE LOAN");
-    test_env.repay_full("borrower2");
+    let receipt = test_env.write_off_delegation(delegation_id);
+    assert!(receipt.result.is_ok());
 
-    println!("LET BORROWER 1 TAKE LOAN");
+    println!("THE DRAWN AMOUNT IS WRITTEN OFF AND THE UNDRAWN ALLOWANCE IS RELEASED BACK TO THE LENDER");
+    let (amount, _) = test_env.account_return("lender1");
+    assert!(amount == dec!("9600"));
 
-    let receipt = test_env.revolving_credit("borrower1", dec!("30"));
+    println!("THE DEBT IS KEPT ON THE DELEGATION");
+    let delegation = test_env.get_delegation(delegation_id);
+    assert!(delegation.defaulted && delegation.drawn == dec!("400") && delegation.debt_interest == dec!("20"));
 
-    assert!(receipt.result.is_ok());
+    println!("THE DELEGATED LOAN HAS BEEN WRITTEN OFF, THIS SHOULD FAIL!");
+    let receipt = test_env.write_off_delegation(delegation_id);
+    assert!(receipt.result.is_err());
 
-    println!("LET BORROWER 1 REPAY FULL OF THE LOAN");
+    println!("THE DELEGATED LOAN HAS BEEN WRITTEN OFF, THIS SHOULD FAIL!");
+    let receipt = test_env.delegated_credit("borrower1", delegation_id, dec!("100"));
+    assert!(receipt.result.is_err());
 
-    test_env.repay_full("borrower1");
+    println!("THE BORROWER'S REPAYMENT IS RECOVERED TO THE LENDER");
+    test_env.repay_delegated_credit("borrower1", delegation_id, dec!("1000"));
+    let (amount, _) = test_env.account_return("lender1");
+    assert!(amount == dec!("10020"));
 
-    println!("LET LENDER 1 WITHDRAW ALL HIS ACCOUNT");
+    println!("THE DELEGATION HAS BEEN CLOSED, THIS SHOULD FAIL!");
+    let receipt = test_env.env.call_method(test_env.ground_lending, "get_delegation", vec![scrypto_encode(&delegation_id)]);
+    assert!(receipt.result.is_err());
 
     test_env.withdraw_all("lender1");
 
 }
 
-/// ## Ground Lending blueprint's installment credit test:
+/// ## Ground Checkout blueprint test:
 /// The test will do the following:
 /// 
-/// - Go through all the blueprint's functions.
-/// - Create lenders and fund some stable coin into the protocol.
-/// - Test the Ground Lending blueprint's methods for installment credit borrowers, included late-repayment test. 
-/// - Test the "Tolerance threshold" of the protocol: 
-/// 
-/// Borrowers cannot take a loan after the protocol's vault contain < 60% of the total return amount. (this can be edited on TestEngine blueprint)
-/// 
-/// Testers can edit the params in the test.
+/// - Create a checkout component and list it on the lending protocol.
+/// - Let a buyer pay merchant invoices with the revolving credit.
+/// - Test that a refund reverse exactly the purchase's own debt and interest, and never the buyer's other debt.
+/// - Test that a refund cannot be claimed with a forged Identity SBT.
 #[test]
-fn test_installment_credit() {
+fn test_checkout_refund() {
 
     let mut ledger = InMemorySubstateStore::with_bootstrap();
     let env = TestEnv::new(&mut ledger);
@@ -882,65 +3401,84 @@ fn test_installment_credit() {
 
     test_env.update_neuracle_time(Some("10000000".to_string()));
 
+    let checkout = test_env.init_checkout();
+
     test_env.new_lender("lender1", dec!("10000"));
 
-    test_env.update_neuracle_time(Some("10056231".to_string()));
+    test_env.new_credit_user("buyer", dec!("30"));
 
-    println!("NEW CREDIT USER WITH MONTHLY ALLOWANCE AT 30");
-    test_env.new_credit_user("borrower1", dec!("30"));
-    
-    test_env.get_installment_credit("borrower1", dec!("4100"), 3u8);
+    test_env.new_merchant("merchant", &checkout);
 
-    println!("TEST THE PROTOCOL'S TOLERANCE THRESHOLD, THIS SHOULD FAIL!");
-    let receipt = test_env.installment_credit("borrower1");
+    let invoice_id = test_env.create_invoice("merchant", &checkout, dec!("20"), "buyer");
+
+    let receipt = test_env.pay_invoice("buyer", &checkout, invoice_id);
+    assert!(receipt.result.is_ok());
+
+    println!("THE INVOICE HAS BEEN PAID, THIS SHOULD FAIL!");
+    let receipt = test_env.pay_invoice("buyer", &checkout, invoice_id);
     assert!(receipt.result.is_err());
 
-    test_env.new_lender("lender2", dec!("300"));
+    test_env.settle_invoice("merchant", &checkout, invoice_id);
 
-    test_env.update_neuracle_time(Some("10167622".to_string()));
+    println!("THE PURCHASE HAS TAKEN MOST OF THE CREDIT, THIS SHOULD FAIL!");
+    let receipt = test_env.revolving_credit("buyer", dec!("11"));
+    assert!(receipt.result.is_err());
 
-    let receipt = test_env.installment_credit("borrower1");
+    test_env.refund("merchant", &checkout, dec!("20"));
+
+    println!("THE PURCHASE'S DEBT AND INTEREST HAVE BEEN REVERSED, NOTHING IS KEPT FOR THE BUYER. THIS SHOULD FAIL!");
+    let receipt = test_env.claim_refund("buyer", &checkout);
+    assert!(receipt.result.is_err());
+
+    println!("THE WHOLE CREDIT IS AVAILABLE AGAIN");
+    let receipt = test_env.revolving_credit("buyer", dec!("30"));
     assert!(receipt.result.is_ok());
 
-    test_env.update_neuracle_time(Some("10372546".to_string()));
+    test_env.repay_full("buyer");
 
-    test_env.new_lender("lender3", dec!("10000"));
+    test_env.update_neuracle_time(Some("10086400".to_string()));
 
-    test_env.update_neuracle_time(Some("11653624".to_string()));
+    let invoice_id = test_env.create_invoice("merchant", &checkout, dec!("20"), "buyer");
 
-    test_env.repay_part("borrower1", dec!("352"));
+    let receipt = test_env.pay_invoice("buyer", &checkout, invoice_id);
+    assert!(receipt.result.is_ok());
 
-    test_env.update_neuracle_time(Some("12722659".to_string()));
+    test_env.settle_invoice("merchant", &checkout, invoice_id);
 
-    test_env.repay_part("borrower1", dec!("1351"));
+    test_env.repay_full("buyer");
 
-    test_env.update_neuracle_time(Some("13565695".to_string()));
+    test_env.update_neuracle_time(Some("10172800".to_string()));
 
-    test_env.repay_full("borrower1");
+    let receipt = test_env.revolving_credit("buyer", dec!("5"));
+    assert!(receipt.result.is_ok());
 
-    test_env.update_neuracle_time(Some("14536859".to_string()));
+    test_env.refund("merchant", &checkout, dec!("20"));
 
-    test_env.withdraw_all("lender1");
+    test_env.new_credit_user("thief", dec!("30"));
 
-    test_env.withdraw_all("lender2");
+    let forged_id = test_env.forge_id("thief", "buyer");
 
-    test_env.withdraw_all("lender3");
+    println!("THE IDENTITY SBT IS FORGED, THIS SHOULD FAIL!");
+    let receipt = test_env.claim_refund_with("thief", &checkout, forged_id);
+    assert!(receipt.result.is_err());
+
+    println!("THE BUYER HAS REPAID THE PURCHASE, THE REFUND IS KEPT FOR THE BUYER");
+    let receipt = test_env.claim_refund("buyer", &checkout);
+    assert!(receipt.result.is_ok());
+
+    println!("THE REFUND DIDN'T TOUCH THE NEW DEBT, THIS SHOULD FAIL!");
+    let receipt = test_env.revolving_credit("buyer", dec!("26"));
+    assert!(receipt.result.is_err());
 
 }
 
-/// ## Ground Lending blueprint's delinquent loan and compensation test:
+/// ## Ground Lending blueprint's credit health test:
 /// The test will do the following:
 /// 
-/// - Go through all the blueprint's functions.
-/// - Go through a concept consensus round in the Ground Business DAO to let the protocol use the dao's treasury.
-/// - Create lenders and fund some stable coin into the protocol.
-/// - Let delinquent credit users take about 40% the fund.
-/// - Lenders try to withdraw 60% left.
-/// - Lenders try to get the compensation. (compensation rate is 50%, you can change this amount in the TestEngine.)
-/// 
-/// Testers can edit the params in the test.
+/// - Read the credit health of a wrong Identity SBT resource and an unknown ID.
+/// - Read the credit health of a borrower who is late for more than 255 months.
 #[test]
-fn test_compensation() {
+fn test_credit_health() {
 
     let mut ledger = InMemorySubstateStore::with_bootstrap();
     let env = TestEnv::new(&mut ledger);
@@ -948,40 +3486,74 @@ fn test_compensation() {
 
     test_env.update_neuracle_time(Some("10000000".to_string()));
 
-    // test_env.new_dao_member("DAO_member", 8u8);
+    test_env.new_lender("lender1", dec!("10000"));
 
-    // let methods = Methods {methods: vec![Method {
+    test_env.new_credit_user("borrower1", dec!("30"));
 
-    //     component: test_env.ground_lending,
+    let id = test_env.get_id("borrower1");
 
-    //     method: "use_dao".to_string(),
-  
-    //     args: vec![scrypto_encode(&test_env.dao)],
+    println!("THE CREDIT SBT IS NOT AN IDENTITY SBT, THIS SHOULD RETURN NONE");
+    assert!(test_env.credit_health(id.clone(), test_env.credit_sbt).is_none());
 
-    // }]};
+    println!("THE ID IS NOT ISSUED, THIS SHOULD RETURN NONE");
+    assert!(test_env.credit_health(NonFungibleId::from_u64(123456789), test_env.id_sbt).is_none());
 
-    // test_env.proof_of_concept_consensus(methods);
+    let receipt = test_env.revolving_credit("borrower1", dec!("20"));
+    assert!(receipt.result.is_ok());
 
-    test_env.lending_use_dao();
+    let health = test_env.credit_health(id.clone(), test_env.id_sbt).unwrap();
+    assert!(!health.late && health.drawn == dec!("20"));
 
-    test_env.new_lender("lender1", dec!("10000"));
+    println!("LET THE BORROWER BE LATE FOR 300 MONTHS");
+    test_env.update_neuracle_time(Some((10000000u64 + 2592000 * 301).to_string()));
 
-    test_env.new_lender("lender2", dec!("10000"));
+    let health = test_env.credit_health(id, test_env.id_sbt).unwrap();
+    assert!(health.late && health.projected_late_penalty > Decimal::ZERO);
 
-    test_env.new_credit_user("delinquent_borrower", dec!("10000"));
+}
 
-    test_env.update_neuracle_time(Some("11300000".to_string()));
+/// ## Ground Credit blueprint's business credit test:
+/// The test will do the following:
+/// 
+/// - Let a business take a revolving loan without any business credit terms.
+/// - Link duplicated owners to the business.
+/// - Let the business take a revolving loan under the business credit terms.
+#[test]
+fn test_business_credit() {
 
-    let receipt = test_env.revolving_credit("delinquent_borrower", dec!("7999"));
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let env = TestEnv::new(&mut ledger);
+    let mut test_env = new_test_env(env);
+
+    test_env.update_neuracle_time(Some("10000000".to_string()));
+
+    test_env.new_lender("lender1", dec!("10000"));
+
+    test_env.new_business_user("business1", dec!("30"));
+
+    test_env.new_credit_user("owner1", dec!("30"));
 
+    println!("NO BUSINESS CREDIT TERMS, THE BUSINESS CREDIT IS CALCULATED AS A PERSON'S CREDIT");
+    let receipt = test_env.revolving_credit("business1", dec!("30"));
     assert!(receipt.result.is_ok());
 
-    test_env.withdraw_all("lender1");
+    test_env.repay_full("business1");
 
-    test_env.withdraw_fail("lender2");
+    test_env.change_business_credit_terms(Some(BusinessCreditTerms {
+        revenue_rate: dec!("50"),
+        owner_weight: dec!("50"),
+        owner_degrade_rate: dec!("10")
+    }));
 
-    test_env.withdraw("lender2", dec!("2001"));
+    println!("LINK THE SAME OWNER TWICE, THE OWNER IS ONLY LINKED ONCE");
+    let owners = test_env.link_business_owners("business1", vec!["owner1", "owner1"]);
+    assert!(owners.len() == 1);
 
-    test_env.compensation("lender2")
+    println!("THE BUSINESS CREDIT IS HALVED BY THE REVENUE RATE, THIS SHOULD FAIL!");
+    let receipt = test_env.revolving_credit("business1", dec!("16"));
+    assert!(receipt.result.is_err());
+
+    let receipt = test_env.revolving_credit("business1", dec!("15"));
+    assert!(receipt.result.is_ok());
 
-}
\ No newline at end of file
+}